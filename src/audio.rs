@@ -63,6 +63,7 @@ pub struct Audio<'a> {
     pub songs: HashMap<Song, Music<'a>>,
     pub sounds: HashMap<SoundEffect, Sound<'a>>,
     pub music_volume: f32,
+    pub music_pitch: f32,
     pub sound_effects_volume: f32,
 
     pub sound_effect_cooldowns: HashMap<SoundEffect, f32>,
@@ -76,6 +77,7 @@ impl<'a> Audio<'a> {
             songs: load_songs(rl_audio)?,
             sounds: load_sounds(rl_audio)?,
             music_volume: 1.0,
+            music_pitch: 1.0,
             sound_effects_volume: 1.0,
             sound_effect_cooldowns: HashMap::new(),
         })
@@ -91,6 +93,7 @@ impl<'a> Audio<'a> {
         self.current_song = Some(song);
         if let Some(music) = self.songs.get_mut(&song) {
             music.set_volume(self.music_volume);
+            music.set_pitch(self.music_pitch);
             music.play_stream();
         }
     }
@@ -174,6 +177,16 @@ impl<'a> Audio<'a> {
         }
     }
 
+    /// Sets the playback speed for all music tracks and updates the currently playing one.
+    pub fn set_music_pitch(&mut self, pitch: f32) {
+        self.music_pitch = pitch;
+        if let Some(song) = self.current_song {
+            if let Some(music) = self.songs.get_mut(&song) {
+                music.set_pitch(self.music_pitch);
+            }
+        }
+    }
+
     /// Sets the volume for all sound effects.
     pub fn set_sfx_volume(&mut self, volume: f32) {
         self.sound_effects_volume = volume.clamp(0.0, 1.0);
@@ -0,0 +1,196 @@
+/* Neighbor-aware tile sprites.
+   Every tile keeps a 4-bit mask of which cardinal neighbors belong to the same family.
+   Stage::set_tile refreshes the mask for the changed tile and its direct neighbors only,
+   so rendering just reads it back.
+*/
+
+use crate::{
+    sprite::Sprite,
+    stage::{Stage, TileData, TileLayer},
+    tile::{get_tile_sprite, Tile},
+};
+
+pub const NORTH: u8 = 1;
+pub const EAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const WEST: u8 = 8;
+
+/// Mask bit and grid offset for each cardinal direction.
+pub const MASK_DIRECTIONS: [(u8, i32, i32); 4] =
+    [(NORTH, 0, -1), (EAST, 1, 0), (SOUTH, 0, 1), (WEST, -1, 0)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileFamily {
+    Wall,
+    Water,
+    Rail,
+}
+
+/// Tiles in the same family connect to each other visually. Doors sit inside walls.
+pub fn tile_family(tile: Tile) -> Option<TileFamily> {
+    match tile {
+        Tile::Wall | Tile::Door => Some(TileFamily::Wall),
+        Tile::Water => Some(TileFamily::Water),
+        Tile::Rail | Tile::RailSwitch | Tile::Station | Tile::Crossing => Some(TileFamily::Rail),
+        _ => None,
+    }
+}
+
+fn layer_tile_family(stage: &Stage, layer: TileLayer, x: usize, y: usize) -> Option<TileFamily> {
+    stage
+        .get_layer_tile(layer, x, y)
+        .and_then(|td| tile_family(td.tile))
+}
+
+/// Builds the connection mask for a tile from its four neighbors on the same layer.
+pub fn compute_mask(stage: &Stage, layer: TileLayer, x: usize, y: usize) -> u8 {
+    let family = match layer_tile_family(stage, layer, x, y) {
+        Some(family) => family,
+        None => return 0,
+    };
+
+    let mut mask = 0;
+    for (bit, dx, dy) in MASK_DIRECTIONS {
+        let nx = x as i32 + dx;
+        let ny = y as i32 + dy;
+        if nx < 0 || ny < 0 {
+            continue;
+        }
+        if layer_tile_family(stage, layer, nx as usize, ny as usize) == Some(family) {
+            mask |= bit;
+        }
+    }
+    mask
+}
+
+/// Recomputes the mask of the tile at (x, y) and its cardinal neighbors on one layer.
+pub fn refresh_masks_around(stage: &mut Stage, layer: TileLayer, x: usize, y: usize) {
+    let mut targets = vec![(x as i32, y as i32)];
+    for (_, dx, dy) in MASK_DIRECTIONS {
+        targets.push((x as i32 + dx, y as i32 + dy));
+    }
+
+    for (tx, ty) in targets {
+        if tx < 0 || ty < 0 {
+            continue;
+        }
+        let mask = compute_mask(stage, layer, tx as usize, ty as usize);
+        if let Some(tile_data) = stage.get_layer_tile_mut(layer, tx as usize, ty as usize) {
+            tile_data.mask = mask;
+        }
+    }
+}
+
+fn connection_count(mask: u8) -> u32 {
+    (mask & (NORTH | EAST | SOUTH | WEST)).count_ones()
+}
+
+/// Picks the rail piece for a mask. The curve sprite connects south and east unrotated.
+fn rail_layers(tile_data: &TileData) -> Vec<(Sprite, f32)> {
+    let mask = tile_data.mask;
+    match connection_count(mask) {
+        4 => {
+            return vec![
+                (Sprite::Rail, 0.0),
+                (Sprite::Rail, 90.0),
+                (Sprite::RailCrossing, 0.0),
+            ]
+        }
+        // a junction: the line running through, with a curve peeling off toward the branch
+        3 => {
+            let (through, branch) = if mask & (NORTH | SOUTH) == NORTH | SOUTH {
+                (0.0, if mask & EAST != 0 { 0.0 } else { 90.0 })
+            } else {
+                (90.0, if mask & NORTH != 0 { 270.0 } else { 0.0 })
+            };
+            return vec![(Sprite::Rail, through), (Sprite::RailCurve, branch)];
+        }
+        _ => {}
+    }
+
+    match mask {
+        m if m == SOUTH | EAST => vec![(Sprite::RailCurve, 0.0)],
+        m if m == SOUTH | WEST => vec![(Sprite::RailCurve, 90.0)],
+        m if m == NORTH | WEST => vec![(Sprite::RailCurve, 180.0)],
+        m if m == NORTH | EAST => vec![(Sprite::RailCurve, 270.0)],
+        m if m & (EAST | WEST) != 0 => vec![(Sprite::Rail, 90.0)],
+        m if m & (NORTH | SOUTH) != 0 => vec![(Sprite::Rail, 0.0)],
+        // a lone piece keeps whatever way it was laid
+        _ => vec![(Sprite::Rail, tile_data.rot)],
+    }
+}
+
+/// Returns every sprite to draw for a tile, bottom to top, with rotations in degrees.
+pub fn get_autotile_layers(tile_data: &TileData) -> Vec<(Sprite, f32)> {
+    let base = match get_tile_sprite(tile_data) {
+        Some(sprite) => sprite,
+        None => return vec![],
+    };
+    let mask = tile_data.mask;
+
+    match tile_data.tile {
+        Tile::Rail => rail_layers(tile_data),
+        Tile::RailSwitch => {
+            // the lever flips over when the switch is thrown
+            let mut layers = rail_layers(tile_data);
+            let lever_rot = if tile_data.switched { 180.0 } else { 0.0 };
+            layers.push((Sprite::RailSwitch, lever_rot));
+            layers
+        }
+        Tile::Crossing => {
+            // the barrier arm lies along the track when lowered and stands up when raised
+            let mut layers = rail_layers(tile_data);
+            let along_track = if mask & (NORTH | SOUTH) != 0 && mask & (EAST | WEST) == 0 {
+                90.0
+            } else {
+                0.0
+            };
+            let barrier_rot = if tile_data.switched {
+                along_track
+            } else {
+                along_track + 270.0
+            };
+            layers.push((Sprite::TrainCarBlockPole, barrier_rot));
+            let sign = if tile_data.switched && tile_data.variant == 0 {
+                Sprite::TrainBlinkensignLit
+            } else {
+                Sprite::TrainBlinkensign
+            };
+            layers.push((sign, 0.0));
+            layers
+        }
+        Tile::Station => {
+            // platforms run alongside the track
+            let mut layers = rail_layers(tile_data);
+            let platform_rot = if mask & (EAST | WEST) != 0 { 90.0 } else { 0.0 };
+            layers.push((Sprite::Station, platform_rot));
+            layers
+        }
+        Tile::Wall => {
+            // exposed sides get a lit edge so wall runs read as one solid piece
+            let mut layers = vec![(base, tile_data.rot)];
+            for (bit, rot) in [(NORTH, 0.0), (EAST, 90.0), (SOUTH, 180.0), (WEST, 270.0)] {
+                if mask & bit == 0 {
+                    layers.push((Sprite::WallEdge, rot));
+                }
+            }
+            layers
+        }
+        Tile::Water => {
+            // fill the rounded corners wherever the water keeps going
+            let mut layers = vec![(base, tile_data.rot)];
+            for (bits, rot) in [
+                (NORTH | WEST, 0.0),
+                (NORTH | EAST, 90.0),
+                (SOUTH | EAST, 180.0),
+                (SOUTH | WEST, 270.0),
+            ] {
+                if mask & bits == bits {
+                    layers.push((Sprite::WaterCorner, rot));
+                }
+            }
+            layers
+        }
+        _ => vec![(base, tile_data.rot)],
+    }
+}
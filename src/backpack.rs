@@ -0,0 +1,127 @@
+/* Backpack.
+   Past the hotbar the player's inventory goes on into a backpack, which only shows up in its
+   own panel. The panel lays every slot out as a grid with the hotbar as its top row, so things
+   get onto the hotbar by picking them up in one slot and putting them down in another.
+   Slots can be marked as favorites, which keeps pickups from landing in them or swapping them
+   out, and keeps sorting from moving them. Sorting merges stacks and packs the rest in from
+   the first slot, and quick-stacking empties whatever nearby chests already hold into them.
+   Like the shop, the panel pauses the game while it's open.
+*/
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    inventory::{SortKey, HOTBAR_SLOTS},
+    state::{Mode, State},
+};
+
+/// The grid is as wide as the hotbar, so the hotbar is the top row.
+pub const BACKPACK_COLUMNS: usize = HOTBAR_SLOTS;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BackpackView {
+    pub cursor: usize,
+    /// The slot picked up to be put down elsewhere. It stays where it is until then.
+    pub held: Option<usize>,
+    pub sort_key: SortKey,
+}
+
+impl BackpackView {
+    pub fn new() -> BackpackView {
+        BackpackView {
+            cursor: 0,
+            held: None,
+            sort_key: SortKey::Type,
+        }
+    }
+}
+
+pub fn open_backpack(state: &mut State) {
+    state.backpack_view.cursor = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .map_or(0, |player| player.inventory.selected_index);
+    state.backpack_view.held = None;
+    state.mode = Mode::Backpack;
+}
+
+pub fn close_backpack(state: &mut State) {
+    state.backpack_view.held = None;
+    state.mode = Mode::Playing;
+}
+
+/// Moves the cursor by a number of columns and rows, wrapping around within the grid.
+pub fn move_cursor(state: &mut State, dx: i32, dy: i32) {
+    let capacity = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+    {
+        Some(player) => player.inventory.capacity,
+        None => return,
+    };
+    let columns = BACKPACK_COLUMNS as i32;
+    let rows = capacity.div_ceil(BACKPACK_COLUMNS) as i32;
+    let cursor = state.backpack_view.cursor as i32;
+    let mut column = (cursor % columns + dx).rem_euclid(columns);
+    let row = (cursor / columns + dy).rem_euclid(rows);
+    // the last row can be short
+    let row_len = (capacity as i32 - row * columns).min(columns);
+    if column >= row_len {
+        column = if dx > 0 { 0 } else { row_len - 1 };
+    }
+    state.backpack_view.cursor = (row * columns + column) as usize;
+}
+
+/// Picks up the stack under the cursor, or puts a held one down there, topping up or swapping
+/// with whatever is in the way.
+pub fn pick_or_place(state: &mut State, audio: &mut Audio) {
+    let cursor = state.backpack_view.cursor;
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        Some(player) => player,
+        None => return,
+    };
+    match state.backpack_view.held {
+        None => {
+            if player.inventory.get(cursor).is_some() {
+                state.backpack_view.held = Some(cursor);
+                audio.play_sound_effect(SoundEffect::Confirm);
+            } else {
+                audio.play_sound_effect(SoundEffect::CantUse);
+            }
+        }
+        Some(held) => {
+            state.backpack_view.held = None;
+            if held != cursor {
+                if player.inventory.move_slot(held, cursor) {
+                    audio.play_sound_effect(SoundEffect::Drop);
+                } else {
+                    audio.play_sound_effect(SoundEffect::CantUse);
+                }
+            }
+        }
+    }
+}
+
+pub fn toggle_favorite(state: &mut State) {
+    let cursor = state.backpack_view.cursor;
+    if let Some(player) = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        player.inventory.toggle_favorite(cursor);
+    }
+}
+
+pub fn sort_backpack(state: &mut State, audio: &mut Audio) {
+    let sort_key = state.backpack_view.sort_key;
+    if let Some(player) = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        player.inventory.sort(sort_key);
+        state.backpack_view.held = None;
+        audio.play_sound_effect(SoundEffect::Confirm);
+    }
+}
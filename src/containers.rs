@@ -0,0 +1,362 @@
+/* Containers.
+   Chests the player builds, crates left lying in the ruins and the bodies zombies leave
+   behind all keep items in an inventory of their own. Interacting with one next to the player
+   opens a two-pane panel, the player's inventory on one side and the container's on the other,
+   and things move across one at a time, a stack at a time, or all at once.
+   Moves go through the usual inventory insert, so a full side swaps with its selected slot the
+   same way picking something up does. Moving everything never swaps, it only fills what room
+   there is. Bodies are gone once they've been picked clean or once they've lain around long
+   enough to rot, and anything that destroys a container or rots away spills what was in it on
+   the ground.
+   Quick-stacking only moves what the other side already has some of, so a chest set aside for
+   wood keeps getting wood. Favorite slots never move out on their own, only one at a time.
+   Like the shop, the panel pauses the game while it's open.
+*/
+
+use glam::{IVec2, Vec2};
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    entity::{EntityType, VID},
+    entity_templates::init_as_container,
+    ground::drop_item,
+    inventory::Inventory,
+    item::Item,
+    particle::{ParticleData, ParticleLayer},
+    render::TILE_SIZE,
+    settings::INTERACT_DEBOUNCE_INTERVAL,
+    sprite::Sprite,
+    state::{Mode, State},
+    step::TIMESTEP,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContainerKind {
+    /// Built by the player.
+    Chest,
+    /// Found in the ruins with something already inside.
+    Crate,
+    /// What's left of a zombie that had something on it. Rots away after a while.
+    Corpse,
+}
+
+impl ContainerKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContainerKind::Chest => "CHEST",
+            ContainerKind::Crate => "CRATE",
+            ContainerKind::Corpse => "BODY",
+        }
+    }
+}
+
+/// Which side of the container panel the cursor is on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContainerPane {
+    Player,
+    Container,
+}
+
+/// How much a move takes from the selected slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transfer {
+    One,
+    Stack,
+    /// Every slot on the side the cursor is on.
+    All,
+    /// Every stack the other side already has some of.
+    QuickStack,
+}
+
+/// The container panel. Each side's cursor is that inventory's selected slot.
+#[derive(Debug, Clone, Copy)]
+pub struct ContainerView {
+    pub vid: Option<VID>,
+    pub pane: ContainerPane,
+    /// The player's selected slot when the panel opened, it's put back on close since the
+    /// cursor can wander off into the backpack.
+    pub player_slot: usize,
+}
+
+impl ContainerView {
+    pub fn new() -> ContainerView {
+        ContainerView {
+            vid: None,
+            pane: ContainerPane::Player,
+            player_slot: 0,
+        }
+    }
+}
+
+/// The container on a tile, if there is one.
+pub fn container_at(state: &State, pos: IVec2) -> Option<VID> {
+    if !state.stage.in_bounds(pos) {
+        return None;
+    }
+    state.spatial_grid[pos.x as usize][pos.y as usize]
+        .iter()
+        .find(|vid| {
+            state
+                .entity_manager
+                .get_entity(**vid)
+                .is_some_and(|e| e.type_ == EntityType::Container && e.active)
+        })
+        .copied()
+}
+
+/// Makes a container on a tile with the items inside. It always has room for all of them.
+pub fn spawn_container(
+    state: &mut State,
+    kind: ContainerKind,
+    pos: IVec2,
+    items: &[Item],
+) -> Option<VID> {
+    let vid = state.entity_manager.new_entity()?;
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        init_as_container(entity, kind);
+        entity.pos = pos.as_vec2() + Vec2::splat(0.5);
+        entity.inventory.capacity = entity.inventory.capacity.max(items.len());
+        for item in items {
+            entity.inventory.insert(*item);
+        }
+    }
+    state.add_entity_to_grid(vid, pos);
+    Some(vid)
+}
+
+pub fn open_container(state: &mut State, vid: VID) {
+    state.container_view.vid = Some(vid);
+    state.container_view.pane = ContainerPane::Player;
+    state.container_view.player_slot = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .map_or(0, |player| player.inventory.selected_index);
+    state.mode = Mode::Container;
+}
+
+pub fn close_container(state: &mut State) {
+    // so the same press doesn't open it straight back up
+    state.playing_input_debounce_timers.interact = INTERACT_DEBOUNCE_INTERVAL;
+    let player_slot = state.container_view.player_slot;
+    if let Some(player) = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        player.inventory.set_selected_index(player_slot);
+    }
+    state.container_view.vid = None;
+    state.mode = Mode::Playing;
+}
+
+/// Moves items out of the side the cursor is on into the other side. Returns whether anything
+/// moved. Closes the panel if that picked a body clean.
+pub fn transfer(state: &mut State, audio: &mut Audio, transfer: Transfer) -> bool {
+    let (player_vid, container_vid) = match (state.player_vid, state.container_view.vid) {
+        (Some(player_vid), Some(container_vid)) => (player_vid, container_vid),
+        _ => return false,
+    };
+    let mut container_inventory = match state.entity_manager.get_entity_mut(container_vid) {
+        Some(container) => std::mem::take(&mut container.inventory),
+        None => return false,
+    };
+
+    let moved = match state.entity_manager.get_entity_mut(player_vid) {
+        Some(player) => {
+            let (from, to) = match state.container_view.pane {
+                ContainerPane::Player => (&mut player.inventory, &mut container_inventory),
+                ContainerPane::Container => (&mut container_inventory, &mut player.inventory),
+            };
+            match transfer {
+                Transfer::One => move_selected(from, to, 1),
+                Transfer::Stack => move_selected(from, to, u32::MAX),
+                Transfer::All => move_all(from, to),
+                Transfer::QuickStack => quick_stack(from, to),
+            }
+        }
+        None => false,
+    };
+
+    let picked_clean = container_inventory.is_empty();
+    if let Some(container) = state.entity_manager.get_entity_mut(container_vid) {
+        container.inventory = container_inventory;
+    }
+
+    if moved {
+        audio.play_sound_effect(SoundEffect::Drop);
+    } else {
+        audio.play_sound_effect(SoundEffect::CantUse);
+    }
+    let is_corpse = state
+        .entity_manager
+        .get_entity(container_vid)
+        .is_some_and(|e| e.container == Some(ContainerKind::Corpse));
+    if picked_clean && is_corpse {
+        remove_corpse(state, container_vid);
+        close_container(state);
+    }
+    moved
+}
+
+/// Moves up to `count` from the selected slot of one inventory into another. Whatever the other
+/// side gives back, the rest that didn't fit or what got swapped out, takes the slot's place.
+/// Nothing moves if that wouldn't fit back in the slot.
+fn move_selected(from: &mut Inventory, to: &mut Inventory, count: u32) -> bool {
+    let slot = from.selected_index;
+    let item = match from.get(slot) {
+        Some(entry) => entry.item,
+        None => return false,
+    };
+    let moving = Item {
+        count: item.count.min(count),
+        ..item
+    };
+
+    // work on copies so a move that can't finish leaves both sides as they were
+    let mut new_from = from.clone();
+    let mut new_to = to.clone();
+    new_from.remove_count_from_slot(slot, moving.count);
+    if let Some(back) = new_to.insert(moving) {
+        if back == moving {
+            return false;
+        }
+        match new_from.get_mut(slot) {
+            None => new_from.set_slot(slot, back),
+            Some(entry)
+                if entry.item.type_ == back.type_
+                    && entry.item.durability == back.durability
+                    && entry.item.count + back.count <= entry.item.max_count =>
+            {
+                entry.item.count += back.count
+            }
+            Some(_) => return false,
+        }
+    }
+    *from = new_from;
+    *to = new_to;
+    true
+}
+
+/// Moves as much of every stack as the other side has room for, without swapping anything out.
+fn move_all(from: &mut Inventory, to: &mut Inventory) -> bool {
+    move_stacks(from, to, |_| true)
+}
+
+/// Moves as much as fits of every stack the other side already has some of.
+pub fn quick_stack(from: &mut Inventory, to: &mut Inventory) -> bool {
+    let wanted: Vec<_> = to.iter().map(|entry| entry.item.type_).collect();
+    move_stacks(from, to, |item| wanted.contains(&item.type_))
+}
+
+/// Moves what fits of the stacks that pass `filter`, leaving favorite slots alone.
+fn move_stacks(from: &mut Inventory, to: &mut Inventory, filter: impl Fn(&Item) -> bool) -> bool {
+    let mut moved = false;
+    let slots: Vec<usize> = from
+        .iter()
+        .map(|entry| entry.index)
+        .filter(|slot| !from.is_favorite(*slot))
+        .collect();
+    for slot in slots {
+        let item = match from.get(slot) {
+            Some(entry) if filter(&entry.item) => entry.item,
+            _ => continue,
+        };
+        let count = to.room_for(&item).min(item.count);
+        if count == 0 {
+            continue;
+        }
+        to.insert(Item { count, ..item });
+        from.remove_count_from_slot(slot, count);
+        moved = true;
+    }
+    moved
+}
+
+/// Quick-stacks the player's inventory into every chest and crate in reach. Bodies are left
+/// out, nobody means to put things back on a zombie.
+pub fn quick_stack_nearby(state: &mut State, audio: &mut Audio) -> bool {
+    const QUICK_STACK_RANGE: i32 = 4; // in tiles, each way
+
+    let (player_vid, player_pos) = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid).map(|p| (vid, p.pos)))
+    {
+        Some(found) => found,
+        None => return false,
+    };
+    let center = player_pos.as_ivec2();
+    let mut targets = Vec::new();
+    for dx in -QUICK_STACK_RANGE..=QUICK_STACK_RANGE {
+        for dy in -QUICK_STACK_RANGE..=QUICK_STACK_RANGE {
+            if let Some(vid) = container_at(state, center + IVec2::new(dx, dy)) {
+                let is_corpse = state
+                    .entity_manager
+                    .get_entity(vid)
+                    .is_some_and(|e| e.container == Some(ContainerKind::Corpse));
+                if !is_corpse {
+                    targets.push(vid);
+                }
+            }
+        }
+    }
+
+    let mut moved = false;
+    for vid in targets {
+        let mut container_inventory = match state.entity_manager.get_entity_mut(vid) {
+            Some(container) => std::mem::take(&mut container.inventory),
+            None => continue,
+        };
+        if let Some(player) = state.entity_manager.get_entity_mut(player_vid) {
+            moved |= quick_stack(&mut player.inventory, &mut container_inventory);
+        }
+        if let Some(container) = state.entity_manager.get_entity_mut(vid) {
+            container.inventory = container_inventory;
+        }
+    }
+
+    if moved {
+        audio.play_sound_effect(SoundEffect::Drop);
+    } else {
+        audio.play_sound_effect(SoundEffect::CantUse);
+    }
+    moved
+}
+
+/// Bodies nobody gets around to searching rot away, spilling whatever was still on them.
+pub fn step_corpse_decay(state: &mut State, audio: &mut Audio, vid: VID) {
+    const BODY_DECAY_SECONDS: f32 = 60.0;
+
+    let (pos, contents) = match state.entity_manager.get_entity_mut(vid) {
+        Some(entity) if entity.active && entity.container == Some(ContainerKind::Corpse) => {
+            entity.counter_a += TIMESTEP;
+            if entity.counter_a < BODY_DECAY_SECONDS {
+                return;
+            }
+            let contents: Vec<Item> = entity.inventory.iter().map(|entry| entry.item).collect();
+            (entity.pos.as_ivec2(), contents)
+        }
+        _ => return,
+    };
+    for item in contents {
+        drop_item(state, audio, item, pos);
+    }
+    remove_corpse(state, vid);
+}
+
+/// Takes a picked clean body off the map, leaving an ordinary one to rot away.
+fn remove_corpse(state: &mut State, vid: VID) {
+    let (pos, rot) = match state.entity_manager.get_entity(vid) {
+        Some(entity) => (entity.pos, entity.rot),
+        None => return,
+    };
+    state.remove_entity_from_grid(vid, pos.as_ivec2());
+    state.entity_manager.set_inactive_vid(vid);
+    state.particles.spawn_static(ParticleData::new(
+        pos,
+        Vec2::splat(TILE_SIZE),
+        rot,
+        1.0,
+        60 * 15, // Lasts for 15 seconds
+        Sprite::ZombieDead,
+        ParticleLayer::Background,
+    ));
+}
@@ -0,0 +1,255 @@
+/* Crafting.
+   Recipes turn stacks of one thing in the inventory into another: stone from broken walls
+   back into walls, cloth off zombies into bandages, feathers and sticks into arrows. They live
+   in a data file so they can be tuned without a rebuild, and get loaded once at startup.
+   The same file says what fixes up gear that wears out; anything worn down that can be
+   repaired shows up in the panel under the recipes.
+   Like the shop, the panel pauses the game while it's open.
+*/
+
+use serde::Deserialize;
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    equipment::{EquipSlot, EQUIP_SLOTS},
+    inventory::Inventory,
+    item::{Item, ItemType},
+    state::State,
+};
+
+pub const RECIPES_PATH: &str = "assets/data/recipes.toml";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Ingredient {
+    pub item: ItemType,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    pub output: ItemType,
+    /// How many come out of one craft.
+    #[serde(default = "one")]
+    pub count: u32,
+    pub ingredients: Vec<Ingredient>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// Materials that put durability back on an item.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Repair {
+    pub item: ItemType,
+    /// Durability one repair puts back.
+    pub amount: u32,
+    pub ingredients: Vec<Ingredient>,
+}
+
+/// The layout of the recipes file, lists of `[[recipe]]` and `[[repair]]` tables.
+#[derive(Deserialize)]
+struct RecipeFile {
+    recipe: Vec<Recipe>,
+    #[serde(default)]
+    repair: Vec<Repair>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Crafting {
+    pub recipes: Vec<Recipe>,
+    pub repairs: Vec<Repair>,
+    pub selected: usize,
+}
+
+impl Crafting {
+    pub fn new() -> Crafting {
+        Crafting {
+            recipes: Vec::new(),
+            repairs: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn load() -> Result<Crafting, String> {
+        let text = std::fs::read_to_string(RECIPES_PATH)
+            .map_err(|e| format!("couldn't read {}: {}", RECIPES_PATH, e))?;
+        let file: RecipeFile =
+            toml::from_str(&text).map_err(|e| format!("couldn't parse {}: {}", RECIPES_PATH, e))?;
+        Ok(Crafting {
+            recipes: file.recipe,
+            repairs: file.repair,
+            selected: 0,
+        })
+    }
+}
+
+/// Where the item being repaired is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepairTarget {
+    /// An inventory slot index.
+    Slot(usize),
+    Worn(EquipSlot),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CraftingJob {
+    /// Index into the recipes.
+    Make(usize),
+    /// Index into the repairs, and what to fix with it.
+    Repair(usize, RepairTarget),
+}
+
+/// A row of the crafting panel.
+#[derive(Debug, Clone)]
+pub struct CraftingEntry {
+    pub job: CraftingJob,
+    pub name: String,
+    /// What the row's icon shows.
+    pub item: ItemType,
+    pub ingredients: Vec<Ingredient>,
+}
+
+/// Every recipe, then a repair for each worn down item the player has that can be fixed.
+pub fn crafting_entries(state: &State) -> Vec<CraftingEntry> {
+    let mut entries: Vec<CraftingEntry> = state
+        .crafting
+        .recipes
+        .iter()
+        .enumerate()
+        .map(|(i, recipe)| CraftingEntry {
+            job: CraftingJob::Make(i),
+            name: recipe.name.clone(),
+            item: recipe.output,
+            ingredients: recipe.ingredients.clone(),
+        })
+        .collect();
+
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+    {
+        Some(player) => player,
+        None => return entries,
+    };
+    let carried = player
+        .inventory
+        .iter()
+        .map(|entry| (RepairTarget::Slot(entry.index), &entry.item));
+    let worn = EQUIP_SLOTS.iter().filter_map(|&slot| {
+        player
+            .equipment
+            .get(slot)
+            .map(|item| (RepairTarget::Worn(slot), item))
+    });
+    for (target, item) in carried.chain(worn) {
+        if item.durability >= item.max_durability {
+            continue;
+        }
+        if let Some(i) = state
+            .crafting
+            .repairs
+            .iter()
+            .position(|r| r.item == item.type_)
+        {
+            entries.push(CraftingEntry {
+                job: CraftingJob::Repair(i, target),
+                name: format!(
+                    "Repair {} {}/{}",
+                    item.name, item.durability, item.max_durability
+                ),
+                item: item.type_,
+                ingredients: state.crafting.repairs[i].ingredients.clone(),
+            });
+        }
+    }
+    entries
+}
+
+/// Whether the inventory holds enough of everything on the list.
+pub fn has_ingredients(inventory: &Inventory, ingredients: &[Ingredient]) -> bool {
+    ingredients
+        .iter()
+        .all(|ingredient| inventory.count_of(ingredient.item) >= ingredient.count)
+}
+
+/// Does whatever a row of the crafting panel does, using up its ingredients from the player's
+/// inventory. Nothing is taken if it can't be afforded or doesn't work out.
+pub fn craft(state: &mut State, audio: &mut Audio, index: usize) -> bool {
+    let entry = match crafting_entries(state).into_iter().nth(index) {
+        Some(entry) => entry,
+        None => return false,
+    };
+    let can_afford = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .is_some_and(|player| has_ingredients(&player.inventory, &entry.ingredients));
+    if !can_afford {
+        audio.play_sound_effect(SoundEffect::CantUse);
+        return false;
+    }
+    let done = match entry.job {
+        CraftingJob::Make(i) => make(state, i),
+        CraftingJob::Repair(i, target) => repair(state, i, target),
+    };
+    if done {
+        audio.play_sound_effect(SoundEffect::Confirm);
+    } else {
+        audio.play_sound_effect(SoundEffect::CantUse);
+    }
+    done
+}
+
+/// Uses up a recipe's ingredients and puts what it makes in the inventory, unless there's no
+/// room for it.
+fn make(state: &mut State, index: usize) -> bool {
+    let recipe = state.crafting.recipes[index].clone();
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        Some(player) => player,
+        None => return false,
+    };
+
+    // try it on a copy so a full inventory doesn't eat the ingredients
+    let mut inventory = player.inventory.clone();
+    for ingredient in &recipe.ingredients {
+        inventory.remove_count(ingredient.item, ingredient.count);
+    }
+    if inventory
+        .insert(Item::new_stack(recipe.output, recipe.count))
+        .is_some()
+    {
+        return false;
+    }
+    player.inventory = inventory;
+    true
+}
+
+/// Uses up a repair's ingredients and puts durability back on the item, up to its max.
+fn repair(state: &mut State, index: usize, target: RepairTarget) -> bool {
+    let repair = state.crafting.repairs[index].clone();
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        Some(player) => player,
+        None => return false,
+    };
+    // taking the ingredients doesn't move anything between slots
+    for ingredient in &repair.ingredients {
+        player
+            .inventory
+            .remove_count(ingredient.item, ingredient.count);
+    }
+    let item = match target {
+        RepairTarget::Slot(slot) => player.inventory.get_mut(slot).map(|entry| &mut entry.item),
+        RepairTarget::Worn(slot) => player.equipment.get_mut(slot),
+    };
+    if let Some(item) = item {
+        item.durability = (item.durability + repair.amount).min(item.max_durability);
+    }
+    true
+}
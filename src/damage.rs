@@ -0,0 +1,167 @@
+/* Damage.
+   Every hit is an amount of some type of damage. Before it comes off an entity's health it's
+   rolled (a bit of variance, and now and then a critical for double), cut by the target's
+   resistance to that type, and then the physical types lose a flat amount to armor.
+   Tiles go through the same table, so a wall shrugs off a scratch but not a bat.
+*/
+
+use rand::random_range;
+
+use crate::{entity::VID, state::State, tile::Tile};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DamageType {
+    Blunt,
+    Slash,
+    Pierce,
+    Fire,
+    Explosive,
+    Acid,
+}
+
+impl DamageType {
+    /// Armor only stops things that hit you.
+    pub fn physical(self) -> bool {
+        matches!(
+            self,
+            DamageType::Blunt | DamageType::Slash | DamageType::Pierce
+        )
+    }
+}
+
+const DAMAGE_TYPE_COUNT: usize = 6;
+
+/// How much of each type of damage gets stopped. 0.0 takes all of it, 1.0 is immune, and below
+/// zero takes extra.
+#[derive(Debug, Clone, Copy)]
+pub struct Resistances {
+    values: [f32; DAMAGE_TYPE_COUNT],
+}
+
+impl Resistances {
+    pub fn none() -> Resistances {
+        Resistances {
+            values: [0.0; DAMAGE_TYPE_COUNT],
+        }
+    }
+
+    pub fn immune() -> Resistances {
+        Resistances {
+            values: [1.0; DAMAGE_TYPE_COUNT],
+        }
+    }
+
+    pub fn get(&self, type_: DamageType) -> f32 {
+        self.values[type_ as usize]
+    }
+
+    pub fn set(&mut self, type_: DamageType, resistance: f32) {
+        self.values[type_ as usize] = resistance.min(1.0);
+    }
+
+    pub fn immune_to(&self, type_: DamageType) -> bool {
+        self.get(type_) >= 1.0
+    }
+}
+
+/// An attack before it lands.
+#[derive(Debug, Clone, Copy)]
+pub struct Damage {
+    pub amount: u32,
+    pub type_: DamageType,
+    /// Fraction the amount can swing either way.
+    pub variance: f32,
+    /// Chance to land a critical for double.
+    pub crit_chance: f32,
+}
+
+impl Damage {
+    pub fn new(amount: u32, type_: DamageType) -> Damage {
+        Damage {
+            amount,
+            type_,
+            variance: 0.0,
+            crit_chance: 0.0,
+        }
+    }
+}
+
+/// What an attack actually did.
+#[derive(Debug, Clone, Copy)]
+pub struct Hit {
+    pub amount: u32,
+    pub critical: bool,
+}
+
+/// Rolls variance and criticals for an attack.
+pub fn roll_damage(damage: &Damage) -> Hit {
+    const CRIT_MULTIPLIER: f32 = 2.0;
+
+    let mut amount = damage.amount as f32;
+    if damage.variance > 0.0 {
+        amount *= 1.0 + random_range(-damage.variance..damage.variance);
+    }
+    let critical = damage.crit_chance > 0.0 && random_range(0.0..1.0) < damage.crit_chance;
+    if critical {
+        amount *= CRIT_MULTIPLIER;
+    }
+    Hit {
+        amount: amount.round().max(0.0) as u32,
+        critical,
+    }
+}
+
+/// Cuts an amount down by resistance and armor. Anything not immune still takes at least 1.
+pub fn mitigate(amount: u32, type_: DamageType, resistances: &Resistances, armor: u32) -> u32 {
+    if amount == 0 || resistances.immune_to(type_) {
+        return 0;
+    }
+    let resisted = (amount as f32 * (1.0 - resistances.get(type_))).round() as u32;
+    let armored = if type_.physical() {
+        resisted.saturating_sub(armor)
+    } else {
+        resisted
+    };
+    armored.max(1)
+}
+
+/// Lands an attack on an entity: rolls it, takes off what it resists and its armor (its own and
+/// whatever it's wearing) stops, and lowers its health by the rest. Returns None if there's no
+/// such entity.
+pub fn deal_damage(state: &mut State, vid: VID, damage: &Damage) -> Option<Hit> {
+    let entity = state.entity_manager.get_entity_mut(vid)?;
+    let mut hit = roll_damage(damage);
+    let armor = entity.armor + entity.equipment.stats().armor;
+    hit.amount = mitigate(hit.amount, damage.type_, &entity.resistances, armor);
+    entity.health = entity.health.saturating_sub(hit.amount);
+    Some(hit)
+}
+
+/// What each kind of tile holds up against.
+pub fn tile_resistances(tile: Tile) -> Resistances {
+    let mut resistances = Resistances::none();
+    match tile {
+        Tile::Wall => {
+            resistances.set(DamageType::Slash, 0.5);
+            resistances.set(DamageType::Pierce, 0.75);
+            resistances.set(DamageType::Fire, 0.5);
+            resistances.set(DamageType::Explosive, -0.5);
+        }
+        Tile::Door => {
+            resistances.set(DamageType::Slash, 0.25);
+            resistances.set(DamageType::Fire, -0.5); // it's wood
+        }
+        Tile::Rock => {
+            resistances.set(DamageType::Blunt, 0.5);
+            resistances.set(DamageType::Slash, 0.9);
+            resistances.set(DamageType::Pierce, 0.9);
+            resistances.set(DamageType::Fire, 1.0);
+            resistances.set(DamageType::Acid, 0.25);
+        }
+        Tile::Spikes => {
+            resistances.set(DamageType::Fire, 1.0);
+        }
+        _ => {}
+    }
+    resistances
+}
@@ -0,0 +1,147 @@
+/* Time of day.
+   The clock runs in game hours and wraps every `day_length` seconds. Darkness is what the rest
+   of the game reads: it fades in over dusk and out over dawn, and everything night-related
+   (the tint, how far the player can see, zombie speed, wave sizes, roosting chickens, the
+   music) scales with it instead of flipping at a fixed hour.
+*/
+
+use raylib::color::Color;
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    entity::{EntityType, Mood},
+    state::State,
+    step::TIMESTEP,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeOfDay {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    /// Hours since midnight, 0.0 to 24.0.
+    pub time: f32,
+    /// Starts at 1, ticks over at midnight.
+    pub day: u32,
+    /// Real seconds for a full day.
+    pub day_length: f32,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            time: 8.0,
+            day: 1,
+            day_length: 240.0,
+        }
+    }
+
+    pub fn hour(&self) -> u32 {
+        self.time as u32
+    }
+
+    pub fn minute(&self) -> u32 {
+        (self.time.fract() * 60.0) as u32
+    }
+
+    pub fn time_of_day(&self) -> TimeOfDay {
+        match self.time {
+            t if (5.0..7.0).contains(&t) => TimeOfDay::Dawn,
+            t if (7.0..18.0).contains(&t) => TimeOfDay::Day,
+            t if (18.0..20.0).contains(&t) => TimeOfDay::Dusk,
+            _ => TimeOfDay::Night,
+        }
+    }
+
+    /// 0.0 in full daylight, 1.0 in the dead of night.
+    pub fn darkness(&self) -> f32 {
+        match self.time_of_day() {
+            TimeOfDay::Day => 0.0,
+            TimeOfDay::Night => 1.0,
+            TimeOfDay::Dawn => 1.0 - (self.time - 5.0) / 2.0,
+            TimeOfDay::Dusk => (self.time - 18.0) / 2.0,
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.darkness() >= 0.5
+    }
+}
+
+/// Color laid over the world. Warm around sunrise and sunset, deep blue at night.
+pub fn scene_tint(clock: &Clock) -> Color {
+    const MAX_NIGHT_ALPHA: f32 = 170.0;
+    const TWILIGHT_ALPHA: f32 = 50.0;
+
+    let darkness = clock.darkness();
+    match clock.time_of_day() {
+        TimeOfDay::Day => Color::new(0, 0, 0, 0),
+        TimeOfDay::Night => Color::new(10, 15, 50, MAX_NIGHT_ALPHA as u8),
+        // twilight blends from orange into the night blue as it gets darker
+        TimeOfDay::Dawn | TimeOfDay::Dusk => {
+            let r = (220.0 * (1.0 - darkness) + 10.0 * darkness) as u8;
+            let g = (110.0 * (1.0 - darkness) + 15.0 * darkness) as u8;
+            let b = (40.0 * (1.0 - darkness) + 50.0 * darkness) as u8;
+            let a = TWILIGHT_ALPHA + (MAX_NIGHT_ALPHA - TWILIGHT_ALPHA) * darkness;
+            Color::new(r, g, b, a as u8)
+        }
+    }
+}
+
+/// How much faster zombies get about after dark.
+pub fn zombie_speed_multiplier(clock: &Clock) -> f32 {
+    const NIGHT_SPEEDUP: f32 = 0.5;
+    1.0 + NIGHT_SPEEDUP * clock.darkness()
+}
+
+/// Advances the clock and handles what changes between day and night.
+pub fn step_clock(state: &mut State, audio: &mut Audio) {
+    let before = state.clock.time_of_day();
+
+    state.clock.time += 24.0 * TIMESTEP / state.clock.day_length;
+    if state.clock.time >= 24.0 {
+        state.clock.time -= 24.0;
+        state.clock.day += 1;
+    }
+
+    // the music slows down and drops a little as it gets dark
+    const NIGHT_MUSIC_PITCH: f32 = 0.85;
+    let darkness = state.clock.darkness();
+    audio.set_music_pitch(1.0 - (1.0 - NIGHT_MUSIC_PITCH) * darkness);
+
+    let after = state.clock.time_of_day();
+    if before == after {
+        return;
+    }
+    match after {
+        TimeOfDay::Dawn => {
+            audio.play_sound_effect(SoundEffect::Rooster);
+            set_chickens_roosting(state, false);
+        }
+        TimeOfDay::Dusk => {
+            audio.play_sound_effect(SoundEffect::Hen);
+            set_chickens_roosting(state, true);
+        }
+        TimeOfDay::Night => {
+            audio.play_sound_effect(SoundEffect::ZombieGrowl2);
+        }
+        TimeOfDay::Day => {}
+    }
+}
+
+/// Chickens settle down where they are for the night and wander off again in the morning.
+pub fn set_chickens_roosting(state: &mut State, roosting: bool) {
+    let mood = if roosting { Mood::Idle } else { Mood::Wander };
+    for vid in state.entity_manager.get_active_vids() {
+        if let Some(chicken) = state.entity_manager.get_entity_mut(vid) {
+            if chicken.type_ == EntityType::Chicken {
+                chicken.mood = mood;
+            }
+        }
+    }
+}
@@ -2,12 +2,18 @@ use glam::{IVec2, Vec2};
 
 use crate::{
     audio::{Audio, SoundEffect},
+    containers::ContainerKind,
+    damage::Resistances,
+    equipment::Equipment,
     inventory::Inventory,
     item::Item,
+    loot::LootSource,
     sprite::Sprite,
     state::State,
+    status::StatusEffects,
     step::entity_step_sound_lookup,
     tile::{self, is_tile_occupied},
+    train::Consist,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -19,6 +25,7 @@ pub enum EntityType {
     RailLayer,
     Train,
     Item,
+    Container,
 }
 
 /** these are the low level current actions of the entity */
@@ -67,18 +74,6 @@ pub enum DrawLayer {
     Foreground,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum DamageType {
-    Punch,
-    Scratch,
-}
-
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum DamageVulnerability {
-    Immune,
-    NotImmune,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct VID {
     pub id: usize,
@@ -129,8 +124,11 @@ pub struct Entity {
 
     pub health: u32,
     pub max_hp: u32,
-    pub damage_vulnerability: DamageVulnerability,
+    pub resistances: Resistances,
+    /// Flat amount taken off every physical hit.
+    pub armor: u32,
     pub can_be_stunned: bool,
+    pub status: StatusEffects,
     pub move_cooldown: f32,
     pub move_cooldown_countdown: f32,
 
@@ -149,12 +147,20 @@ pub struct Entity {
     pub attack_cooldown_countdown: f32,
 
     pub inventory: Inventory,
+    pub equipment: Equipment,
     pub growl: Option<SoundEffect>,
     pub death_sound: Option<SoundEffect>,
     pub direction: IVec2,
 
     pub item: Option<Item>,
     pub attackable: bool,
+
+    /// Only trains have one: the cars they pull, see `train`.
+    pub consist: Option<Consist>,
+    /// What it drops when it dies, see `loot`.
+    pub loot: Option<LootSource>,
+    /// Only containers have one, see `containers`.
+    pub container: Option<ContainerKind>,
 }
 
 impl Entity {
@@ -166,7 +172,8 @@ impl Entity {
             type_: EntityType::None,
             vid: VID { id: 0, version: 0 },
             impassable: false,
-            damage_vulnerability: DamageVulnerability::NotImmune,
+            resistances: Resistances::none(),
+            armor: 0,
 
             //  Shape
             pos: Vec2::new(0.0, 0.0),
@@ -188,6 +195,7 @@ impl Entity {
             health: 0,
             max_hp: 0,
             can_be_stunned: false,
+            status: StatusEffects::new(),
             move_cooldown: 0.0,
             move_cooldown_countdown: 0.0,
 
@@ -206,12 +214,17 @@ impl Entity {
             attack_cooldown_countdown: 0.0,
 
             inventory: Inventory::new(),
+            equipment: Equipment::new(),
             growl: None,
             death_sound: None,
             direction: IVec2::new(0, 0),
 
             item: None,
             attackable: true,
+
+            consist: None,
+            loot: None,
+            container: None,
         }
     }
 
@@ -1,21 +1,32 @@
 // wander behavior
 
-use std::vec;
-
 use glam::{IVec2, Vec2};
 use rand::random_range;
 
 use crate::{
     audio::{Audio, SoundEffect},
-    entity::{self, swap_step_sound, EntityState, EntityType, StepSound, VID},
+    containers::{spawn_container, ContainerKind},
+    damage::{deal_damage, Damage, DamageType},
+    day_night,
+    entity::{self, swap_step_sound, Alignment, EntityState, EntityType, StepSound, VID},
     entity_templates::init_as_train,
+    ground::drop_item,
+    item_use::on_item_break,
+    lighting::has_line_of_sight,
     particle::{ParticleData, ParticleLayer},
     particle_templates::{blood_puddle, blood_splatter},
+    physics::{explode, is_sliding, knock_back},
+    rail,
+    shop::award_points,
     sprite::Sprite,
-    stage::TileData,
+    stage::{TileData, TileLayer},
     state::{get_adjacent_entities, State},
+    status::{self, StatusKind},
     step::{entity_step_sound_lookup, lean_entity, TIMESTEP},
-    tile::{is_tile_occupied, tile_shake_area_at, Tile},
+    tile::{damage_tile, is_tile_occupied, tile_shake_area_at, Tile},
+    train::{Consist, TrainCar},
+    utils::new_york_dist,
+    weather::{self, hearing_multiplier},
 };
 
 pub fn wander(state: &mut State, audio: &mut Audio, vid: VID) {
@@ -60,6 +71,126 @@ pub fn wander(state: &mut State, audio: &mut Audio, vid: VID) {
     }
 }
 
+/// Wandering zombies that can see the player within their detection radius start chasing.
+pub fn notice_target(state: &mut State, vid: VID) {
+    let (pos, detection_radius) = match state.entity_manager.get_entity(vid) {
+        Some(entity)
+            if entity.type_ == EntityType::Zombie && entity.mood == entity::Mood::Wander =>
+        {
+            (entity.pos.as_ivec2(), entity.detection_radius)
+        }
+        _ => return,
+    };
+    let player = state
+        .player_vid
+        .and_then(|player_vid| state.entity_manager.get_entity(player_vid))
+        .map(|player| (player.vid, player.pos.as_ivec2()));
+    let (player_vid, player_pos) = match player {
+        Some(player) => player,
+        None => return,
+    };
+
+    if (player_pos - pos).as_vec2().length() > detection_radius
+        || !has_line_of_sight(&state.stage, pos, player_pos)
+    {
+        return;
+    }
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        entity.mood = entity::Mood::ChasingTarget;
+        entity.target_entity = Some(player_vid);
+    }
+}
+
+/// Walks a chasing entity one tile toward its target, going around obstacles along the other axis.
+/// While the target is in sight it heads straight for it. Once it loses sight it makes for the
+/// last place it saw the target, and goes back to wandering if there's nothing there.
+pub fn chase_target(state: &mut State, audio: &mut Audio, vid: VID) {
+    let (pos, target_vid, last_seen) = match state.entity_manager.get_entity(vid) {
+        Some(entity)
+            if matches!(
+                entity.mood,
+                entity::Mood::ChasingTarget | entity::Mood::LosingTarget
+            ) =>
+        {
+            (
+                entity.pos.as_ivec2(),
+                entity.target_entity,
+                entity.target_pos,
+            )
+        }
+        _ => return,
+    };
+
+    let target_pos = target_vid
+        .and_then(|target_vid| state.entity_manager.get_entity(target_vid))
+        .filter(|target| target.active && !target.marked_for_destruction)
+        .map(|target| target.pos.as_ivec2());
+    let target_pos = match target_pos {
+        Some(target_pos) => target_pos,
+        None => {
+            give_up_chase(state, vid);
+            return;
+        }
+    };
+
+    // keep tracking while it's in sight, otherwise go by memory
+    let goal = if has_line_of_sight(&state.stage, pos, target_pos) {
+        if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+            entity.mood = entity::Mood::ChasingTarget;
+            entity.target_pos = Some(target_pos.as_vec2() + Vec2::splat(0.5));
+        }
+        target_pos
+    } else {
+        let last_seen = match last_seen {
+            Some(last_seen) => last_seen.as_ivec2(),
+            None => {
+                give_up_chase(state, vid);
+                return;
+            }
+        };
+        if last_seen == pos {
+            give_up_chase(state, vid);
+            return;
+        }
+        if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+            entity.mood = entity::Mood::LosingTarget;
+        }
+        last_seen
+    };
+
+    if !ready_to_move(state, vid) {
+        return;
+    }
+
+    // already next to it, let the attack logic take over
+    let delta = goal - pos;
+    if goal == target_pos && new_york_dist(pos, goal) <= 1 {
+        return;
+    }
+
+    // close the longer gap first
+    let step_x = IVec2::new(delta.x.signum(), 0);
+    let step_y = IVec2::new(0, delta.y.signum());
+    let (first, second) = if delta.x.abs() >= delta.y.abs() {
+        (step_x, step_y)
+    } else {
+        (step_y, step_x)
+    };
+
+    let moved = move_entity_on_grid(state, audio, vid, pos + first, false, false, false);
+    if !moved && second != IVec2::ZERO {
+        move_entity_on_grid(state, audio, vid, pos + second, false, false, false);
+    }
+}
+
+fn give_up_chase(state: &mut State, vid: VID) {
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        entity.mood = entity::Mood::Wander;
+        entity.target_entity = None;
+        entity.target_pos = None;
+    }
+}
+
 pub fn growl_sometimes(state: &mut State, audio: &mut Audio, vid: VID) {
     // check if exists
     if state.entity_manager.get_entity(vid).is_none() {
@@ -104,20 +235,40 @@ pub fn growl_sometimes(state: &mut State, audio: &mut Audio, vid: VID) {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AttackType {
     FistPunch,
+    BatSwing,
     ZombieScratch,
+    ArrowThrow,
 }
 
 pub fn attack_sprite_lookup(attack_type: AttackType) -> Sprite {
     match attack_type {
         AttackType::FistPunch => Sprite::Fist,
+        AttackType::BatSwing => Sprite::Bat,
         AttackType::ZombieScratch => Sprite::ZombieScratch1,
+        AttackType::ArrowThrow => Sprite::Arrow,
     }
 }
 
+/// How hard and with what each kind of attack hits.
+pub fn attack_damage(attack_type: AttackType) -> Damage {
+    let (amount, type_, crit_chance) = match attack_type {
+        AttackType::FistPunch => (10, DamageType::Blunt, 0.05),
+        AttackType::BatSwing => (25, DamageType::Blunt, 0.1),
+        AttackType::ZombieScratch => (5, DamageType::Slash, 0.0),
+        AttackType::ArrowThrow => (15, DamageType::Pierce, 0.15),
+    };
+    let mut damage = Damage::new(amount, type_);
+    damage.variance = 0.2;
+    damage.crit_chance = crit_chance;
+    damage
+}
+
 pub fn attack_sound_lookup(attack_type: AttackType) -> SoundEffect {
     match attack_type {
         AttackType::FistPunch => SoundEffect::Punch1, // Using fist punch sound as attack sound
+        AttackType::BatSwing => SoundEffect::HitBlock1,
         AttackType::ZombieScratch => SoundEffect::ZombieScratch1, // Using scratch sound as attack sound
+        AttackType::ArrowThrow => SoundEffect::Punch1,
     }
 }
 
@@ -151,21 +302,41 @@ pub fn attack(
         audio.play_sound_effect_scaled(attack_sound_lookup(attack_type), sound_loudness);
     }
 
-    // get strength of attack, lets say zombie scratch is 1
-    let attack_strength = match attack_type {
-        AttackType::FistPunch => 10,    // Fist punch deals 10 damage
-        AttackType::ZombieScratch => 5, // Zombie scratch deals 1 damage
+    // worn gear can hit harder
+    let mut damage = attack_damage(attack_type);
+    let damage_bonus = state
+        .entity_manager
+        .get_entity(*attacker)
+        .map_or(0.0, |e| e.equipment.stats().damage);
+    damage.amount = (damage.amount as f32 * (1.0 + damage_bonus)) as u32;
+    let hit = match deal_damage(state, *attacked, &damage) {
+        Some(hit) => hit,
+        None => return,
     };
     let attacker_pos = state.entity_manager.get_entity(*attacker).unwrap().pos;
     let attackee_pos = state.entity_manager.get_entity(*attacked).unwrap().pos;
-    if let Some(attacked_entity) = state.entity_manager.get_entity_mut(*attacked) {
-        if attacked_entity.health >= attack_strength {
-            attacked_entity.health -= attack_strength;
-        } else {
-            attacked_entity.health = 0;
+    let away = attackee_pos - attacker_pos;
+    if damage.type_.physical() {
+        let broken = state
+            .entity_manager
+            .get_entity_mut(*attacked)
+            .map_or(Vec::new(), |e| e.equipment.wear_armor());
+        for item in broken {
+            on_item_break(state, audio, attackee_pos, &item);
         }
-        // make them shake a little
-        attacked_entity.shake += 0.1; // Set shake to a moderate value for
+    }
+    match attack_type {
+        AttackType::ZombieScratch => status::apply_scratch_effects(state, *attacked),
+        AttackType::BatSwing => {
+            status::stun(state, *attacked, 1.0);
+            knock_back(state, *attacked, away, 2.0);
+        }
+        AttackType::FistPunch => knock_back(state, *attacked, away, 1.0),
+        AttackType::ArrowThrow => knock_back(state, *attacked, away, 0.5),
+    }
+    if let Some(attacked_entity) = state.entity_manager.get_entity_mut(*attacked) {
+        // make them shake a little, and a lot on a critical
+        attacked_entity.shake += if hit.critical { 0.3 } else { 0.1 };
 
         // lean attacker towards attackee at 45 degree angle if attacker is to left or right
         // if attacker is above, become 0 rot, if below, become 180 rot
@@ -245,6 +416,9 @@ pub fn indiscriminately_attack_nearby(state: &mut State, audio: &mut Audio, vid:
             return; // Not ready to attack yet
         }
     }
+    if status::is_stunned(state, vid) {
+        return;
+    }
 
     let pos = state.entity_manager.get_entity(vid).unwrap().pos.as_ivec2();
     let own_alignment = state.entity_manager.get_entity(vid).unwrap().alignment;
@@ -310,6 +484,12 @@ pub fn ready_to_move(state: &mut State, vid: VID) -> bool {
         if entity.move_cooldown_countdown > 0.0 {
             return false; // Not ready to move yet
         }
+        if entity.status.has(StatusKind::Stunned) {
+            return false; // Seeing stars
+        }
+        if entity.vel != Vec2::ZERO {
+            return false; // Still sliding from a hit
+        }
         return true; // Ready to move
     }
     false // Entity not found
@@ -321,10 +501,22 @@ pub fn step_move_cooldown(state: &mut State, vid: VID) {
         return; // Entity not found, exit early
     }
 
+    // zombies get about faster after dark and slower in a storm
+    let speed = day_night::zombie_speed_multiplier(&state.clock)
+        * weather::zombie_speed_multiplier(&state.weather);
+
     // Step the move cooldown countdown for the entity
     if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        let mut rate = if entity.type_ == EntityType::Zombie {
+            speed
+        } else {
+            1.0
+        };
+        if entity.status.has(StatusKind::Slowed) {
+            rate *= 0.5;
+        }
         if entity.move_cooldown_countdown > 0.0 {
-            entity.move_cooldown_countdown -= TIMESTEP;
+            entity.move_cooldown_countdown -= TIMESTEP * rate;
         } else {
             entity.move_cooldown_countdown = 0.0;
         }
@@ -333,7 +525,9 @@ pub fn step_move_cooldown(state: &mut State, vid: VID) {
 
 pub fn reset_move_cooldown(state: &mut State, vid: VID) {
     if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
-        entity.move_cooldown_countdown = entity.move_cooldown;
+        // worn gear can speed you up or weigh you down
+        let speed = (1.0 + entity.equipment.stats().move_speed).max(0.25);
+        entity.move_cooldown_countdown = entity.move_cooldown / speed;
     }
 }
 
@@ -348,6 +542,7 @@ pub fn calc_sound_loudness_from_player_dist_falloff(
     if let Some(player_vid) = state.player_vid {
         if let Some(player) = state.entity_manager.get_entity(player_vid) {
             let distance = sound_pos.distance(player.pos);
+            let hear_distance = hear_distance * hearing_multiplier(&state.weather);
             if distance < hear_distance {
                 // Volume falls off linearly with distance
                 return 1.0 - (distance / hear_distance);
@@ -374,10 +569,18 @@ pub fn move_entity_on_grid(
     // Get the grid representation of the target position
 
     // Check if the terrain is walkable
-    let terrain_is_walkable = state
+    let mut terrain_is_walkable = state
         .stage
-        .get_tile_type(target_grid_pos.x as usize, target_grid_pos.y as usize)
-        .is_some_and(|t| t.walkable());
+        .is_walkable(target_grid_pos.x as usize, target_grid_pos.y as usize);
+
+    // zombies and chickens respect lowered crossing barriers, the player is on their own
+    if rail::is_crossing_closed(&state.stage, target_grid_pos) {
+        if let Some(entity) = state.entity_manager.get_entity(vid) {
+            if matches!(entity.type_, EntityType::Zombie | EntityType::Chicken) {
+                terrain_is_walkable = false;
+            }
+        }
+    }
 
     // Check if the tile is already occupied by another impassable entity
     let tile_is_unoccupied = !is_tile_occupied(state, target_grid_pos);
@@ -395,6 +598,7 @@ pub fn move_entity_on_grid(
             moved = true;
         }
     } else {
+        status::touch_fire(state, vid, target_grid_pos);
         // fail to move sound, scale with dist // currently only if player
         if let Some(entity) = state.entity_manager.get_entity(vid) {
             if entity.type_ == crate::entity::EntityType::Player {
@@ -500,6 +704,10 @@ pub fn on_entity_death(state: &mut State, audio: &mut Audio, vid: VID) {
     let mut corpse_sprite = None;
     let mut entity_pos = glam::Vec2::ZERO;
     let mut entity_rot = 0.0;
+    let mut loot = None;
+    let mut leaves_body = false;
+    let mut is_container = false;
+    let mut contents = Vec::new();
     let mut should_spawn_effects = false;
 
     // --- Scope 1: Read Data (Immutable Borrow) ---
@@ -512,6 +720,12 @@ pub fn on_entity_death(state: &mut State, audio: &mut Audio, vid: VID) {
         };
         entity_pos = entity.pos;
         entity_rot = entity.rot;
+        loot = entity.loot;
+        leaves_body = entity.type_ == EntityType::Zombie;
+        is_container = entity.type_ == EntityType::Container;
+        if is_container {
+            contents = entity.inventory.iter().map(|entry| entry.item).collect();
+        }
         should_spawn_effects = true;
     }
     // The immutable borrow of `state` (via `entity`) ends here.
@@ -519,6 +733,21 @@ pub fn on_entity_death(state: &mut State, audio: &mut Audio, vid: VID) {
     // --- Scope 2: Apply Effects (Mutable Borrows) ---
     // Now that the immutable borrow is gone, we can safely mutate `state`.
     if should_spawn_effects {
+        // 0. Zombies keep what they had on them, and leave a body to search for it.
+        let mut dropped = loot.map_or(Vec::new(), |source| state.loot.roll(source));
+        if leaves_body && !dropped.is_empty() {
+            let body_pos = entity_pos.as_ivec2();
+            if let Some(body_vid) =
+                spawn_container(state, ContainerKind::Corpse, body_pos, &dropped)
+            {
+                if let Some(body) = state.entity_manager.get_entity_mut(body_vid) {
+                    body.rot = entity_rot;
+                }
+                corpse_sprite = None; // the body is the corpse
+                dropped.clear();
+            }
+        }
+
         // 1. Spawn a static particle for the corpse.
         if let Some(corpse_sprite) = corpse_sprite {
             let corpse_data = ParticleData::new(
@@ -542,6 +771,7 @@ pub fn on_entity_death(state: &mut State, audio: &mut Audio, vid: VID) {
             EntityType::RailLayer => SoundEffect::BoxBreak,
             EntityType::Train => SoundEffect::BoxBreak,
             EntityType::Item => SoundEffect::BoxBreak,
+            EntityType::Container => SoundEffect::BoxBreak,
         };
         let sound_loudness = calc_sound_loudness_from_player_dist_falloff(
             state,
@@ -552,15 +782,22 @@ pub fn on_entity_death(state: &mut State, audio: &mut Audio, vid: VID) {
             audio.play_sound_effect_scaled(death_sound_effect, sound_loudness);
         }
 
-        // 3. Spawn blood and gore effects.
-        blood_splatter(
-            state,
-            audio,
-            entity_pos,
-            glam::Vec2::new(0.0, -1.0), // Splatter moves generally upwards
-            0.8,                        // A good amount of splatter
-        );
-        blood_puddle(&mut state.particles, entity_pos, 1.0);
+        // 3. Spawn blood and gore effects, chests and crates just break.
+        if !is_container {
+            blood_splatter(
+                state,
+                audio,
+                entity_pos,
+                glam::Vec2::new(0.0, -1.0), // Splatter moves generally upwards
+                0.8,                        // A good amount of splatter
+            );
+            blood_puddle(&mut state.particles, entity_pos, 1.0);
+        }
+
+        // 4. Leave something behind, and spill whatever it was holding.
+        for item in dropped.into_iter().chain(contents) {
+            drop_item(state, audio, item, entity_pos.as_ivec2());
+        }
     }
 }
 
@@ -578,6 +815,18 @@ pub fn die_if_health_zero(state: &mut State, audio: &mut Audio, vid: VID) {
         // Trigger all the death effects (sound, particles, corpse).
         on_entity_death(state, audio, vid);
 
+        // tally kills for the end screen, tougher zombies are worth more
+        if let Some(zombie) = state
+            .entity_manager
+            .get_entity(vid)
+            .filter(|e| e.type_ == EntityType::Zombie)
+        {
+            const MIN_KILL_POINTS: u32 = 10;
+            let points = (zombie.max_hp / 4).max(MIN_KILL_POINTS);
+            state.run_stats.kills += 1;
+            award_points(state, points);
+        }
+
         // Mark the entity for cleanup at the end of the frame.
         if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
             entity.marked_for_destruction = true;
@@ -658,35 +907,12 @@ pub fn step_rail_layer(state: &mut State, audio: &mut Audio, vid: VID) {
         }
         start_pos -= opposite_direction; // Step back to the last valid position
 
-        // kill all entities in the start position instantly, just set health to 0
-        // get all vids in the spatial grid at the start position
-        if !state.stage.in_bounds(start_pos) {
-            return;
-        }
-        let vids_in_start_pos = &state.spatial_grid[start_pos.x as usize][start_pos.y as usize];
-
-        for entity_vid in vids_in_start_pos {
-            if *entity_vid != vid {
-                if let Some(entity) = state.entity_manager.get_entity_mut(*entity_vid) {
-                    entity.health = 0; // Set health to 0 to simulate instant death
-                }
-            }
-        }
-
-        // spawn a train at the start position
-        if let Some(new_entity_vid) = state.entity_manager.new_entity() {
-            if let Some(entity) = state.entity_manager.get_entity_mut(new_entity_vid) {
-                init_as_train(entity);
-                entity.pos = start_pos.as_vec2() + Vec2::splat(0.5); // Center the train on the grid tile
-                                                                     // set the direction to be the same as the rail layer
-                entity.direction = direction;
-                // set target position to be its starting position
-                entity.target_pos = Some(start_pos.as_vec2() + Vec2::splat(0.5));
-                // do int 5-20, cast to float
-                let train_length: f32 = (random_range(5..=20) as f32).floor();
-                entity.counter_a = train_length;
-            }
-        }
+        // spawn a train at the start position, heading the same way the rail layer went.
+        // the rest of the cars roll in behind it as it clears the edge
+        let train_length = random_range(5..=20);
+        let mut consist = Consist::new(start_pos, direction, train_length);
+        consist.speed = consist.max_speed; // arrives at full tilt
+        spawn_train(state, consist);
         return;
     }
 
@@ -713,156 +939,438 @@ pub fn step_rail_layer(state: &mut State, audio: &mut Audio, vid: VID) {
     move_entity_on_grid(state, audio, vid, new_pos, true, true, false);
 }
 
-/// now a step train
+/// Steps a train consist along the track.
 /*
-    a train should move in the direction is is facing if that target tile is a rail tile
-    when a train moves, it should immediatly do 1000 damage to all entities in the target tile (except trains)
-    when a trains next move will put it off the map, mark itself for destruction
-    trains ignore entity collision, but first check to make sure the target position doesnt have a train before trying to move
-
-
-    if the target tile is not a rail tile, it should set its own hp to 0
+    the head follows the rail topology and every car steps into the tile the car ahead left
+    the train speeds up toward its max speed, and brakes when it sees trouble within its stopping distance
+    it slows for the next station, stops with the head on it, pays out cargo and waits out its dwell time
+    when the head moves, it immediately does 1000 damage to all entities in the target tile (except trains)
+    a train blocked by another train stops dead, a train that runs off the end of the track derails
+    when the head runs off the map it is removed, and the rest of the cars follow it out
 
     later: (do not implement now)
-        and spawn a fire and a bunch of smoke particles
+        derailing should spawn a fire and a bunch of smoke particles
 */
 pub fn step_train(state: &mut State, audio: &mut Audio, vid: VID) {
-    // check if exists
-    if state.entity_manager.get_entity(vid).is_none() {
-        return; // Entity not found, exit early
-    }
+    // take the consist out while we work on it, trains without one don't move
+    let mut consist = match state.entity_manager.get_entity_mut(vid) {
+        Some(entity) if entity.type_ == EntityType::Train && !entity.marked_for_destruction => {
+            match entity.consist.take() {
+                Some(consist) => consist,
+                None => return,
+            }
+        }
+        _ => return,
+    };
 
-    // check if entity is a train
-    if let Some(entity) = state.entity_manager.get_entity(vid) {
-        if entity.type_ != crate::entity::EntityType::Train {
-            return; // Only trains should step this way
+    // waiting at a station
+    if consist.is_dwelling() {
+        consist.dwell = (consist.dwell - TIMESTEP).max(0.0);
+        if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+            entity.consist = Some(consist);
         }
+        return;
     }
 
-    // check if entity is ready to move
-    if !ready_to_move(state, vid) {
-        return; // Not ready to move yet
+    // brake for anything within stopping distance, otherwise open the throttle
+    const STATION_APPROACH_SPEED: f32 = 3.0;
+    let look_ahead = consist.stopping_distance().ceil() as i32 + 1;
+    match look_down_the_line(state, vid, &consist, look_ahead) {
+        Some(TrackAhead::Obstacle) => {
+            consist.speed = (consist.speed - consist.braking * TIMESTEP).max(0.0);
+        }
+        Some(TrackAhead::Station) => {
+            // roll in slowly rather than stopping short of the platform
+            consist.speed = if consist.speed > STATION_APPROACH_SPEED {
+                (consist.speed - consist.braking * TIMESTEP).max(STATION_APPROACH_SPEED)
+            } else {
+                (consist.speed + consist.acceleration * TIMESTEP).min(STATION_APPROACH_SPEED)
+            };
+        }
+        None => {
+            consist.speed =
+                (consist.speed + consist.acceleration * TIMESTEP).min(consist.max_speed);
+        }
     }
 
-    // get current position and direction
-    let current_pos = state.entity_manager.get_entity(vid).unwrap().pos.as_ivec2();
-    let direction = state.entity_manager.get_entity(vid).unwrap().direction;
+    consist.progress += consist.speed * TIMESTEP;
+    let mut outcome = TrainAdvance::Moved;
+    while consist.progress >= 1.0 {
+        consist.progress -= 1.0;
+        outcome = advance_consist(state, audio, vid, &mut consist);
+        if outcome != TrainAdvance::Moved {
+            break;
+        }
 
-    // calculate new position based on direction
-    let new_pos = current_pos + direction;
+        // pull in with the head on the platform
+        if let Some(head) = consist.head().copied() {
+            if rail::is_station_at(&state.stage, head.pos) && consist.last_station != Some(head.pos)
+            {
+                let points = consist.arrive_at_station(head.pos);
+                if points > 0 {
+                    award_points(state, points);
+                    audio.play_sound_effect(SoundEffect::SuperConfirm);
+                }
+                break;
+            }
+        }
+    }
 
-    // check if new position is within bounds of the stage
-    if !state.stage.in_bounds(new_pos) {
-        // mark for destruction if out of bounds
-        if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
-            entity.marked_for_destruction = true;
+    match outcome {
+        TrainAdvance::Moved => {}
+        TrainAdvance::Blocked => {
+            // hit something we couldn't stop for, stop dead
+            consist.speed = 0.0;
+            consist.progress = 0.0;
+            if let Some(head) = consist.head() {
+                tile_shake_area_at(state, head.pos, 2.0, 2.0);
+                let sound_loudness = calc_sound_loudness_from_player_dist_falloff(
+                    state,
+                    head.pos.as_vec2() + Vec2::splat(0.5),
+                    BASE_SOUND_HEAR_DISTANCE,
+                );
+                if sound_loudness > 0.0 {
+                    audio.play_sound_effect_scaled(SoundEffect::HitBlock1, sound_loudness);
+                }
+            }
+            if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+                entity.shake = 0.2;
+            }
+        }
+        TrainAdvance::Derailed => {
+            const DERAIL_BLAST_RADIUS: f32 = 2.5;
+            const DERAIL_BLAST_DAMAGE: u32 = 80;
+            if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+                entity.health = 0;
+            }
+            if let Some(head) = consist.head() {
+                let center = head.pos.as_vec2() + Vec2::splat(0.5);
+                explode(
+                    state,
+                    audio,
+                    center,
+                    DERAIL_BLAST_RADIUS,
+                    DERAIL_BLAST_DAMAGE,
+                );
+            }
         }
-        return;
     }
 
-    // check if the target tile is a rail tile
-    let target_tile = state
-        .stage
-        .get_tile_type(new_pos.x as usize, new_pos.y as usize);
-    if target_tile.is_none() || target_tile.unwrap() != Tile::Rail {
-        // set own hp to 0 and mark for destruction
-        if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
-            entity.health = 0;
-            println!(
-                "Train hit non-rail tile, marking for destruction: {:?}",
-                vid
-            );
+    // put the consist back and keep the entity on the head car
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        if let Some(head) = consist.head() {
+            entity.pos = head.pos.as_vec2() + Vec2::splat(0.5);
+            entity.direction = head.direction;
+        } else if consist.is_empty() {
+            // every car has left the map
+            entity.marked_for_destruction = true;
         }
-        return;
+        entity.consist = Some(consist);
     }
+}
 
-    // check for other trains in the target position
-    // pub spatial_grid: Vec<Vec<Vec<VID>>>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TrainAdvance {
+    Moved,
+    Blocked,
+    Derailed,
+}
 
-    let other_trains_in_target = state.spatial_grid[new_pos.x as usize][new_pos.y as usize]
+/// True if a train other than `vid` has a car on `pos`.
+fn other_train_at(state: &State, vid: VID, pos: IVec2) -> bool {
+    state.spatial_grid[pos.x as usize][pos.y as usize]
         .iter()
         .any(|&other_vid| {
-            if let Some(other_entity) = state.entity_manager.get_entity(other_vid) {
-                other_entity.type_ == EntityType::Train && other_vid != vid
-            } else {
-                false // Entity not found, treat as not a train
-            }
-        });
+            other_vid != vid
+                && state
+                    .entity_manager
+                    .get_entity(other_vid)
+                    .is_some_and(|other| other.type_ == EntityType::Train)
+        })
+}
 
-    if other_trains_in_target {
-        // do not move
-        return;
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TrackAhead {
+    /// Another train, or the end of the line.
+    Obstacle,
+    /// A station the train hasn't stopped at yet.
+    Station,
+}
+
+/// Walks the track in front of the head and returns the first thing the train needs to
+/// slow down for, if there's one within `max_distance` tiles.
+fn look_down_the_line(
+    state: &State,
+    vid: VID,
+    consist: &Consist,
+    max_distance: i32,
+) -> Option<TrackAhead> {
+    let head = consist.head()?;
+    let mut pos = head.pos;
+    let mut heading = head.direction;
+    for _ in 1..=max_distance {
+        let direction = match rail::next_rail_direction(&state.stage, pos, heading) {
+            Some(direction) => direction,
+            None => return Some(TrackAhead::Obstacle),
+        };
+        pos += direction;
+        heading = direction;
+        if !state.stage.in_bounds(pos) {
+            return None; // clear run off the map
+        }
+        if !rail::is_rail_at(&state.stage, pos) || other_train_at(state, vid, pos) {
+            return Some(TrackAhead::Obstacle);
+        }
+        if rail::is_station_at(&state.stage, pos) && consist.last_station != Some(pos) {
+            return Some(TrackAhead::Station);
+        }
     }
+    None
+}
 
-    // move the entity to the new position
-    let moved = move_entity_on_grid(state, audio, vid, new_pos, true, true, false);
-    let mut hit_entities: Vec<VID> = vec![];
-    if moved {
-        // get the target tile data
-        // damage all entities in the target tile (except trains)
-        for entity in state.spatial_grid[new_pos.x as usize][new_pos.y as usize]
-            .iter()
-            .filter_map(|&vid| state.entity_manager.get_entity(vid))
-        {
-            if entity.type_ != crate::entity::EntityType::Train {
-                hit_entities.push(entity.vid);
-            }
+/// Moves the whole consist one tile along the track.
+fn advance_consist(
+    state: &mut State,
+    audio: &mut Audio,
+    vid: VID,
+    consist: &mut Consist,
+) -> TrainAdvance {
+    let head = match consist.head() {
+        Some(head) => *head,
+        None => {
+            // the last cars on the map have left, nothing more to move
+            consist.pending.clear();
+            return TrainAdvance::Moved;
+        }
+    };
+
+    // follow the track: curves turn the train, switches pick the branch at junctions
+    let direction = match rail::next_rail_direction(&state.stage, head.pos, head.direction) {
+        Some(direction) => direction,
+        None => return TrainAdvance::Derailed, // dead end
+    };
+    let new_pos = head.pos + direction;
+    let head_leaves_map = !state.stage.in_bounds(new_pos);
+
+    if !head_leaves_map {
+        if other_train_at(state, vid, new_pos) {
+            return TrainAdvance::Blocked;
         }
+        if !rail::is_rail_at(&state.stage, new_pos) {
+            return TrainAdvance::Derailed;
+        }
+    }
 
-        tile_shake_area_at(state, new_pos, 2.0, 2.0);
+    // every car steps into the tile the one in front of it just left
+    let old_cars = consist.cars.clone();
+    for car in &old_cars {
+        state.remove_entity_from_grid(vid, car.pos);
+    }
+    for i in (1..consist.cars.len()).rev() {
+        consist.cars[i].pos = old_cars[i - 1].pos;
+        consist.cars[i].direction = old_cars[i - 1].pos - old_cars[i].pos;
+    }
+    consist.cars[0].pos = new_pos;
+    consist.cars[0].direction = direction;
 
-        // fetch a target position, if none, dont do this part
+    // the front car rolls off the map, the next one takes the lead
+    if head_leaves_map {
+        consist.cars.remove(0);
+    }
 
-        pub struct NewTrain {
-            pub pos: Vec2,
-            pub direction: IVec2,
-            pub sprite: Option<Sprite>,
+    // the next waiting car enters where the tail was
+    if !consist.pending.is_empty() {
+        if let Some(tail) = old_cars.last() {
+            let sprite = consist.pending.remove(0);
+            consist.cars.push(TrainCar {
+                sprite,
+                pos: tail.pos,
+                direction: tail.direction,
+                cargo: None,
+            });
         }
-        let mut new_train: Option<NewTrain> = None;
-        if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
-            if let Some(target_pos) = entity.target_pos {
-                // if counter_a is 0, spawn a train at the target position
-                // if counter_a is 1, spawn a caboose at the target position
-                // if counter_a is 2-inf, spawn a traincar at the target position
-                if entity.counter_a >= 2.0 {
-                    // spawn a traincar
-                    new_train = Some(NewTrain {
-                        pos: target_pos,
-                        direction: entity.direction,
-                        sprite: Some(Sprite::TrainCarA),
-                    });
-                } else if entity.counter_a > 0.0 {
-                    // spawn a caboose
-                    new_train = Some(NewTrain {
-                        pos: target_pos,
-                        direction: entity.direction,
-                        sprite: Some(Sprite::Caboose),
-                    });
-                }
-                // decrement the counter_a
-                entity.counter_a -= 1.0;
-                entity.counter_a = entity.counter_a.max(0.0);
+    }
+
+    for car in &consist.cars {
+        state.add_entity_to_grid(vid, car.pos);
+    }
+
+    if head_leaves_map {
+        return TrainAdvance::Moved;
+    }
+
+    // throw everything in the way off to the side (except trains), what can't be thrown or has
+    // nowhere to land gets run over
+    const TRAIN_HIT_DAMAGE: u32 = 60;
+    const TRAIN_THROW: f32 = 3.0;
+    let hit_entities: Vec<VID> = state.spatial_grid[new_pos.x as usize][new_pos.y as usize]
+        .iter()
+        .filter_map(|&other_vid| state.entity_manager.get_entity(other_vid))
+        .filter(|entity| entity.type_ != EntityType::Train)
+        .map(|entity| entity.vid)
+        .collect();
+    let side = IVec2::new(-direction.y, direction.x);
+    let sides = if random_range(0..2) == 0 {
+        [side, -side]
+    } else {
+        [-side, side]
+    };
+    let throw = sides.into_iter().find(|&side| {
+        let landing = new_pos + side;
+        state.stage.in_bounds(landing)
+            && state
+                .stage
+                .is_walkable(landing.x as usize, landing.y as usize)
+            && !is_tile_occupied(state, landing)
+    });
+    for hit_vid in hit_entities {
+        deal_damage(
+            state,
+            hit_vid,
+            &Damage::new(TRAIN_HIT_DAMAGE, DamageType::Blunt),
+        );
+        if let Some(throw) = throw {
+            knock_back(state, hit_vid, throw.as_vec2(), TRAIN_THROW);
+        }
+        if throw.is_none() || !is_sliding(state, hit_vid) {
+            if let Some(hit_entity) = state.entity_manager.get_entity_mut(hit_vid) {
+                hit_entity.health = 0;
             }
         }
+    }
 
-        // if we have a new train, spawn it
-        if let Some(new_train) = new_train {
-            if let Some(new_entity_vid) = state.entity_manager.new_entity() {
-                if let Some(entity) = state.entity_manager.get_entity_mut(new_entity_vid) {
-                    init_as_train(entity);
-                    entity.pos = new_train.pos; // Set the position to the target position
-                    entity.direction = new_train.direction; // Set the direction to the same as the train
-                    entity.sprite = new_train.sprite; // Set the sprite to the train car sprite
-                }
-            }
+    tile_shake_area_at(state, new_pos, 2.0, 2.0);
+
+    // clatter
+    let sound_loudness = calc_sound_loudness_from_player_dist_falloff(
+        state,
+        new_pos.as_vec2() + Vec2::splat(0.5),
+        STEP_SOUND_HEAR_DISTANCE,
+    );
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        if sound_loudness > 0.0 {
+            audio.play_sound_effect_scaled(entity_step_sound_lookup(entity), sound_loudness);
+            swap_step_sound(entity);
         }
+        lean_entity(entity);
     }
 
-    // apply damage to all hit entities
-    for hit_vid in hit_entities {
-        if let Some(hit_entity) = state.entity_manager.get_entity_mut(hit_vid) {
-            // apply 1000 damage
-            hit_entity.health = hit_entity.health.saturating_sub(1000);
+    TrainAdvance::Moved
+}
+
+/// Puts a new train on the map at its head car's position, clearing whatever was standing there.
+pub fn spawn_train(state: &mut State, consist: Consist) -> Option<VID> {
+    let start_pos = consist.head()?.pos;
+    if !state.stage.in_bounds(start_pos) {
+        return None;
+    }
+
+    // kill all entities in the start position instantly, just set health to 0
+    let vids_in_start_pos = &state.spatial_grid[start_pos.x as usize][start_pos.y as usize];
+    for entity_vid in vids_in_start_pos {
+        if let Some(entity) = state.entity_manager.get_entity_mut(*entity_vid) {
+            entity.health = 0; // Set health to 0 to simulate instant death
+        }
+    }
+
+    let vid = state.entity_manager.new_entity()?;
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        init_as_train(entity, consist);
+    }
+    state.add_entity_to_grid(vid, start_pos);
+    Some(vid)
+}
+
+/// Sends a train down the stage's rail line whenever the timetable says one is due.
+pub fn dispatch_trains(state: &mut State, audio: &mut Audio) {
+    let row = match state.stage_layout.rail_row {
+        Some(row) => row,
+        None => return,
+    };
+
+    state.timetable.countdown -= TIMESTEP;
+    if state.timetable.countdown > 0.0 {
+        return;
+    }
+
+    let (start_pos, direction) = if state.timetable.eastbound {
+        (IVec2::new(0, row), IVec2::new(1, 0))
+    } else {
+        (
+            IVec2::new(state.stage.get_width() as i32 - 1, row),
+            IVec2::new(-1, 0),
+        )
+    };
+
+    // the line might have been torn up, or the last train is still on it. There's only the
+    // one track, so sending another the other way would run them into each other
+    let line_busy = state
+        .entity_manager
+        .iter()
+        .any(|e| e.active && e.type_ == EntityType::Train);
+    if !rail::is_rail_at(&state.stage, start_pos) || line_busy {
+        state.timetable.countdown = 1.0; // try again shortly
+        return;
+    }
+
+    let train_length = random_range(4..=10);
+    let mut consist = Consist::new(start_pos, direction, train_length);
+    consist.speed = consist.max_speed * 0.5;
+    if spawn_train(state, consist).is_some() {
+        audio.play_sound_effect(SoundEffect::DistantTrainSound);
+    }
+
+    state.timetable.countdown = state.timetable.interval;
+    state.timetable.eastbound = !state.timetable.eastbound;
+}
+
+/// Lowers the barriers and flashes the lights at every crossing with a train coming,
+/// ringing out so the player can hear it from a distance.
+pub fn step_crossings(state: &mut State, audio: &mut Audio) {
+    const WARNING_DISTANCE: i32 = 10;
+    const BLINK_FRAMES: u32 = 20;
+    const BELL_FRAMES: u32 = 90;
+
+    let train_cars: Vec<IVec2> = state
+        .entity_manager
+        .iter()
+        .filter(|e| e.active && e.type_ == EntityType::Train)
+        .filter_map(|e| e.consist.as_ref())
+        .flat_map(|consist| consist.cars.iter().map(|car| car.pos))
+        .collect();
+
+    for crossing in state.stage_layout.crossings.clone() {
+        let train_near = train_cars
+            .iter()
+            .any(|car| new_york_dist(*car, crossing) <= WARNING_DISTANCE);
+
+        let was_closed = rail::is_crossing_closed(&state.stage, crossing);
+        if let Some(tile_data) = state.stage.get_layer_tile_mut(
+            TileLayer::Structure,
+            crossing.x as usize,
+            crossing.y as usize,
+        ) {
+            if tile_data.tile != Tile::Crossing {
+                continue; // torn up
+            }
+            tile_data.switched = train_near;
+            tile_data.variant = if train_near {
+                ((state.frame / BLINK_FRAMES) % 2) as u8
+            } else {
+                0
+            };
+        }
+
+        // ring when the barriers come down, and keep ringing while they're down
+        if train_near && (!was_closed || state.frame % BELL_FRAMES == 0) {
+            let sound_loudness = calc_sound_loudness_from_player_dist_falloff(
+                state,
+                crossing.as_vec2() + Vec2::splat(0.5),
+                BASE_SOUND_HEAR_DISTANCE * 2.0,
+            );
+            if sound_loudness > 0.0 {
+                audio.play_sound_effect_scaled(SoundEffect::DistantTrainSound, sound_loudness);
+            }
         }
     }
 }
@@ -937,3 +1445,51 @@ pub fn pick_random_tile_position_in_radius_include_center(pos: IVec2, radius: i3
         IVec2::new(pos.x + x_offset, pos.y + y_offset)
     }
 }
+
+/// Zombies standing on spikes take a stab every so often, and wear the spikes down doing it.
+pub fn step_spike_traps(state: &mut State, audio: &mut Audio) {
+    const SPIKE_INTERVAL: u32 = 30; // frames
+    const SPIKE_DAMAGE: u32 = 15;
+    const SPIKE_WEAR: u8 = 10;
+
+    if state.frame % SPIKE_INTERVAL != 0 {
+        return;
+    }
+    let spike = Damage::new(SPIKE_DAMAGE, DamageType::Pierce);
+
+    let victims: Vec<(VID, IVec2)> = state
+        .entity_manager
+        .iter()
+        .filter(|e| e.active && !e.marked_for_destruction && e.alignment == Alignment::Enemy)
+        .map(|e| (e.vid, e.pos.as_ivec2()))
+        .filter(|(_, pos)| {
+            state
+                .stage
+                .get_layer_tile(TileLayer::Structure, pos.x as usize, pos.y as usize)
+                .is_some_and(|td| td.tile == Tile::Spikes)
+        })
+        .collect();
+
+    for (vid, pos) in victims {
+        deal_damage(state, vid, &spike);
+        if let Some(zombie) = state.entity_manager.get_entity_mut(vid) {
+            zombie.shake += 0.1;
+        }
+        blood_splatter(
+            state,
+            audio,
+            pos.as_vec2() + Vec2::splat(0.5),
+            Vec2::new(0.0, -1.0),
+            0.5,
+        );
+        damage_tile(
+            state,
+            audio,
+            pos,
+            SPIKE_WEAR,
+            DamageType::Pierce,
+            pos.as_vec2() + Vec2::splat(0.5),
+        );
+        die_if_health_zero(state, audio, vid);
+    }
+}
@@ -3,9 +3,13 @@ use rand::random_range;
 
 use crate::{
     audio::SoundEffect,
-    entity::{Alignment, DamageVulnerability, Entity, EntityType, Mood},
+    containers::ContainerKind,
+    damage::{DamageType, Resistances},
+    entity::{Alignment, Entity, EntityType, Mood},
     item::{Item, ItemType},
+    loot::LootSource,
     sprite::Sprite,
+    train::Consist,
 };
 
 pub fn init_as_player(entity: &mut Entity) {
@@ -42,9 +46,25 @@ pub fn init_as_player(entity: &mut Entity) {
     bandaid_item.count = 20.max(bandaid_item.max_count); // Start with 20 bandaids
     entity.inventory.insert(bandaid_item);
 
-    // give a conductor hat
+    // give a conductor hat, and the whistle to go with it
     let conductor_hat_item = Item::new(ItemType::ConductorHat);
     entity.inventory.insert(conductor_hat_item);
+    let whistle_item = Item::new(ItemType::Whistle);
+    entity.inventory.insert(whistle_item);
+
+    // and some track to play with
+    let mut rail_item = Item::new(ItemType::Rail);
+    rail_item.count = 50;
+    entity.inventory.insert(rail_item);
+
+    let mut rail_switch_item = Item::new(ItemType::RailSwitch);
+    rail_switch_item.count = 5;
+    entity.inventory.insert(rail_switch_item);
+
+    // a few torches for the first night
+    let mut torch_item = Item::new(ItemType::Torch);
+    torch_item.count = 5;
+    entity.inventory.insert(torch_item);
 }
 
 pub fn init_as_zombie(entity: &mut Entity) {
@@ -58,8 +78,11 @@ pub fn init_as_zombie(entity: &mut Entity) {
     entity.attack_cooldown = 1.0;
     entity.health = 40;
     entity.max_hp = 40;
+    entity.can_be_stunned = true;
+    entity.resistances.set(DamageType::Fire, -0.5); // dry and rotten, goes up like kindling
     entity.death_sound = Some(SoundEffect::AnimalCrush1);
     entity.attackable = true;
+    entity.loot = Some(LootSource::Zombie);
     // randomize move cooldown timer in range
     entity.move_cooldown_countdown = rand::random::<f32>() * entity.move_cooldown;
     // randomize step sound, 1 or 2
@@ -105,6 +128,7 @@ pub fn init_as_chicken(entity: &mut Entity) {
             entity.health = 1; // Less health for chicks
             entity.max_hp = 1;
             entity.growl = Some(SoundEffect::Chick);
+            entity.loot = Some(LootSource::Chick);
         }
         ChickenType::Hen => {
             entity.sprite = Some(Sprite::Hen);
@@ -112,6 +136,7 @@ pub fn init_as_chicken(entity: &mut Entity) {
             entity.health = 3; // More health for hens
             entity.max_hp = 3;
             entity.growl = Some(SoundEffect::Hen);
+            entity.loot = Some(LootSource::Hen);
         }
         ChickenType::Rooster => {
             entity.sprite = Some(Sprite::Rooster);
@@ -119,6 +144,7 @@ pub fn init_as_chicken(entity: &mut Entity) {
             entity.health = 30; // More health for roosters
             entity.max_hp = 30;
             entity.growl = Some(SoundEffect::Rooster);
+            entity.loot = Some(LootSource::Rooster);
         }
     }
 
@@ -142,23 +168,26 @@ pub fn init_as_rail_layer(entity: &mut Entity) {
     entity.move_cooldown_countdown = entity.move_cooldown;
     entity.health = 10000000;
     entity.max_hp = 10000000;
-    entity.damage_vulnerability = DamageVulnerability::Immune;
+    entity.resistances = Resistances::immune();
     entity.death_sound = None;
     entity.attackable = false;
 }
 
-pub fn init_as_train(entity: &mut Entity) {
+pub fn init_as_train(entity: &mut Entity, consist: Consist) {
     entity.active = true;
     entity.type_ = EntityType::Train;
     entity.sprite = Some(Sprite::TrainHead);
     entity.impassable = true;
     entity.alignment = Alignment::Neutral;
     entity.mood = Mood::Idle;
-    entity.move_cooldown = 0.02; // Faster movement for train
-    entity.move_cooldown_countdown = entity.move_cooldown;
+    // the consist's speed drives movement, not the move cooldown
+    if let Some(head) = consist.head() {
+        entity.pos = head.pos.as_vec2() + Vec2::splat(0.5);
+        entity.direction = head.direction;
+    }
+    entity.consist = Some(consist);
     entity.health = 10000000;
     entity.max_hp = 10000000;
-    entity.damage_vulnerability = DamageVulnerability::NotImmune;
     entity.size = Vec2::new(2.0, 2.0); // Train is larger than other entities
     entity.death_sound = Some(SoundEffect::BoxBreak);
     entity.attackable = true;
@@ -170,10 +199,31 @@ pub fn init_as_item(entity: &mut Entity, item: Item) {
     entity.item = Some(item);
     entity.size = Vec2::new(0.5, 0.5);
     entity.impassable = false;
-    entity.damage_vulnerability = DamageVulnerability::Immune;
+    entity.resistances = Resistances::immune();
     entity.health = 1000;
     entity.max_hp = 1000;
     entity.sprite = item.sprite;
     entity.alignment = Alignment::Neutral;
     entity.attackable = false;
 }
+
+pub fn init_as_container(entity: &mut Entity, kind: ContainerKind) {
+    entity.active = true;
+    entity.type_ = EntityType::Container;
+    entity.container = Some(kind);
+    let (sprite, slots, impassable) = match kind {
+        ContainerKind::Chest => (Sprite::Chest, 12, true),
+        ContainerKind::Crate => (Sprite::Crate, 6, true),
+        // bodies lie flat, they can be walked over and searched from underfoot
+        ContainerKind::Corpse => (Sprite::ZombieDead, 4, false),
+    };
+    entity.sprite = Some(sprite);
+    entity.inventory.capacity = slots;
+    entity.impassable = impassable;
+    entity.counter_a = 0.0; // how long a body has been lying there
+    entity.resistances = Resistances::immune();
+    entity.health = 1000;
+    entity.max_hp = 1000;
+    entity.alignment = Alignment::Neutral;
+    entity.attackable = false;
+}
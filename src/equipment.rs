@@ -0,0 +1,187 @@
+/* Equipment.
+   What an entity is wearing, one item per slot, kept apart from the inventory. Using a
+   wearable item from the inventory puts it on (and whatever was in that slot goes back
+   where it came from), taking things off puts them back in the inventory.
+   Worn items add up into `EquipStats`, which the rest of the game reads: armor in the
+   damage pipeline, move speed when the move cooldown resets, view distance for sight
+   and melee damage when attacking. Anything with armor wears down as it takes hits, and
+   falls apart when it runs out.
+*/
+
+use crate::{
+    entity::Entity,
+    item::{Item, ItemType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquipSlot {
+    Head,
+    Body,
+    Hands,
+    Offhand,
+}
+
+pub const EQUIP_SLOTS: [EquipSlot; 4] = [
+    EquipSlot::Head,
+    EquipSlot::Body,
+    EquipSlot::Hands,
+    EquipSlot::Offhand,
+];
+
+impl EquipSlot {
+    pub fn name(self) -> &'static str {
+        match self {
+            EquipSlot::Head => "Head",
+            EquipSlot::Body => "Body",
+            EquipSlot::Hands => "Hands",
+            EquipSlot::Offhand => "Offhand",
+        }
+    }
+}
+
+/// What wearing something does. Everything adds up across slots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquipStats {
+    /// Flat amount taken off every physical hit.
+    pub armor: u32,
+    /// Fraction faster to move, negative is slower.
+    pub move_speed: f32,
+    /// Extra tiles of sight.
+    pub view_distance: f32,
+    /// Fraction more melee damage.
+    pub damage: f32,
+}
+
+impl EquipStats {
+    pub fn new() -> EquipStats {
+        EquipStats {
+            armor: 0,
+            move_speed: 0.0,
+            view_distance: 0.0,
+            damage: 0.0,
+        }
+    }
+}
+
+/// Where an item goes and what it does once it's on, or None if it can't be worn.
+pub fn equip_info(item_type: ItemType) -> Option<(EquipSlot, EquipStats)> {
+    let mut stats = EquipStats::new();
+    let slot = match item_type {
+        ItemType::ConductorHat => {
+            stats.view_distance = 2.0; // see the trains coming
+            EquipSlot::Head
+        }
+        ItemType::Helmet => {
+            stats.armor = 3;
+            stats.move_speed = -0.05;
+            EquipSlot::Head
+        }
+        ItemType::Jacket => {
+            stats.armor = 2;
+            EquipSlot::Body
+        }
+        ItemType::Gloves => {
+            stats.armor = 1;
+            stats.damage = 0.25;
+            EquipSlot::Hands
+        }
+        ItemType::Lantern => {
+            stats.view_distance = 3.0;
+            EquipSlot::Offhand
+        }
+        _ => return None,
+    };
+    Some((slot, stats))
+}
+
+#[derive(Debug, Clone)]
+pub struct Equipment {
+    slots: [Option<Item>; EQUIP_SLOTS.len()],
+}
+
+impl Equipment {
+    pub fn new() -> Equipment {
+        Equipment {
+            slots: [None; EQUIP_SLOTS.len()],
+        }
+    }
+
+    pub fn get(&self, slot: EquipSlot) -> Option<&Item> {
+        self.slots[slot as usize].as_ref()
+    }
+
+    pub fn get_mut(&mut self, slot: EquipSlot) -> Option<&mut Item> {
+        self.slots[slot as usize].as_mut()
+    }
+
+    /// Puts an item on, returning whatever was in its slot before. Gives the item straight
+    /// back if it isn't wearable.
+    pub fn equip(&mut self, item: Item) -> Result<Option<Item>, Item> {
+        match equip_info(item.type_) {
+            Some((slot, _)) => Ok(self.slots[slot as usize].replace(item)),
+            None => Err(item),
+        }
+    }
+
+    pub fn unequip(&mut self, slot: EquipSlot) -> Option<Item> {
+        self.slots[slot as usize].take()
+    }
+
+    /// Worn items with the slot each is in.
+    pub fn iter(&self) -> impl Iterator<Item = (EquipSlot, &Item)> {
+        EQUIP_SLOTS
+            .iter()
+            .zip(self.slots.iter())
+            .filter_map(|(&slot, item)| item.as_ref().map(|item| (slot, item)))
+    }
+
+    /// Every worn piece that has armor takes a hit's worth of wear. Anything that breaks comes
+    /// off and is returned.
+    pub fn wear_armor(&mut self) -> Vec<Item> {
+        let mut broken = Vec::new();
+        for slot in self.slots.iter_mut() {
+            let stops_hits = slot
+                .as_ref()
+                .and_then(|item| equip_info(item.type_))
+                .is_some_and(|(_, stats)| stats.armor > 0);
+            if stops_hits && slot.as_mut().is_some_and(|item| item.wear()) {
+                broken.extend(slot.take());
+            }
+        }
+        broken
+    }
+
+    /// Everything being worn added together.
+    pub fn stats(&self) -> EquipStats {
+        let mut total = EquipStats::new();
+        for (_, item) in self.iter() {
+            if let Some((_, stats)) = equip_info(item.type_) {
+                total.armor += stats.armor;
+                total.move_speed += stats.move_speed;
+                total.view_distance += stats.view_distance;
+                total.damage += stats.damage;
+            }
+        }
+        total
+    }
+}
+
+/// Takes off the last worn piece (offhand first, head last) and puts it in the inventory.
+/// Returns false if there's nothing on or no room for it.
+pub fn take_off_last(entity: &mut Entity) -> bool {
+    let slot = match EQUIP_SLOTS
+        .iter()
+        .rev()
+        .find(|&&slot| entity.equipment.get(slot).is_some())
+    {
+        Some(&slot) => slot,
+        None => return false,
+    };
+    if entity.inventory.is_full() {
+        return false;
+    }
+    if let Some(item) = entity.equipment.unequip(slot) {
+        entity.inventory.insert(item);
+    }
+    true
+}
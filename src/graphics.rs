@@ -3,6 +3,7 @@
 */
 
 use crate::render::TILE_SIZE;
+use crate::render_map::MapTexture;
 use crate::sprite::Sprite;
 use glam::*;
 use raylib::prelude::*;
@@ -17,6 +18,9 @@ pub struct PlayCam {
     pub zoom: f32,
 }
 
+/// Index of the grayscale shader in `Graphics.shaders`, matching the load order below.
+pub const GRAYSCALE_SHADER: usize = 0;
+
 pub struct Graphics {
     // Window and rendering dimensions
     pub window_dims: glam::UVec2,
@@ -30,6 +34,8 @@ pub struct Graphics {
     // Asset storage
     pub sprite_textures: HashMap<Sprite, Texture2D>,
     pub shaders: Vec<Shader>,
+    /// Made on first use, since it depends on the stage size.
+    pub map: Option<MapTexture>,
 }
 
 impl Graphics {
@@ -89,6 +95,7 @@ impl Graphics {
             },
             sprite_textures,
             shaders,
+            map: None,
         })
     }
 
@@ -97,6 +104,11 @@ impl Graphics {
         self.sprite_textures.get(&sprite)
     }
 
+    /// Scales window/screen coordinates to the render texture, where the UI is drawn.
+    pub fn screen_to_texture(&self, screen_pos: Vec2) -> Vec2 {
+        screen_pos * self.dims.as_vec2() / self.window_dims.as_vec2()
+    }
+
     /// Converts window/screen coordinates to pixel-based WORLD coordinates.
     /// This is the known-good function from our working test example.
     pub fn screen_to_world(&self, screen_pos: Vec2) -> Vec2 {
@@ -0,0 +1,296 @@
+/* Ground piles.
+   Items on the ground are item entities, and a tile can hold a small pile of them, one stack
+   each. Dropping something onto a pile tops up a matching stack first, then starts a new one,
+   and once the pile is full the rest spills over onto the nearest tiles with room. If there's
+   no floor left anywhere near, whatever didn't fit stays with whoever dropped it.
+   Picking up from a tile with more than one stack opens a menu to choose what to take, or to
+   take everything there's room for.
+*/
+
+use glam::{IVec2, Vec2};
+use rand::{random_range, seq::SliceRandom};
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    entity::{EntityType, VID},
+    entity_templates::init_as_item,
+    item::Item,
+    state::State,
+};
+
+/// How many stacks fit on one tile.
+pub const MAX_PILE_STACKS: usize = 6;
+
+/// The pickup menu, open on the tile the player was standing on.
+#[derive(Debug, Clone, Copy)]
+pub struct PickupMenu {
+    pub tile: IVec2,
+    pub selected: usize,
+}
+
+impl PickupMenu {
+    pub fn new() -> PickupMenu {
+        PickupMenu {
+            tile: IVec2::ZERO,
+            selected: 0,
+        }
+    }
+}
+
+/// The item entities lying on a tile, oldest first.
+pub fn ground_items(state: &State, pos: IVec2) -> Vec<VID> {
+    if !state.stage.in_bounds(pos) {
+        return Vec::new();
+    }
+    state.spatial_grid[pos.x as usize][pos.y as usize]
+        .iter()
+        .filter(|vid| {
+            state
+                .entity_manager
+                .get_entity(**vid)
+                .is_some_and(|e| e.type_ == EntityType::Item && e.active)
+        })
+        .copied()
+        .collect()
+}
+
+/// Puts an item on the ground at a tile. Tops up matching stacks in the pile there, starts a new
+/// stack if the pile has room, and spills whatever's left onto the closest tiles around it.
+/// Returns how many made it onto the ground, which is short of the whole stack only if there was
+/// nowhere left to put the rest.
+pub fn drop_item(state: &mut State, audio: &mut Audio, item: Item, pos: IVec2) -> u32 {
+    let placed = place_on_ground(state, item, pos);
+    if placed < item.count {
+        audio.play_sound_effect(SoundEffect::CantUse);
+    }
+    placed
+}
+
+/// `drop_item` without the sound, returns how many were placed.
+fn place_on_ground(state: &mut State, item: Item, pos: IVec2) -> u32 {
+    const SPILL_RADIUS: i32 = 3;
+
+    let mut item = item;
+    let count = item.count;
+    if add_to_pile(state, &mut item, pos) {
+        return count;
+    }
+    for radius in 1..=SPILL_RADIUS {
+        let mut ring: Vec<IVec2> = (-radius..=radius)
+            .flat_map(|x| (-radius..=radius).map(move |y| IVec2::new(x, y)))
+            .filter(|offset| offset.x.abs().max(offset.y.abs()) == radius)
+            .map(|offset| pos + offset)
+            .collect();
+        ring.shuffle(&mut rand::rng());
+        for spill_pos in ring {
+            if add_to_pile(state, &mut item, spill_pos) {
+                return count;
+            }
+        }
+    }
+    count - item.count
+}
+
+/// Puts as much of an item as fits onto the pile at a tile, taking it out of `item`. Returns
+/// whether all of it is down.
+fn add_to_pile(state: &mut State, item: &mut Item, pos: IVec2) -> bool {
+    if !state.stage.in_bounds(pos) || !state.stage.is_walkable(pos.x as usize, pos.y as usize) {
+        return false;
+    }
+    let pile = ground_items(state, pos);
+
+    // --- 1. Top up matching stacks ---
+    if item.is_stackable() {
+        for &vid in &pile {
+            let ground_item = match state
+                .entity_manager
+                .get_entity_mut(vid)
+                .and_then(|e| e.item.as_mut())
+            {
+                Some(ground_item) => ground_item,
+                None => continue,
+            };
+            if ground_item.type_ == item.type_
+                && ground_item.durability == item.durability
+                && ground_item.count < ground_item.max_count
+            {
+                let moved = (ground_item.max_count - ground_item.count).min(item.count);
+                ground_item.count += moved;
+                item.count -= moved;
+                if item.count == 0 {
+                    return true;
+                }
+            }
+        }
+    }
+
+    // --- 2. Start a new stack ---
+    if pile.len() >= MAX_PILE_STACKS {
+        return false;
+    }
+    let vid = match state.entity_manager.new_entity() {
+        Some(vid) => vid,
+        None => return false,
+    };
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        const PILE_SPREAD: f32 = 0.2;
+
+        init_as_item(entity, *item);
+        // a pile sprawls a bit so the stacks don't all hide under the top one
+        let spread = if pile.is_empty() {
+            Vec2::ZERO
+        } else {
+            Vec2::new(
+                random_range(-PILE_SPREAD..PILE_SPREAD),
+                random_range(-PILE_SPREAD..PILE_SPREAD),
+            )
+        };
+        entity.pos = pos.as_vec2() + Vec2::splat(0.5) + spread;
+    }
+    state.add_entity_to_grid(vid, pos);
+    item.count = 0;
+    true
+}
+
+/// Moves an item from the ground into the player's inventory. Anything that doesn't fit, or that
+/// gets swapped out of the selected slot to make room, goes back on the ground.
+pub fn pick_up_item(state: &mut State, audio: &mut Audio, item_vid: VID) -> bool {
+    let player_vid = match state.player_vid {
+        Some(vid) => vid,
+        None => return false,
+    };
+    let (item, pos) = match state.entity_manager.get_entity(item_vid) {
+        Some(entity) if entity.type_ == EntityType::Item => match entity.item {
+            Some(item) => (item, entity.pos.as_ivec2()),
+            None => return false,
+        },
+        _ => return false,
+    };
+
+    // off the ground first, so whatever comes back has the room it left
+    state.remove_entity_from_grid(item_vid, pos);
+    state.entity_manager.set_inactive_vid(item_vid);
+
+    let leftover = match state.entity_manager.get_entity_mut(player_vid) {
+        Some(player) => player.inventory.insert(item),
+        None => Some(item),
+    };
+    if let Some(leftover) = leftover {
+        drop_item(state, audio, leftover, pos);
+    }
+    true
+}
+
+/// Takes as much of the pile on a tile as the player has room for, without swapping anything
+/// out of their inventory for it. Returns whether anything was taken.
+pub fn pick_up_all(state: &mut State, audio: &mut Audio, pos: IVec2) -> bool {
+    let player_vid = match state.player_vid {
+        Some(vid) => vid,
+        None => return false,
+    };
+    let mut took_any = false;
+    for item_vid in ground_items(state, pos) {
+        let item = match state
+            .entity_manager
+            .get_entity(item_vid)
+            .and_then(|e| e.item)
+        {
+            Some(item) => item,
+            None => continue,
+        };
+        let room = match state.entity_manager.get_entity(player_vid) {
+            Some(player) => player.inventory.room_for(&item),
+            None => return took_any,
+        };
+        if room >= item.count {
+            took_any |= pick_up_item(state, audio, item_vid);
+        } else if room > 0 {
+            // only part of the stack fits, the rest stays where it is
+            if let Some(ground_item) = state
+                .entity_manager
+                .get_entity_mut(item_vid)
+                .and_then(|e| e.item.as_mut())
+            {
+                ground_item.count -= room;
+            }
+            if let Some(player) = state.entity_manager.get_entity_mut(player_vid) {
+                player.inventory.insert(Item {
+                    count: room,
+                    ..item
+                });
+            }
+            took_any = true;
+        }
+    }
+    took_any
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        item::ItemType,
+        stage::{Stage, StageType},
+    };
+
+    /// A 3x3 stage, so the ring around the middle tile is all there is to spill onto.
+    fn small_state() -> State {
+        let mut state = State::new();
+        state.stage = Stage::new(StageType::TestArena, 3, 3);
+        state.spatial_grid = vec![vec![Vec::new(); 3]; 3];
+        state
+    }
+
+    /// Tops the pile on a tile up to `MAX_PILE_STACKS` with stacks of an unstackable item.
+    fn fill_pile(state: &mut State, pos: IVec2) {
+        while ground_items(state, pos).len() < MAX_PILE_STACKS {
+            place_on_ground(state, Item::new(ItemType::Bat), pos);
+        }
+    }
+
+    fn stone_on_ground(state: &State) -> u32 {
+        state
+            .entity_manager
+            .iter()
+            .filter(|e| e.active && e.type_ == EntityType::Item)
+            .filter_map(|e| e.item)
+            .filter(|item| item.type_ == ItemType::Stone)
+            .map(|item| item.count)
+            .sum()
+    }
+
+    #[test]
+    fn drop_spills_onto_the_ring_around_a_full_pile() {
+        let mut state = small_state();
+        let middle = IVec2::new(1, 1);
+        fill_pile(&mut state, middle);
+        assert_eq!(
+            place_on_ground(&mut state, Item::new_stack(ItemType::Stone, 5), middle),
+            5
+        );
+        assert_eq!(stone_on_ground(&state), 5);
+        assert_eq!(ground_items(&state, middle).len(), MAX_PILE_STACKS);
+    }
+
+    #[test]
+    fn drop_onto_full_pile_and_full_ring_places_only_what_fits() {
+        let mut state = small_state();
+        let middle = IVec2::new(1, 1);
+        for x in 0..3 {
+            for y in 0..3 {
+                let pos = IVec2::new(x, y);
+                if pos != middle {
+                    fill_pile(&mut state, pos);
+                }
+            }
+        }
+        // one stack in the middle can still take a single stone
+        place_on_ground(&mut state, Item::new_stack(ItemType::Stone, 98), middle);
+        fill_pile(&mut state, middle);
+        assert_eq!(stone_on_ground(&state), 98);
+
+        let placed = place_on_ground(&mut state, Item::new_stack(ItemType::Stone, 5), middle);
+        assert_eq!(placed, 1);
+        assert_eq!(stone_on_ground(&state), 99);
+    }
+}
@@ -2,10 +2,23 @@ use glam::*;
 use raylib::prelude::*;
 
 use crate::{
-    audio::Audio,
+    audio::{Audio, SoundEffect},
+    backpack::{
+        close_backpack, move_cursor, open_backpack, pick_or_place, sort_backpack, toggle_favorite,
+    },
+    containers::{close_container, quick_stack_nearby, transfer, ContainerPane, Transfer},
+    crafting::{craft, crafting_entries},
+    equipment::take_off_last,
     graphics::Graphics,
-    settings::INVENTORY_SELECTION_DEBOUNCE_INTERVAL,
-    stage::init_playing_state,
+    ground::{ground_items, pick_up_all, pick_up_item},
+    inventory_mouse::process_inventory_mouse,
+    save::{can_save, load_game, save_game},
+    settings::{
+        INTERACT_DEBOUNCE_INTERVAL, INVENTORY_SELECTION_DEBOUNCE_INTERVAL,
+        MENU_SELECTION_DEBOUNCE_INTERVAL,
+    },
+    shop::{buy, can_open_shop, shop_entries},
+    stage::{init_playing_state, init_stage_preview},
     state::{Mode, State},
 };
 
@@ -31,11 +44,18 @@ pub fn process_input(
 
     match state.mode {
         Mode::Title => process_input_title(rl, rlt, state, audio, graphics, dt),
+        Mode::StagePreview => process_input_stage_preview(rl, rlt, state, audio, graphics, dt),
         Mode::Settings => {} // process_input_settings_menu(rl, rlt, state, audio, graphics, dt),
         Mode::VideoSettings => {} //{process_input_video_settings_menu(rl, rlt, state, audio, graphics, dt)}
         Mode::Playing => process_input_playing(rl, rlt, state, audio, graphics, dt),
+        Mode::Shop => process_input_shop(rl, rlt, state, audio, graphics, dt),
+        Mode::Crafting => process_input_crafting(rl, rlt, state, audio, graphics, dt),
+        Mode::Pickup => process_input_pickup(rl, rlt, state, audio, graphics, dt),
+        Mode::Container => process_input_container(rl, rlt, state, audio, graphics, dt),
+        Mode::Backpack => process_input_backpack(rl, rlt, state, audio, graphics, dt),
+        Mode::Map => process_input_map(rl, rlt, state, audio, graphics, dt),
         Mode::GameOver => process_input_game_over(rl, rlt, state, audio, graphics, dt),
-        Mode::Win => {} //process_input_win(rl, rlt, state, audio, graphics, dt),
+        Mode::Win => process_input_win(rl, rlt, state, audio, graphics, dt),
     }
 }
 
@@ -45,6 +65,13 @@ pub fn process_input(
 pub struct MouseInputs {
     pub left: bool,
     pub right: bool,
+    /// Only true on the frame the button went down or came up.
+    pub left_pressed: bool,
+    pub left_released: bool,
+    pub right_pressed: bool,
+    pub right_released: bool,
+    /// Either shift key held, for shift-clicks.
+    pub shift: bool,
     pub pos: IVec2,
     pub scroll: f32,
 }
@@ -54,6 +81,11 @@ impl MouseInputs {
         MouseInputs {
             left: false,
             right: false,
+            left_pressed: false,
+            left_released: false,
+            right_pressed: false,
+            right_released: false,
+            shift: false,
             pos: IVec2::ZERO,
             scroll: 0.0,
         }
@@ -126,6 +158,7 @@ pub struct PlayingInputs {
 
     pub pick_up: bool,
     pub drop: bool,
+    pub interact: bool,
 }
 impl PlayingInputs {
     pub fn new() -> PlayingInputs {
@@ -172,6 +205,7 @@ impl PlayingInputs {
 
             pick_up: false,
             drop: false,
+            interact: false,
         }
     }
 }
@@ -186,6 +220,13 @@ pub fn set_mouse_inputs(rl: &mut RaylibHandle, state: &mut State, _dt: f32) {
     state.mouse_inputs = MouseInputs {
         left: rl.is_mouse_button_down(raylib::consts::MouseButton::MOUSE_BUTTON_LEFT),
         right: rl.is_mouse_button_down(raylib::consts::MouseButton::MOUSE_BUTTON_RIGHT),
+        left_pressed: rl.is_mouse_button_pressed(raylib::consts::MouseButton::MOUSE_BUTTON_LEFT),
+        left_released: rl.is_mouse_button_released(raylib::consts::MouseButton::MOUSE_BUTTON_LEFT),
+        right_pressed: rl.is_mouse_button_pressed(raylib::consts::MouseButton::MOUSE_BUTTON_RIGHT),
+        right_released: rl
+            .is_mouse_button_released(raylib::consts::MouseButton::MOUSE_BUTTON_RIGHT),
+        shift: rl.is_key_down(raylib::consts::KeyboardKey::KEY_LEFT_SHIFT)
+            || rl.is_key_down(raylib::consts::KeyboardKey::KEY_RIGHT_SHIFT),
         pos: mouse_pos,
         scroll,
     };
@@ -341,6 +382,13 @@ pub fn set_playing_inputs(rl: &mut RaylibHandle, state: &mut State, dt: f32) {
         );
     }
 
+    // interact with whatever is underfoot or adjacent
+    new_inputs.interact = rl.is_key_down(raylib::consts::KeyboardKey::KEY_F)
+        || rl.is_gamepad_button_down(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP,
+        );
+
     // select inventory index via numpad
     {
         new_inputs.select_inventory_index_0 = rl.is_key_down(raylib::consts::KeyboardKey::KEY_KP_1);
@@ -370,7 +418,7 @@ pub fn process_input_title(
     rl: &mut RaylibHandle,
     _rlt: &mut RaylibThread,
     state: &mut State,
-    _audio: &mut Audio,
+    audio: &mut Audio,
     graphics: &mut Graphics,
     _dt: f32,
 ) {
@@ -383,16 +431,39 @@ pub fn process_input_title(
     {
         state.mode = Mode::Playing;
         init_playing_state(state, graphics);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_P) {
+        init_stage_preview(state);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_F9) {
+        load_saved_run(state, audio, graphics);
     }
 }
 
-pub fn process_input_playing(
+/// R rerolls the previewed stage, confirm starts playing on it, back returns to the title.
+pub fn process_input_stage_preview(
     rl: &mut RaylibHandle,
     _rlt: &mut RaylibThread,
     state: &mut State,
     _audio: &mut Audio,
     graphics: &mut Graphics,
     _dt: f32,
+) {
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_R) {
+        init_stage_preview(state);
+    } else if state.menu_inputs.confirm {
+        state.next_stage_seed = Some(state.stage.seed);
+        init_playing_state(state, graphics);
+    } else if state.menu_inputs.back {
+        state.mode = Mode::Title;
+    }
+}
+
+pub fn process_input_playing(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    audio: &mut Audio,
+    graphics: &mut Graphics,
+    _dt: f32,
 ) {
     // --- Camera Zoom Control (New!) ---
     let wheel_move = rl.get_mouse_wheel_move();
@@ -420,15 +491,92 @@ pub fn process_input_playing(
         graphics.play_cam.zoom = (graphics.play_cam.zoom + 0.25).min(8.0);
     }
 
-    // inventory management
+    // quicksave and quickload
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_F5) {
+        if !can_save(state) {
+            audio.play_sound_effect(SoundEffect::CantUse);
+        } else if let Err(e) = save_game(state) {
+            println!("Error saving game: {}", e);
+            audio.play_sound_effect(SoundEffect::CantUse);
+        } else {
+            audio.play_sound_effect(SoundEffect::Confirm);
+        }
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_F9) {
+        load_saved_run(state, audio, graphics);
+    }
+
+    // the shop only opens between waves
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_B)
+        || rl
+            .is_gamepad_button_pressed(0, raylib::consts::GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT)
+    {
+        if can_open_shop(state) {
+            state.mode = Mode::Shop;
+        } else {
+            audio.play_sound_effect(SoundEffect::CantUse);
+        }
+    }
+
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_C)
+        || rl
+            .is_gamepad_button_pressed(0, raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB)
+    {
+        state.mode = Mode::Crafting;
+    }
+
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_I)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+        )
+    {
+        open_backpack(state);
+    }
+
+    // drag stacks around the hotbar with the mouse, or out of it to drop them
+    process_inventory_mouse(state, audio, graphics);
+
+    // take off worn gear, one piece at a time
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_R)
+        || rl.is_gamepad_button_pressed(0, raylib::consts::GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB)
+    {
+        if let Some(player) = state
+            .player_vid
+            .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+        {
+            if take_off_last(player) {
+                audio.play_sound_effect(SoundEffect::ClothRip);
+            } else {
+                audio.play_sound_effect(SoundEffect::CantUse);
+            }
+        }
+    }
+
+    // the full map opens centered on the player
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_M)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT,
+        )
+    {
+        if let Some(player) = state
+            .player_vid
+            .and_then(|vid| state.entity_manager.get_entity(vid))
+        {
+            state.map_view.pan = player.pos;
+            state.mode = Mode::Map;
+        }
+    }
+
+    // inventory management, scrolling stays on the hotbar
     if let Some(player_vid) = state.player_vid {
         let player = state.entity_manager.get_entity_mut(player_vid).unwrap();
         if state.playing_inputs.inventory_next {
-            player.inventory.increment_selected_index();
+            player.inventory.increment_hotbar_index();
             state.playing_input_debounce_timers.inventory_next =
                 INVENTORY_SELECTION_DEBOUNCE_INTERVAL;
         } else if state.playing_inputs.inventory_prev {
-            player.inventory.decrement_selected_index();
+            player.inventory.decrement_hotbar_index();
             state.playing_input_debounce_timers.inventory_prev =
                 INVENTORY_SELECTION_DEBOUNCE_INTERVAL;
         }
@@ -462,6 +610,310 @@ pub fn process_input_playing(
     }
 }
 
+fn load_saved_run(state: &mut State, audio: &mut Audio, graphics: &mut Graphics) {
+    match load_game(state, graphics) {
+        Ok(()) => audio.play_sound_effect(SoundEffect::Confirm),
+        Err(e) => {
+            println!("Error loading game: {}", e);
+            audio.play_sound_effect(SoundEffect::CantUse);
+        }
+    }
+}
+
+/// Up and down pick an entry, confirm buys it, back or B goes back to the game.
+pub fn process_input_shop(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    audio: &mut Audio,
+    _graphics: &mut Graphics,
+    _dt: f32,
+) {
+    let entry_count = shop_entries(state).len();
+    if state.menu_inputs.up {
+        state.shop.selected = (state.shop.selected + entry_count - 1) % entry_count;
+        state.menu_input_debounce_timers.up = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    } else if state.menu_inputs.down {
+        state.shop.selected = (state.shop.selected + 1) % entry_count;
+        state.menu_input_debounce_timers.down = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    }
+
+    if state.menu_inputs.confirm {
+        buy(state, audio, state.shop.selected);
+    } else if state.menu_inputs.back
+        || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_B)
+        || rl
+            .is_gamepad_button_pressed(0, raylib::consts::GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT)
+    {
+        state.mode = Mode::Playing;
+    }
+}
+
+pub fn process_input_crafting(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    audio: &mut Audio,
+    _graphics: &mut Graphics,
+    _dt: f32,
+) {
+    let entry_count = crafting_entries(state).len();
+    if entry_count > 0 {
+        if state.menu_inputs.up {
+            state.crafting.selected = (state.crafting.selected + entry_count - 1) % entry_count;
+            state.menu_input_debounce_timers.up = MENU_SELECTION_DEBOUNCE_INTERVAL;
+        } else if state.menu_inputs.down {
+            state.crafting.selected = (state.crafting.selected + 1) % entry_count;
+            state.menu_input_debounce_timers.down = MENU_SELECTION_DEBOUNCE_INTERVAL;
+        }
+    }
+
+    if state.menu_inputs.confirm {
+        craft(state, audio, state.crafting.selected);
+        // a finished repair drops off the list
+        let entry_count = crafting_entries(state).len();
+        state.crafting.selected = state.crafting.selected.min(entry_count.saturating_sub(1));
+    } else if state.menu_inputs.back
+        || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_C)
+        || rl
+            .is_gamepad_button_pressed(0, raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB)
+    {
+        state.mode = Mode::Playing;
+    }
+}
+
+/// One row per stack in the pile and a last one to take the lot. Closes once the pile is gone.
+pub fn process_input_pickup(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    audio: &mut Audio,
+    _graphics: &mut Graphics,
+    _dt: f32,
+) {
+    let pile = ground_items(state, state.pickup_menu.tile);
+    let row_count = pile.len() + 1;
+    if state.menu_inputs.up {
+        state.pickup_menu.selected = (state.pickup_menu.selected + row_count - 1) % row_count;
+        state.menu_input_debounce_timers.up = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    } else if state.menu_inputs.down {
+        state.pickup_menu.selected = (state.pickup_menu.selected + 1) % row_count;
+        state.menu_input_debounce_timers.down = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    }
+
+    if state.menu_inputs.confirm {
+        match pile.get(state.pickup_menu.selected) {
+            Some(&vid) => {
+                pick_up_item(state, audio, vid);
+            }
+            None => {
+                if !pick_up_all(state, audio, state.pickup_menu.tile) {
+                    audio.play_sound_effect(SoundEffect::CantUse);
+                }
+            }
+        }
+        let row_count = ground_items(state, state.pickup_menu.tile).len() + 1;
+        state.pickup_menu.selected = state.pickup_menu.selected.min(row_count - 1);
+    } else if state.menu_inputs.back || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_E) {
+        // so the same press doesn't open it straight back up
+        state.playing_input_debounce_timers.pick_up = INTERACT_DEBOUNCE_INTERVAL;
+        state.mode = Mode::Playing;
+    }
+
+    if ground_items(state, state.pickup_menu.tile).is_empty() {
+        state.mode = Mode::Playing;
+    }
+}
+
+/// Left and right pick a side, up and down a slot on it. Confirm moves the stack across, X moves
+/// one, TAB moves everything, Q quick-stacks what the other side already has. Back or F closes it.
+pub fn process_input_container(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    audio: &mut Audio,
+    _graphics: &mut Graphics,
+    _dt: f32,
+) {
+    let (player_vid, container_vid) = match (state.player_vid, state.container_view.vid) {
+        (Some(player_vid), Some(container_vid)) => (player_vid, container_vid),
+        _ => {
+            close_container(state);
+            return;
+        }
+    };
+    if state.entity_manager.get_entity(container_vid).is_none() {
+        close_container(state);
+        return;
+    }
+
+    if state.menu_inputs.left {
+        state.container_view.pane = ContainerPane::Player;
+        state.menu_input_debounce_timers.left = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    } else if state.menu_inputs.right {
+        state.container_view.pane = ContainerPane::Container;
+        state.menu_input_debounce_timers.right = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    }
+
+    let pane_vid = match state.container_view.pane {
+        ContainerPane::Player => player_vid,
+        ContainerPane::Container => container_vid,
+    };
+    if let Some(entity) = state.entity_manager.get_entity_mut(pane_vid) {
+        if state.menu_inputs.up {
+            entity.inventory.decrement_selected_index();
+            state.menu_input_debounce_timers.up = MENU_SELECTION_DEBOUNCE_INTERVAL;
+        } else if state.menu_inputs.down {
+            entity.inventory.increment_selected_index();
+            state.menu_input_debounce_timers.down = MENU_SELECTION_DEBOUNCE_INTERVAL;
+        }
+    }
+
+    if state.menu_inputs.confirm {
+        transfer(state, audio, Transfer::Stack);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_X)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+        )
+    {
+        transfer(state, audio, Transfer::One);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_TAB)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP,
+        )
+    {
+        transfer(state, audio, Transfer::All);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_Q)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+        )
+    {
+        transfer(state, audio, Transfer::QuickStack);
+    } else if state.menu_inputs.back || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_F) {
+        close_container(state);
+    }
+}
+
+/// Directions move around the grid, confirm picks a stack up and puts it down again. F marks a
+/// favorite, TAB picks what to sort by and R sorts, Q quick-stacks into chests nearby. Back or I
+/// closes it.
+pub fn process_input_backpack(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    audio: &mut Audio,
+    _graphics: &mut Graphics,
+    _dt: f32,
+) {
+    if state.menu_inputs.left {
+        move_cursor(state, -1, 0);
+        state.menu_input_debounce_timers.left = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    } else if state.menu_inputs.right {
+        move_cursor(state, 1, 0);
+        state.menu_input_debounce_timers.right = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    } else if state.menu_inputs.up {
+        move_cursor(state, 0, -1);
+        state.menu_input_debounce_timers.up = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    } else if state.menu_inputs.down {
+        move_cursor(state, 0, 1);
+        state.menu_input_debounce_timers.down = MENU_SELECTION_DEBOUNCE_INTERVAL;
+    }
+
+    if state.menu_inputs.confirm {
+        pick_or_place(state, audio);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_F)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+        )
+    {
+        toggle_favorite(state);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_TAB) {
+        state.backpack_view.sort_key = state.backpack_view.sort_key.next();
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_R)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP,
+        )
+    {
+        sort_backpack(state, audio);
+    } else if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_Q)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+        )
+    {
+        quick_stack_nearby(state, audio);
+    } else if state.menu_inputs.back
+        || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_I)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+        )
+    {
+        close_backpack(state);
+    }
+}
+
+/// Directions pan, the wheel and - / = zoom, M or back goes back to the game.
+pub fn process_input_map(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    _audio: &mut Audio,
+    _graphics: &mut Graphics,
+    dt: f32,
+) {
+    const PAN_SPEED: f32 = 32.0; // tiles per second at zoom 1
+    const ZOOM_INCREMENT: f32 = 0.25;
+    const MIN_ZOOM: f32 = 0.5;
+    const MAX_ZOOM: f32 = 6.0;
+
+    let mut pan = Vec2::ZERO;
+    if state.menu_inputs.left {
+        pan.x -= 1.0;
+    }
+    if state.menu_inputs.right {
+        pan.x += 1.0;
+    }
+    if state.menu_inputs.up {
+        pan.y -= 1.0;
+    }
+    if state.menu_inputs.down {
+        pan.y += 1.0;
+    }
+    let stage_dims = state.stage.get_dims().as_vec2();
+    state.map_view.pan = (state.map_view.pan + pan * PAN_SPEED * dt / state.map_view.zoom)
+        .clamp(Vec2::ZERO, stage_dims);
+
+    let mut zoom = 0.0;
+    if state.mouse_inputs.scroll > 0.0 {
+        zoom += ZOOM_INCREMENT;
+    } else if state.mouse_inputs.scroll < 0.0 {
+        zoom -= ZOOM_INCREMENT;
+    }
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_MINUS) {
+        zoom -= ZOOM_INCREMENT;
+    }
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_EQUAL) {
+        zoom += ZOOM_INCREMENT;
+    }
+    state.map_view.zoom = (state.map_view.zoom + zoom).clamp(MIN_ZOOM, MAX_ZOOM);
+
+    if state.menu_inputs.back
+        || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_M)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT,
+        )
+    {
+        state.mode = Mode::Playing;
+    }
+}
+
 // process input game over, on enter or space, go to title
 pub fn process_input_game_over(
     rl: &mut RaylibHandle,
@@ -482,6 +934,26 @@ pub fn process_input_game_over(
     }
 }
 
+// process input win, on enter or space, go to title
+pub fn process_input_win(
+    rl: &mut RaylibHandle,
+    _rlt: &mut RaylibThread,
+    state: &mut State,
+    _audio: &mut Audio,
+    _graphics: &mut Graphics,
+    _dt: f32,
+) {
+    if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_ENTER)
+        || rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_SPACE)
+        || rl.is_gamepad_button_pressed(
+            0,
+            raylib::consts::GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+        )
+    {
+        state.mode = Mode::Title;
+    }
+}
+
 ////////////////////////    INPUT DEBOUNCE TIMERS    ////////////////////////
 
 /**
@@ -526,6 +998,8 @@ impl MenuInputDebounceTimers {
 pub struct PlayingInputDebounceTimers {
     pub inventory_prev: f32,
     pub inventory_next: f32,
+    pub pick_up: f32,
+    pub interact: f32,
 }
 
 impl PlayingInputDebounceTimers {
@@ -533,12 +1007,16 @@ impl PlayingInputDebounceTimers {
         PlayingInputDebounceTimers {
             inventory_prev: 0.0,
             inventory_next: 0.0,
+            pick_up: 0.0,
+            interact: 0.0,
         }
     }
 
     pub fn step(&mut self, dt: f32) {
         self.inventory_prev = (self.inventory_prev - dt).max(0.0);
         self.inventory_next = (self.inventory_next - dt).max(0.0);
+        self.pick_up = (self.pick_up - dt).max(0.0);
+        self.interact = (self.interact - dt).max(0.0);
     }
 
     pub fn debounce(&self, playing_inputs: &PlayingInputs) -> PlayingInputs {
@@ -576,8 +1054,9 @@ impl PlayingInputDebounceTimers {
             use_up: playing_inputs.use_up,
             use_down: playing_inputs.use_down,
             use_center: playing_inputs.use_center, // use item in place
-            pick_up: playing_inputs.pick_up,
+            pick_up: self.pick_up == 0.0 && playing_inputs.pick_up,
             drop: playing_inputs.drop,
+            interact: self.interact == 0.0 && playing_inputs.interact,
         }
     }
 }
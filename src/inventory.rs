@@ -1,7 +1,39 @@
-use crate::item::Item;
+use std::collections::HashSet;
 
-/// The fixed number of slots in any inventory.
-pub const MAX_SLOTS: usize = 10;
+use crate::item::{Item, ItemType};
+
+/// The number of slots an inventory starts with.
+pub const BASE_SLOTS: usize = 10;
+/// The first slots are the hotbar, anything past it is the backpack.
+pub const HOTBAR_SLOTS: usize = 10;
+/// As big as an inventory gets, however many upgrades and backpacks go into it.
+pub const MAX_SLOTS: usize = 40;
+
+/// What `Inventory::sort` orders by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Type,
+    Count,
+    Name,
+}
+
+impl SortKey {
+    pub fn next(self) -> SortKey {
+        match self {
+            SortKey::Type => SortKey::Count,
+            SortKey::Count => SortKey::Name,
+            SortKey::Name => SortKey::Type,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Type => "type",
+            SortKey::Count => "count",
+            SortKey::Name => "name",
+        }
+    }
+}
 
 /// Represents a single slot in the inventory, linking a slot index
 /// to a unique Item stack with its own state (count, cooldown, etc.).
@@ -12,11 +44,27 @@ pub struct InvEntry {
 }
 
 /// Manages a collection of items, handling the logic for adding, stacking,
-/// swapping, and removing them within a fixed number of slots.
-#[derive(Debug, Clone, Default)]
+/// swapping, and removing them within a limited number of slots.
+#[derive(Debug, Clone)]
 pub struct Inventory {
     pub entries: Vec<InvEntry>,
     pub selected_index: usize,
+    /// How many slots there are. Starts at `BASE_SLOTS`, upgrades and backpacks add more.
+    pub capacity: usize,
+    /// Slots the player has locked. Insert never puts anything in them or swaps them out,
+    /// and sorting leaves them where they are.
+    pub favorites: HashSet<usize>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            selected_index: 0,
+            capacity: BASE_SLOTS,
+            favorites: HashSet::new(),
+        }
+    }
 }
 
 impl Inventory {
@@ -30,9 +78,12 @@ impl Inventory {
     /// # Logic Priority:
     /// 1. **Stack First:** Scans the entire inventory to merge with any compatible, non-full stacks.
     /// 2. **Fill Empty Slot (Overflow):** If the item remains, finds the first available empty slot *prioritizing the selected slot if empty* and places it there.
+    ///    After the selected slot the hotbar fills up before the backpack.
     /// 3. **Swap with Selected (Last Resort):** Only if the inventory is completely full, it will swap the incoming
     ///    item with the one in the `selected_index` slot.
     ///
+    /// Favorite slots are skipped at every step, they're never stacked onto, filled or swapped.
+    ///
     /// # Returns
     /// `None` if the item was fully added.
     /// `Some(Item)` containing the swapped-out item or the un-addable remainder.
@@ -40,7 +91,13 @@ impl Inventory {
         // --- 1. Prioritize Stacking Globally ---
         if item_to_add.is_stackable() {
             for entry in self.entries.iter_mut() {
-                if entry.item.type_ == item_to_add.type_ && entry.item.count < entry.item.max_count
+                if self.favorites.contains(&entry.index) {
+                    continue;
+                }
+                // a worn stack only takes more of the same wear
+                if entry.item.type_ == item_to_add.type_
+                    && entry.item.durability == item_to_add.durability
+                    && entry.item.count < entry.item.max_count
                 {
                     let space_available = entry.item.max_count - entry.item.count;
                     let amount_to_transfer = space_available.min(item_to_add.count);
@@ -58,9 +115,7 @@ impl Inventory {
         // --- 2. If item remains, find an empty slot, prioritizing the selected one ---
         if !self.is_full() {
             // 2a. Prioritize the selected slot if it's empty.
-            let is_selected_slot_empty =
-                !self.entries.iter().any(|e| e.index == self.selected_index);
-            if is_selected_slot_empty {
+            if self.is_open_slot(self.selected_index) {
                 self.entries.push(InvEntry {
                     index: self.selected_index, // Use the selected index
                     item: item_to_add,
@@ -70,9 +125,7 @@ impl Inventory {
             }
 
             // 2b. If the selected slot was taken, find any other empty slot.
-            if let Some(slot_index) =
-                (0..MAX_SLOTS).find(|i| !self.entries.iter().any(|e| e.index == *i))
-            {
+            if let Some(slot_index) = (0..self.capacity).find(|i| self.is_open_slot(*i)) {
                 self.entries.push(InvEntry {
                     index: slot_index,
                     item: item_to_add,
@@ -87,6 +140,7 @@ impl Inventory {
             .entries
             .iter()
             .position(|e| e.index == self.selected_index)
+            .filter(|_| !self.favorites.contains(&self.selected_index))
         {
             // An item exists in the selected slot, so we can swap with it.
             let old_item = self.entries[pos_in_vec].item;
@@ -95,13 +149,114 @@ impl Inventory {
         }
 
         // Failsafe: If inventory is full but the selected slot is somehow empty (which can happen
-        // if an item was just used up), this would fail. We can't add the item, so we return it.
+        // if an item was just used up) or a favorite, this would fail. We can't add the item, so
+        // we return it.
         Some(item_to_add)
     }
 
-    /// Check if full.
+    /// How many of an item fit without swapping anything out, in stacks it tops up and empty slots.
+    pub fn room_for(&self, item: &Item) -> u32 {
+        let free_slots = (0..self.capacity).filter(|i| self.is_open_slot(*i)).count() as u32;
+        let mut room = free_slots * item.max_count;
+        if item.is_stackable() {
+            room += self
+                .entries
+                .iter()
+                .filter(|e| !self.favorites.contains(&e.index))
+                .filter(|e| e.item.type_ == item.type_ && e.item.durability == item.durability)
+                .map(|e| e.item.max_count.saturating_sub(e.item.count))
+                .sum::<u32>();
+        }
+        room
+    }
+
+    /// Whether insert could put something in a slot: it's empty and not a favorite.
+    fn is_open_slot(&self, index: usize) -> bool {
+        index < self.capacity && !self.favorites.contains(&index) && self.get(index).is_none()
+    }
+
+    /// Check if full, meaning there's no empty slot left that insert could fill.
     pub fn is_full(&self) -> bool {
-        self.entries.len() >= MAX_SLOTS
+        !(0..self.capacity).any(|i| self.is_open_slot(i))
+    }
+
+    pub fn is_favorite(&self, index: usize) -> bool {
+        self.favorites.contains(&index)
+    }
+
+    /// Locks or unlocks a slot, see `favorites`.
+    pub fn toggle_favorite(&mut self, index: usize) {
+        if index >= self.capacity {
+            return;
+        }
+        if !self.favorites.remove(&index) {
+            self.favorites.insert(index);
+        }
+    }
+
+    /// Adds slots to the backpack, up to `MAX_SLOTS`. Returns false if it's already as big as it
+    /// gets.
+    pub fn add_slots(&mut self, count: usize) -> bool {
+        if self.capacity >= MAX_SLOTS {
+            return false;
+        }
+        self.capacity = (self.capacity + count).min(MAX_SLOTS);
+        true
+    }
+
+    /// How many slots of the hotbar there are.
+    pub fn hotbar_len(&self) -> usize {
+        self.capacity.min(HOTBAR_SLOTS)
+    }
+
+    /// Merges partial stacks and lays everything out again in order, filling the slots from the
+    /// first. Favorite slots keep what's in them.
+    pub fn sort(&mut self, key: SortKey) {
+        let (kept, loose): (Vec<InvEntry>, Vec<InvEntry>) = self
+            .entries
+            .iter()
+            .partition(|e| self.favorites.contains(&e.index));
+
+        // top up earlier stacks with later ones so nothing is left half full twice over
+        let mut items: Vec<Item> = Vec::new();
+        for entry in loose {
+            let mut item = entry.item;
+            if item.is_stackable() {
+                for merged in items.iter_mut() {
+                    if merged.type_ == item.type_
+                        && merged.durability == item.durability
+                        && merged.count < merged.max_count
+                    {
+                        let moved = (merged.max_count - merged.count).min(item.count);
+                        merged.count += moved;
+                        item.count -= moved;
+                        if item.count == 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+            if item.count > 0 {
+                items.push(item);
+            }
+        }
+
+        match key {
+            SortKey::Type => items.sort_by_key(|item| (item.type_ as usize, u32::MAX - item.count)),
+            SortKey::Count => {
+                items.sort_by_key(|item| (u32::MAX - item.count, item.type_ as usize))
+            }
+            SortKey::Name => items.sort_by_key(|item| (item.name, u32::MAX - item.count)),
+        }
+
+        let free_slots: Vec<usize> = (0..self.capacity)
+            .filter(|i| !self.favorites.contains(i))
+            .collect();
+        self.entries = kept;
+        for (index, item) in free_slots.into_iter().zip(items) {
+            self.entries.push(InvEntry { index, item });
+        }
+        self.entries.sort_by_key(|e| e.index);
     }
 
     /// Check if empty.
@@ -120,6 +275,33 @@ impl Inventory {
         }
     }
 
+    /// How many of a type of item there are across all stacks.
+    pub fn count_of(&self, item_type: ItemType) -> u32 {
+        self.entries
+            .iter()
+            .filter(|e| e.item.type_ == item_type)
+            .map(|e| e.item.count)
+            .sum()
+    }
+
+    /// Removes up to `count` of a type of item, taking from the last stacks first.
+    /// Returns how many were actually removed.
+    pub fn remove_count(&mut self, item_type: ItemType, count: u32) -> u32 {
+        let mut removed = 0;
+        for entry in self.entries.iter_mut().rev() {
+            if removed == count {
+                break;
+            }
+            if entry.item.type_ == item_type {
+                let taken = entry.item.count.min(count - removed);
+                entry.item.count -= taken;
+                removed += taken;
+            }
+        }
+        self.entries.retain(|e| e.item.count > 0);
+        removed
+    }
+
     /// Gets an immutable reference to an inventory entry at a specific index.
     pub fn get(&self, index: usize) -> Option<&InvEntry> {
         self.entries.iter().find(|e| e.index == index)
@@ -135,6 +317,76 @@ impl Inventory {
         self.entries.iter_mut().find(|e| e.index == index)
     }
 
+    /// Puts an item in a slot, replacing whatever was there.
+    pub fn set_slot(&mut self, index: usize, item: Item) {
+        self.entries.retain(|e| e.index != index);
+        self.entries.push(InvEntry { index, item });
+        self.entries.sort_by_key(|e| e.index);
+    }
+
+    /// Takes the whole stack out of a slot.
+    pub fn remove_entry(&mut self, index: usize) -> Option<Item> {
+        let pos = self.entries.iter().position(|e| e.index == index)?;
+        Some(self.entries.remove(pos).item)
+    }
+
+    /// Moves the stack in one slot onto another. An empty slot takes all of it, a matching stack
+    /// takes as much as it has room for, and anything else swaps places with it.
+    /// Returns whether anything changed.
+    pub fn move_slot(&mut self, from: usize, to: usize) -> bool {
+        self.move_count(from, to, u32::MAX, true)
+    }
+
+    /// Moves half a stack, rounded up, onto an empty slot or a matching stack. Never swaps.
+    /// Returns whether anything moved.
+    pub fn split_slot(&mut self, from: usize, to: usize) -> bool {
+        let half = match self.get(from) {
+            Some(entry) => entry.item.count.div_ceil(2),
+            None => return false,
+        };
+        self.move_count(from, to, half, false)
+    }
+
+    fn move_count(&mut self, from: usize, to: usize, count: u32, allow_swap: bool) -> bool {
+        if from == to || to >= self.capacity {
+            return false;
+        }
+        let item = match self.get(from) {
+            Some(entry) => entry.item,
+            None => return false,
+        };
+        let count = count.min(item.count);
+        match self.get(to).map(|entry| entry.item) {
+            None => {
+                self.remove_count_from_slot(from, count);
+                self.set_slot(to, Item { count, ..item });
+                true
+            }
+            // a worn stack only takes more of the same wear
+            Some(target)
+                if target.is_stackable()
+                    && target.type_ == item.type_
+                    && target.durability == item.durability =>
+            {
+                let moved = target.max_count.saturating_sub(target.count).min(count);
+                if moved == 0 {
+                    return false;
+                }
+                if let Some(entry) = self.get_mut(to) {
+                    entry.item.count += moved;
+                }
+                self.remove_count_from_slot(from, moved);
+                true
+            }
+            Some(target) if allow_swap && count == item.count => {
+                self.set_slot(from, target);
+                self.set_slot(to, item);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
     /// Returns an iterator over the inventory entries.
     pub fn iter(&self) -> std::slice::Iter<'_, InvEntry> {
         self.entries.iter()
@@ -157,31 +409,271 @@ impl Inventory {
 
     /// Remove selected entry from the inventory.
     pub fn remove_selected_entry(&mut self) -> Option<Item> {
-        if let Some(pos) = self
-            .entries
-            .iter()
-            .position(|e| e.index == self.selected_index)
-        {
-            let removed_entry = self.entries.remove(pos);
-            return Some(removed_entry.item);
-        }
-        None
+        self.remove_entry(self.selected_index)
     }
 
-    /// Sets the selected index, clamping it to the valid range [0, MAX_SLOTS - 1].
+    /// Sets the selected index, ignoring indices past the last slot.
     pub fn set_selected_index(&mut self, index: usize) {
-        if index < MAX_SLOTS {
+        if index < self.capacity {
             self.selected_index = index;
         }
     }
 
-    /// Moves the selected index to the next slot, wrapping around from 9 to 0.
+    /// Moves the selected index to the next slot, wrapping around from the last to 0.
     pub fn increment_selected_index(&mut self) {
-        self.selected_index = (self.selected_index + 1) % MAX_SLOTS;
+        self.selected_index = (self.selected_index + 1) % self.capacity;
     }
 
-    /// Moves the selected index to the previous slot, wrapping around from 0 to 9.
+    /// Moves the selected index to the previous slot, wrapping around from 0 to the last.
     pub fn decrement_selected_index(&mut self) {
-        self.selected_index = (self.selected_index + MAX_SLOTS - 1) % MAX_SLOTS;
+        self.selected_index = (self.selected_index + self.capacity - 1) % self.capacity;
+    }
+
+    /// Moves the selected index to the next hotbar slot, wrapping around at the end of the hotbar.
+    pub fn increment_hotbar_index(&mut self) {
+        self.selected_index = (self.selected_index + 1) % self.hotbar_len();
+    }
+
+    /// Moves the selected index to the previous hotbar slot, wrapping around at the start.
+    pub fn decrement_hotbar_index(&mut self) {
+        let len = self.hotbar_len();
+        self.selected_index = (self.selected_index.min(len - 1) + len - 1) % len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(kind: ItemType, count: u32) -> Item {
+        Item::new_stack(kind, count)
+    }
+
+    fn inventory_with(items: &[(usize, Item)]) -> Inventory {
+        let mut inventory = Inventory::new();
+        for (index, item) in items {
+            inventory.set_slot(*index, *item);
+        }
+        inventory
+    }
+
+    fn count_at(inventory: &Inventory, index: usize) -> Option<u32> {
+        inventory.get(index).map(|entry| entry.item.count)
+    }
+
+    #[test]
+    fn move_slot_into_empty_slot_moves_whole_stack() {
+        let mut inventory = inventory_with(&[(0, stack(ItemType::Stone, 5))]);
+        assert!(inventory.move_slot(0, 3));
+        assert!(inventory.get(0).is_none());
+        assert_eq!(count_at(&inventory, 3), Some(5));
+    }
+
+    #[test]
+    fn move_slot_swaps_different_items() {
+        let mut inventory = inventory_with(&[
+            (0, stack(ItemType::Stone, 5)),
+            (1, stack(ItemType::Stick, 2)),
+        ]);
+        assert!(inventory.move_slot(0, 1));
+        assert_eq!(inventory.get(0).unwrap().item.type_, ItemType::Stick);
+        assert_eq!(inventory.get(1).unwrap().item.type_, ItemType::Stone);
+        assert_eq!(count_at(&inventory, 1), Some(5));
+    }
+
+    #[test]
+    fn move_slot_merges_matching_stacks_up_to_max() {
+        let max = Item::new(ItemType::Stone).max_count;
+        let mut inventory = inventory_with(&[
+            (0, stack(ItemType::Stone, 10)),
+            (1, stack(ItemType::Stone, max - 4)),
+        ]);
+        assert!(inventory.move_slot(0, 1));
+        assert_eq!(count_at(&inventory, 1), Some(max));
+        assert_eq!(count_at(&inventory, 0), Some(6));
+    }
+
+    #[test]
+    fn move_slot_onto_full_matching_stack_does_nothing() {
+        let max = Item::new(ItemType::Stone).max_count;
+        let mut inventory = inventory_with(&[
+            (0, stack(ItemType::Stone, 3)),
+            (1, stack(ItemType::Stone, max)),
+        ]);
+        assert!(!inventory.move_slot(0, 1));
+        assert_eq!(count_at(&inventory, 0), Some(3));
+        assert_eq!(count_at(&inventory, 1), Some(max));
+    }
+
+    #[test]
+    fn move_slot_keeps_differently_worn_stacks_apart() {
+        let mut worn = stack(ItemType::Wall, 2);
+        worn.durability -= 10;
+        let mut inventory = inventory_with(&[(0, worn), (1, stack(ItemType::Wall, 2))]);
+        assert!(inventory.move_slot(0, 1));
+        // swapped rather than merged
+        assert_eq!(inventory.get(1).unwrap().item.durability, worn.durability);
+        assert_eq!(count_at(&inventory, 0), Some(2));
+    }
+
+    #[test]
+    fn move_slot_rejects_bad_slots() {
+        let mut inventory = inventory_with(&[(0, stack(ItemType::Stone, 5))]);
+        assert!(!inventory.move_slot(0, 0));
+        assert!(!inventory.move_slot(2, 3));
+        assert!(!inventory.move_slot(0, inventory.capacity));
+        assert_eq!(count_at(&inventory, 0), Some(5));
+    }
+
+    #[test]
+    fn split_slot_moves_half_rounded_up() {
+        let mut inventory = inventory_with(&[(0, stack(ItemType::Stone, 7))]);
+        assert!(inventory.split_slot(0, 4));
+        assert_eq!(count_at(&inventory, 0), Some(3));
+        assert_eq!(count_at(&inventory, 4), Some(4));
+    }
+
+    #[test]
+    fn split_slot_of_one_moves_it() {
+        let mut inventory = inventory_with(&[(0, stack(ItemType::Stone, 1))]);
+        assert!(inventory.split_slot(0, 1));
+        assert!(inventory.get(0).is_none());
+        assert_eq!(count_at(&inventory, 1), Some(1));
+    }
+
+    #[test]
+    fn split_slot_tops_up_matching_stack() {
+        let mut inventory = inventory_with(&[
+            (0, stack(ItemType::Stick, 4)),
+            (1, stack(ItemType::Stick, 1)),
+        ]);
+        assert!(inventory.split_slot(0, 1));
+        assert_eq!(count_at(&inventory, 0), Some(2));
+        assert_eq!(count_at(&inventory, 1), Some(3));
+    }
+
+    #[test]
+    fn split_slot_never_swaps() {
+        let mut inventory = inventory_with(&[
+            (0, stack(ItemType::Stone, 4)),
+            (1, stack(ItemType::Stick, 1)),
+        ]);
+        assert!(!inventory.split_slot(0, 1));
+        assert_eq!(inventory.get(0).unwrap().item.type_, ItemType::Stone);
+        assert_eq!(count_at(&inventory, 0), Some(4));
+        assert_eq!(count_at(&inventory, 1), Some(1));
+    }
+
+    #[test]
+    fn remove_entry_takes_whole_stack() {
+        let mut inventory = inventory_with(&[(2, stack(ItemType::Stone, 5))]);
+        let item = inventory.remove_entry(2).unwrap();
+        assert_eq!(item.count, 5);
+        assert!(inventory.is_empty());
+        assert!(inventory.remove_entry(2).is_none());
+    }
+
+    #[test]
+    fn insert_overflow_goes_to_empty_selected_slot_first() {
+        let mut inventory = inventory_with(&[(0, stack(ItemType::Stone, 98))]);
+        inventory.selected_index = 4;
+        assert!(inventory.insert(stack(ItemType::Stone, 3)).is_none());
+        assert_eq!(count_at(&inventory, 0), Some(99));
+        assert_eq!(count_at(&inventory, 4), Some(2));
+    }
+
+    #[test]
+    fn insert_fills_hotbar_before_backpack() {
+        let mut inventory = inventory_with(&[(0, stack(ItemType::Stone, 1))]);
+        inventory.add_slots(6);
+        assert!(inventory.insert(stack(ItemType::Stick, 1)).is_none());
+        assert_eq!(inventory.get(1).unwrap().item.type_, ItemType::Stick);
+    }
+
+    #[test]
+    fn insert_skips_favorite_slots() {
+        let mut inventory = inventory_with(&[(1, stack(ItemType::Stone, 5))]);
+        inventory.toggle_favorite(0);
+        inventory.toggle_favorite(1);
+        assert!(inventory.insert(stack(ItemType::Stone, 2)).is_none());
+        assert!(inventory.get(0).is_none());
+        assert_eq!(count_at(&inventory, 1), Some(5));
+        assert_eq!(count_at(&inventory, 2), Some(2));
+    }
+
+    #[test]
+    fn insert_never_swaps_out_a_favorite() {
+        let mut inventory = Inventory::new();
+        for i in 0..inventory.capacity {
+            inventory.set_slot(i, stack(ItemType::Bat, 1));
+        }
+        inventory.toggle_favorite(0);
+        assert!(inventory.is_full());
+        let stick = stack(ItemType::Stick, 1);
+        assert_eq!(inventory.insert(stick), Some(stick));
+        assert_eq!(inventory.get(0).unwrap().item.type_, ItemType::Bat);
+    }
+
+    #[test]
+    fn room_for_leaves_out_favorites() {
+        let mut inventory = inventory_with(&[(0, stack(ItemType::Stone, 90))]);
+        inventory.capacity = 2;
+        inventory.toggle_favorite(0);
+        assert_eq!(inventory.room_for(&stack(ItemType::Stone, 1)), 99);
+        inventory.toggle_favorite(1);
+        assert_eq!(inventory.room_for(&stack(ItemType::Stone, 1)), 0);
+        assert!(inventory.is_full());
+    }
+
+    #[test]
+    fn add_slots_stops_at_max() {
+        let mut inventory = Inventory::new();
+        assert!(inventory.add_slots(MAX_SLOTS));
+        assert_eq!(inventory.capacity, MAX_SLOTS);
+        assert!(!inventory.add_slots(1));
+    }
+
+    #[test]
+    fn sort_merges_stacks_and_keeps_favorites() {
+        let mut inventory = inventory_with(&[
+            (0, stack(ItemType::Stick, 2)),
+            (3, stack(ItemType::Stone, 4)),
+            (5, stack(ItemType::Bat, 1)),
+            (7, stack(ItemType::Stone, 6)),
+        ]);
+        inventory.toggle_favorite(0);
+        inventory.sort(SortKey::Type);
+        assert_eq!(inventory.get(0).unwrap().item.type_, ItemType::Stick);
+        assert_eq!(inventory.get(1).unwrap().item.type_, ItemType::Bat);
+        assert_eq!(inventory.get(2).unwrap().item.type_, ItemType::Stone);
+        assert_eq!(count_at(&inventory, 2), Some(10));
+        assert_eq!(inventory.entries.len(), 3);
+    }
+
+    #[test]
+    fn sort_by_count_and_name() {
+        let mut inventory = inventory_with(&[
+            (0, stack(ItemType::Stone, 4)),
+            (1, stack(ItemType::Bat, 1)),
+            (2, stack(ItemType::Stick, 9)),
+        ]);
+        inventory.sort(SortKey::Count);
+        assert_eq!(count_at(&inventory, 0), Some(9));
+        assert_eq!(count_at(&inventory, 2), Some(1));
+        inventory.sort(SortKey::Name);
+        assert_eq!(inventory.get(0).unwrap().item.type_, ItemType::Bat);
+        assert_eq!(inventory.get(1).unwrap().item.type_, ItemType::Stick);
+        assert_eq!(inventory.get(2).unwrap().item.type_, ItemType::Stone);
+    }
+
+    #[test]
+    fn hotbar_index_wraps_within_hotbar() {
+        let mut inventory = Inventory::new();
+        inventory.add_slots(6);
+        inventory.selected_index = HOTBAR_SLOTS - 1;
+        inventory.increment_hotbar_index();
+        assert_eq!(inventory.selected_index, 0);
+        inventory.decrement_hotbar_index();
+        assert_eq!(inventory.selected_index, HOTBAR_SLOTS - 1);
     }
 }
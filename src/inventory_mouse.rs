@@ -0,0 +1,161 @@
+/* Inventory mouse handling.
+   The hotbar can be managed with the mouse as well as the number keys. Dragging a stack onto
+   another slot moves it there, topping up or swapping places the way the inventory does, and
+   dragging with the right button takes half of it instead. Letting go of a stack out over the
+   world drops it on the tile under the cursor, if that's close enough to reach. A click picks a
+   slot, and a shift-click drops the stack at the player's feet.
+   Clicks that land on the hotbar never reach item use.
+*/
+
+use glam::IVec2;
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    graphics::Graphics,
+    ground::drop_item,
+    item::Item,
+    render_ui::hotbar_slot_at,
+    state::State,
+    utils::new_york_dist,
+};
+
+/// A stack being dragged out of a hotbar slot. It stays in the slot until it's let go.
+#[derive(Debug, Clone, Copy)]
+pub struct InventoryDrag {
+    pub slot: usize,
+    /// Right-dragged, only half the stack comes along.
+    pub split: bool,
+}
+
+pub fn process_inventory_mouse(state: &mut State, audio: &mut Audio, graphics: &Graphics) {
+    const DROP_RANGE: i32 = 3; // how far away a stack can be dropped, in tiles
+
+    let mouse = state.mouse_inputs;
+    let (hovered, player_tile_pos) = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+    {
+        Some(player) => (
+            hotbar_slot_at(
+                &player.inventory,
+                graphics.screen_to_texture(mouse.pos.as_vec2()),
+            ),
+            player.pos.as_ivec2(),
+        ),
+        None => {
+            state.inventory_drag = None;
+            return;
+        }
+    };
+
+    match state.inventory_drag {
+        None => {
+            if let Some(slot) = hovered {
+                let has_item = slot_item(state, slot).is_some();
+                if mouse.left_pressed && mouse.shift {
+                    if has_item {
+                        drop_from_slot(state, audio, slot, u32::MAX, player_tile_pos);
+                    }
+                } else if (mouse.left_pressed || mouse.right_pressed) && has_item {
+                    state.inventory_drag = Some(InventoryDrag {
+                        slot,
+                        split: mouse.right_pressed,
+                    });
+                } else if mouse.left_pressed {
+                    select_slot(state, slot);
+                }
+            }
+        }
+        Some(drag) => {
+            // the button coming up while the game was elsewhere counts as letting go too
+            let let_go = if drag.split {
+                mouse.right_released || !mouse.right
+            } else {
+                mouse.left_released || !mouse.left
+            };
+            if let_go {
+                state.inventory_drag = None;
+                match hovered {
+                    Some(slot) if slot == drag.slot => select_slot(state, slot),
+                    Some(slot) => {
+                        let moved = state
+                            .player_vid
+                            .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+                            .is_some_and(|player| {
+                                if drag.split {
+                                    player.inventory.split_slot(drag.slot, slot)
+                                } else {
+                                    player.inventory.move_slot(drag.slot, slot)
+                                }
+                            });
+                        if !moved {
+                            audio.play_sound_effect(SoundEffect::CantUse);
+                        }
+                    }
+                    None => {
+                        let target = graphics.screen_to_tile(mouse.pos.as_vec2());
+                        let count = match slot_item(state, drag.slot) {
+                            Some(item) if drag.split => item.count.div_ceil(2),
+                            _ => u32::MAX,
+                        };
+                        if new_york_dist(player_tile_pos, target) <= DROP_RANGE
+                            && state.stage.in_bounds(target)
+                            && state
+                                .stage
+                                .is_walkable(target.x as usize, target.y as usize)
+                        {
+                            drop_from_slot(state, audio, drag.slot, count, target);
+                        } else {
+                            audio.play_sound_effect(SoundEffect::CantUse);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // keep hotbar clicks and drags from using the item in hand
+    if hovered.is_some() || state.inventory_drag.is_some() {
+        state.mouse_inputs.left = false;
+    }
+}
+
+fn slot_item(state: &State, slot: usize) -> Option<Item> {
+    state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .and_then(|player| player.inventory.get(slot))
+        .map(|entry| entry.item)
+}
+
+fn select_slot(state: &mut State, slot: usize) {
+    if let Some(player) = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        player.inventory.set_selected_index(slot);
+    }
+}
+
+/// Drops up to `count` from a slot onto a tile, taking it out of the inventory once it's down.
+fn drop_from_slot(state: &mut State, audio: &mut Audio, slot: usize, count: u32, pos: IVec2) {
+    let item = match slot_item(state, slot) {
+        Some(item) => item,
+        None => return,
+    };
+    if !item.droppable {
+        audio.play_sound_effect(SoundEffect::CantUse);
+        return;
+    }
+    let dropping = Item {
+        count: count.min(item.count),
+        ..item
+    };
+    let dropped = drop_item(state, audio, dropping, pos);
+    if let Some(player) = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        player.inventory.remove_count_from_slot(slot, dropped);
+    }
+}
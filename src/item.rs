@@ -1,6 +1,9 @@
+use serde::Deserialize;
+
 use crate::{item, item_use::use_item, sprite::Sprite, tile::Tile};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ItemType {
     Wall,
     Medkit,
@@ -8,6 +11,26 @@ pub enum ItemType {
     Bandaid,
     Fist,
     ConductorHat,
+    Rail,
+    RailSwitch,
+    Bat,
+    SpikeTrap,
+    Torch,
+    Whistle,
+    Helmet,
+    Jacket,
+    Gloves,
+    Lantern,
+    Stone,
+    Feather,
+    Meat,
+    Cloth,
+    Stick,
+    Arrow,
+    Egg,
+    Scrap,
+    Chest,
+    Backpack,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -39,6 +62,12 @@ pub struct Item {
     pub min_range: f32, // in tiles, minimum range for use
     pub range: f32,     // in tiles
 
+    pub value: u32, // points per unit when delivered by train
+
+    /// Uses (or hits, for worn gear) it can take before it breaks. 0 if it never wears out.
+    pub max_durability: u32,
+    pub durability: u32,
+
     // --- Associated Game Objects ---
     pub sprite: Option<Sprite>,
     // pub attributes: Vec<ItemAttributes>,
@@ -55,6 +84,20 @@ impl Item {
         self.max_count > 1
     }
 
+    /// Takes a point of durability off. Returns true if that broke it, and marks it for
+    /// destruction. Items that don't wear out never break.
+    pub fn wear(&mut self) -> bool {
+        if self.max_durability == 0 || self.durability == 0 {
+            return false;
+        }
+        self.durability -= 1;
+        if self.durability == 0 {
+            self.marked_for_destruction = true;
+            return true;
+        }
+        false
+    }
+
     pub fn step_cooldown(&mut self, dt: f32) {
         // Decrease the cooldown countdown by the elapsed time
         if self.use_cooldown_countdown > 0.0 {
@@ -83,7 +126,10 @@ impl Item {
                 use_cooldown_countdown: 0.0,
                 min_range: 1.0, // Walls can be placed on the same tile
                 range: 2.0,     // Walls can be placed on adjacent tiles
+                max_durability: 100,
+                durability: 100,
                 sprite: Some(Sprite::Wall),
+                value: 2,
             },
             // the big heal
             ItemType::Medkit => Item {
@@ -101,7 +147,10 @@ impl Item {
                 use_cooldown_countdown: 0.0,
                 min_range: 0.0, // Medkits are used on the player, not on tiles
                 range: 0.0,     // Medkits are used on the player, not on tiles
+                max_durability: 0,
+                durability: 0,
                 sprite: Some(Sprite::Medkit),
+                value: 25,
             },
             // the medium heal: bandage
             ItemType::Bandage => Item {
@@ -119,7 +168,10 @@ impl Item {
                 use_cooldown_countdown: 0.0,
                 min_range: 0.0, // Bandages are used on the player, not on tiles
                 range: 0.0,     // Bandages are used on the player, not on tiles
+                max_durability: 0,
+                durability: 0,
                 sprite: Some(Sprite::Bandage),
+                value: 8,
             },
             // the mini heal: bandaid
             ItemType::Bandaid => Item {
@@ -137,7 +189,10 @@ impl Item {
                 use_cooldown_countdown: 0.0,
                 min_range: 0.0, // Bandaids are used on the player, not on tiles
                 range: 0.0,     // Bandaids are used on the player, not on tiles
+                max_durability: 0,
+                durability: 0,
                 sprite: Some(Sprite::Bandaid),
+                value: 3,
             },
 
             ItemType::Fist => Item {
@@ -155,26 +210,443 @@ impl Item {
                 use_cooldown_countdown: 0.0,
                 min_range: 1.0, // Fists can hit the same tile
                 range: 1.0,     // Fists can hit adjacent tiles
+                max_durability: 0,
+                durability: 0,
                 sprite: Some(Sprite::Fist),
+                value: 0,
             },
             // conductor hat
             ItemType::ConductorHat => Item {
                 type_: ItemType::ConductorHat,
                 name: "Conductor Hat",
-                description: "choo choo",
+                description: "choo choo. wear it to see the trains coming",
                 marked_for_destruction: false,
 
-                usable: true,
+                usable: true, // using it puts it on
                 droppable: true,
-                consume_on_use: true,
+                consume_on_use: false,
                 max_count: 1,      // Hats are not stackable
                 count: 1,          // Always 1 for hats
                 use_cooldown: 0.0, // Hats don't have a cooldown
                 use_cooldown_countdown: 0.0,
                 min_range: 0.0,
                 range: 0.0,
+                max_durability: 0,
+                durability: 0,
                 sprite: Some(Sprite::ConductorHat),
+                value: 50,
+            },
+            ItemType::Rail => Item {
+                type_: ItemType::Rail,
+                name: "Rail",
+                description: "lay track, bend it, join it up",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 99,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 0.1,
+                use_cooldown_countdown: 0.0,
+                min_range: 1.0,
+                range: 2.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Rail),
+                value: 1,
+            },
+            ItemType::RailSwitch => Item {
+                type_: ItemType::RailSwitch,
+                name: "Rail Switch",
+                description: "place on a junction, interact to throw it",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 10,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 0.2,
+                use_cooldown_countdown: 0.0,
+                min_range: 1.0,
+                range: 2.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::RailSwitch),
+                value: 10,
+            },
+            ItemType::Bat => Item {
+                type_: ItemType::Bat,
+                name: "Bat",
+                description: "hits harder than a fist",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 1,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.35,
+                use_cooldown_countdown: 0.0,
+                min_range: 1.0,
+                range: 1.0,
+                max_durability: 60,
+                durability: 60,
+                sprite: Some(Sprite::Bat),
+                value: 30,
+            },
+            ItemType::SpikeTrap => Item {
+                type_: ItemType::SpikeTrap,
+                name: "Spike Trap",
+                description: "zombies that walk over it regret it",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 20,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 0.2,
+                use_cooldown_countdown: 0.0,
+                min_range: 1.0,
+                range: 2.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Spikes),
+                value: 5,
+            },
+            ItemType::Torch => Item {
+                type_: ItemType::Torch,
+                name: "Torch",
+                description: "keeps the dark back a little",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 20,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 0.2,
+                use_cooldown_countdown: 0.0,
+                min_range: 1.0,
+                range: 2.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Torch),
+                value: 5,
+            },
+            // calls in the rail layer
+            ItemType::Whistle => Item {
+                type_: ItemType::Whistle,
+                name: "Whistle",
+                description: "one long blast brings the rail layer",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                consume_on_use: true,
+                max_count: 5,
+                count: 1,
+                use_cooldown: 1.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Whistle),
+                value: 40,
+            },
+            ItemType::Helmet => Item {
+                type_: ItemType::Helmet,
+                name: "Helmet",
+                description: "heavy, but it keeps your head on",
+                marked_for_destruction: false,
+
+                usable: true, // using it puts it on
+                droppable: true,
+                max_count: 1,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 40,
+                durability: 40,
+                sprite: Some(Sprite::Helmet),
+                value: 40,
+            },
+            ItemType::Jacket => Item {
+                type_: ItemType::Jacket,
+                name: "Jacket",
+                description: "thick leather, scratches don't get far",
+                marked_for_destruction: false,
+
+                usable: true, // using it puts it on
+                droppable: true,
+                max_count: 1,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 30,
+                durability: 30,
+                sprite: Some(Sprite::Jacket),
+                value: 35,
+            },
+            ItemType::Gloves => Item {
+                type_: ItemType::Gloves,
+                name: "Gloves",
+                description: "work gloves with some weight in the knuckles",
+                marked_for_destruction: false,
+
+                usable: true, // using it puts it on
+                droppable: true,
+                max_count: 1,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 50,
+                durability: 50,
+                sprite: Some(Sprite::Gloves),
+                value: 30,
+            },
+            ItemType::Lantern => Item {
+                type_: ItemType::Lantern,
+                name: "Lantern",
+                description: "held out in front, shows a little more of the dark",
+                marked_for_destruction: false,
+
+                usable: true, // using it puts it on
+                droppable: true,
+                max_count: 1,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Lantern),
+                value: 30,
+            },
+            // crafting materials
+            ItemType::Stone => Item {
+                type_: ItemType::Stone,
+                name: "Stone",
+                description: "a lump of broken wall",
+                marked_for_destruction: false,
+
+                usable: false,
+                droppable: true,
+                max_count: 99,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Stone),
+                value: 1,
+            },
+            ItemType::Feather => Item {
+                type_: ItemType::Feather,
+                name: "Feather",
+                description: "still a bit warm",
+                marked_for_destruction: false,
+
+                usable: false,
+                droppable: true,
+                max_count: 99,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Feather),
+                value: 1,
+            },
+            ItemType::Meat => Item {
+                type_: ItemType::Meat,
+                name: "Meat",
+                description: "raw, but it's something",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 10,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 1.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Meat),
+                value: 3,
+            },
+            ItemType::Cloth => Item {
+                type_: ItemType::Cloth,
+                name: "Cloth",
+                description: "torn off something that used to be a shirt",
+                marked_for_destruction: false,
+
+                usable: false,
+                droppable: true,
+                max_count: 99,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Cloth),
+                value: 1,
+            },
+            ItemType::Stick => Item {
+                type_: ItemType::Stick,
+                name: "Stick",
+                description: "what's left of a door",
+                marked_for_destruction: false,
+
+                usable: false,
+                droppable: true,
+                max_count: 99,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Stick),
+                value: 1,
+            },
+            ItemType::Arrow => Item {
+                type_: ItemType::Arrow,
+                name: "Arrow",
+                description: "thrown by hand it still stings",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 50,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 0.5,
+                use_cooldown_countdown: 0.0,
+                min_range: 2.0,
+                range: 5.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Arrow),
+                value: 2,
+            },
+            ItemType::Egg => Item {
+                type_: ItemType::Egg,
+                name: "Egg",
+                description: "fresh from a hen that won't miss it",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 12,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 1.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Egg),
+                value: 2,
+            },
+            ItemType::Scrap => Item {
+                type_: ItemType::Scrap,
+                name: "Scrap",
+                description: "bent metal, good for patching things up",
+                marked_for_destruction: false,
+
+                usable: false,
+                droppable: true,
+                max_count: 99,
+                count: 1,
+                consume_on_use: false,
+                use_cooldown: 0.0,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Scrap),
+                value: 2,
+            },
+            // storage you can put down anywhere
+            ItemType::Chest => Item {
+                type_: ItemType::Chest,
+                name: "Chest",
+                description: "somewhere to keep what won't fit in your pockets",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 5,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 0.3,
+                use_cooldown_countdown: 0.0,
+                min_range: 1.0,
+                range: 1.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Chest),
+                value: 6,
+            },
+            // sewn onto what you're already carrying, so it's gone once it's used
+            ItemType::Backpack => Item {
+                type_: ItemType::Backpack,
+                name: "Backpack",
+                description: "more room on your back",
+                marked_for_destruction: false,
+
+                usable: true,
+                droppable: true,
+                max_count: 1,
+                count: 1,
+                consume_on_use: true,
+                use_cooldown: 0.5,
+                use_cooldown_countdown: 0.0,
+                min_range: 0.0,
+                range: 0.0,
+                max_durability: 0,
+                durability: 0,
+                sprite: Some(Sprite::Backpack),
+                value: 15,
             },
         }
     }
+
+    /// A stack of `count`, capped at however many fit in one.
+    pub fn new_stack(kind: ItemType, count: u32) -> Self {
+        let mut item = Item::new(kind);
+        item.count = count.clamp(1, item.max_count);
+        item
+    }
 }
@@ -2,14 +2,19 @@ use glam::{IVec2, Vec2};
 
 use crate::{
     audio::{Audio, SoundEffect},
-    entity::{Alignment, DamageType, VID},
-    entity_behavior::{attack, AttackType},
+    containers::{container_at, spawn_container, ContainerKind},
+    entity::{Alignment, VID},
+    entity_behavior::{attack, attack_damage, AttackType},
     entity_templates::init_as_rail_layer,
+    equipment::equip_info,
     graphics::Graphics,
     item::{Item, ItemType},
+    lighting::has_line_of_sight,
+    particle_templates::debris_splatter,
     render::TILE_SIZE,
-    stage::TileData,
+    stage::{TileData, TileLayer},
     state::State,
+    status::StatusKind,
     tile::{self, damage_tile, tile_shake_area_at, Tile},
     utils::new_york_dist,
 };
@@ -37,6 +42,9 @@ pub fn use_item(
 
     if success {
         // If the action was successful, apply cooldown and consumption.
+        // Gear only wears from the hits it takes, and after a swap `item` is the piece that
+        // just came off.
+        let wears = equip_info(item.type_).is_none();
         item.use_cooldown_countdown = item.use_cooldown;
 
         if item.consume_on_use {
@@ -46,12 +54,27 @@ pub fn use_item(
             if item.count == 0 {
                 item.marked_for_destruction = true;
             }
+        } else if wears && item.wear() {
+            if let Some(pos) = user_vid
+                .and_then(|vid| state.entity_manager.get_entity(vid))
+                .map(|user| user.pos)
+            {
+                on_item_break(state, audio, pos, item);
+            }
         }
     }
 
     success
 }
 
+/// Sound and bits flying off when something wears out in someone's hands.
+pub fn on_item_break(state: &mut State, audio: &mut Audio, pos: Vec2, item: &Item) {
+    audio.play_sound_effect(SoundEffect::BoxBreak);
+    if let Some(sprite) = item.sprite {
+        debris_splatter(&mut state.particles, pos, Vec2::new(0.0, -1.0), sprite);
+    }
+}
+
 //////////////////////////////////////////// ITEM LOGIC LUT ////////////////////////////////////////////
 
 /// Calls the correct specific-item-use function based on the item's type.
@@ -69,18 +92,40 @@ fn use_item_internal_lookup(
         ItemType::Bandage => use_bandage(state, audio, user_vid, item),
         ItemType::Bandaid => use_bandaid(state, audio, user_vid, item),
         ItemType::Fist => use_fist(state, graphics, audio, user_vid, item),
-        ItemType::ConductorHat => use_conductor_hat(state, audio, user_vid, item),
+        ItemType::Rail => use_rail(state, graphics, audio, user_vid, item),
+        ItemType::RailSwitch => use_rail_switch(state, graphics, audio, user_vid, item),
+        ItemType::Bat => use_bat(state, graphics, audio, user_vid, item),
+        ItemType::SpikeTrap => use_spike_trap(state, graphics, audio, user_vid, item),
+        ItemType::Torch => use_torch(state, graphics, audio, user_vid, item),
+        ItemType::Whistle => use_whistle(state, audio, user_vid, item),
+        ItemType::ConductorHat
+        | ItemType::Helmet
+        | ItemType::Jacket
+        | ItemType::Gloves
+        | ItemType::Lantern => use_wearable(state, audio, user_vid, item),
+        ItemType::Meat | ItemType::Egg => use_food(state, audio, user_vid, item),
+        ItemType::Arrow => use_arrow(state, graphics, audio, user_vid, item),
+        ItemType::Chest => use_chest(state, graphics, audio, user_vid, item),
+        ItemType::Backpack => use_backpack(state, audio, user_vid, item),
+        // materials, only good for crafting
+        ItemType::Stone
+        | ItemType::Feather
+        | ItemType::Cloth
+        | ItemType::Stick
+        | ItemType::Scrap => false,
     }
 }
 
 //////////////////////////////////////////// SPECIFIC ITEM LOGIC ////////////////////////////////////////////
-/// Places a wall tile at the mouse cursor location if within range and on a valid tile.
-pub fn use_wall(
+/// Places a structure tile at the use position if it's within the item's range and buildable.
+fn place_structure(
     state: &mut State,
     graphics: &Graphics,
     audio: &mut Audio,
     user_vid: Option<VID>,
     item: &Item,
+    tile: TileData,
+    sound: SoundEffect,
 ) -> bool {
     let user = match user_vid.and_then(|vid| state.entity_manager.get_entity(vid)) {
         Some(e) => e,
@@ -99,18 +144,12 @@ pub fn use_wall(
         && distance <= item.range as i32
         && tile::can_build_on(state, target_tile_pos)
     {
-        // Place the wall.
-        let mut tile = TileData::default();
-        tile.tile = Tile::Wall;
-        tile.hp = 100;
-        tile.max_hp = 100;
-        tile.breakable = true;
-
-        state
-            .stage
-            .set_tile(target_tile_pos.x as usize, target_tile_pos.y as usize, tile);
+        let (x, y) = (target_tile_pos.x as usize, target_tile_pos.y as usize);
+        state.stage.set_tile(x, y, tile);
+        // building over rubble clears it away
+        state.stage.clear_layer_tile(TileLayer::Overlay, x, y);
 
-        audio.play_sound_effect(SoundEffect::BlockLand);
+        audio.play_sound_effect(sound);
         return true; // Success
     }
 
@@ -119,6 +158,185 @@ pub fn use_wall(
     false // Use failed
 }
 
+/// Places a wall tile at the mouse cursor location if within range and on a valid tile.
+pub fn use_wall(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    // the wall holds up as well as the one in hand did
+    let mut tile = TileData::default();
+    tile.tile = Tile::Wall;
+    tile.hp = item.durability.min(u8::MAX as u32) as u8;
+    tile.max_hp = item.max_durability.min(u8::MAX as u32) as u8;
+    tile.breakable = true;
+
+    place_structure(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        tile,
+        SoundEffect::BlockLand,
+    )
+}
+
+/// Sets down an empty chest next to the user.
+pub fn use_chest(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    let user_tile_pos = match user_vid.and_then(|vid| state.entity_manager.get_entity(vid)) {
+        Some(user) => user.pos.as_ivec2(),
+        None => return false,
+    };
+    let target_tile_pos = match get_item_use_pos(state, graphics) {
+        Some(tile) => tile,
+        None => return false,
+    };
+    let distance = new_york_dist(user_tile_pos, target_tile_pos);
+
+    if distance >= item.min_range as i32
+        && distance <= item.range as i32
+        && tile::can_build_on(state, target_tile_pos)
+        && state
+            .stage
+            .is_walkable(target_tile_pos.x as usize, target_tile_pos.y as usize)
+        && container_at(state, target_tile_pos).is_none()
+        && spawn_container(state, ContainerKind::Chest, target_tile_pos, &[]).is_some()
+    {
+        audio.play_sound_effect(SoundEffect::BlockLand);
+        return true;
+    }
+
+    audio.play_sound_effect(SoundEffect::CantUse);
+    false
+}
+
+/// Lays a piece of track. Curves and junctions sort themselves out from the neighbors.
+pub fn use_rail(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    let mut tile = TileData::default();
+    tile.tile = Tile::Rail;
+
+    place_structure(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        tile,
+        SoundEffect::RailPlace,
+    )
+}
+
+/// Places a switch, either on open ground or swapped in for an existing piece of track.
+pub fn use_rail_switch(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    let mut tile = TileData::default();
+    tile.tile = Tile::RailSwitch;
+
+    // upgrading a plain rail in range skips the buildable check
+    if let (Some(user), Some(target_tile_pos)) = (
+        user_vid.and_then(|vid| state.entity_manager.get_entity(vid)),
+        get_item_use_pos(state, graphics),
+    ) {
+        let distance = new_york_dist(user.pos.as_ivec2(), target_tile_pos);
+        let on_rail = state
+            .stage
+            .get_layer_tile(
+                TileLayer::Structure,
+                target_tile_pos.x as usize,
+                target_tile_pos.y as usize,
+            )
+            .is_some_and(|td| td.tile == Tile::Rail);
+
+        if on_rail && distance >= item.min_range as i32 && distance <= item.range as i32 {
+            state
+                .stage
+                .set_tile(target_tile_pos.x as usize, target_tile_pos.y as usize, tile);
+            audio.play_sound_effect(SoundEffect::RailPlace);
+            return true;
+        }
+    }
+
+    place_structure(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        tile,
+        SoundEffect::RailPlace,
+    )
+}
+
+/// Sets spikes into the ground. They hurt zombies that walk over them and wear out doing it.
+pub fn use_spike_trap(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    let mut tile = TileData::default();
+    tile.tile = Tile::Spikes;
+    tile.hp = 60;
+    tile.max_hp = 60;
+    tile.breakable = true;
+
+    place_structure(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        tile,
+        SoundEffect::BlockLand,
+    )
+}
+
+/// Stands a torch up on the ground. It lights the area around it and can be knocked over.
+pub fn use_torch(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    let mut tile = TileData::default();
+    tile.tile = Tile::Torch;
+    tile.hp = 20;
+    tile.max_hp = 20;
+    tile.breakable = true;
+
+    place_structure(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        tile,
+        SoundEffect::BlockLand,
+    )
+}
+
 /// Heals the user for a fixed amount if their health is not full.
 pub fn use_medkit(
     state: &mut State,
@@ -130,8 +348,10 @@ pub fn use_medkit(
         if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
             const HEAL_AMOUNT: u32 = 100;
 
-            // Use the entity's own max_hp value
-            if entity.health < entity.max_hp {
+            // patches up everything, infection included
+            let cured =
+                entity.status.cure(StatusKind::Bleeding) | entity.status.cure(StatusKind::Infected);
+            if cured || entity.health < entity.max_hp {
                 entity.health = (entity.health + HEAL_AMOUNT).min(entity.max_hp);
                 audio.play_sound_effect(SoundEffect::ClothRip);
                 return true; // Success
@@ -143,7 +363,7 @@ pub fn use_medkit(
     false
 }
 
-/// bandage is like medkit but only heals 10 HP
+/// bandage is like medkit but only heals 10 HP, and stops the bleeding
 pub fn use_bandage(
     state: &mut State,
     audio: &mut Audio,
@@ -154,8 +374,8 @@ pub fn use_bandage(
         if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
             const HEAL_AMOUNT: u32 = 10;
 
-            // Use the entity's own max_hp value
-            if entity.health < entity.max_hp {
+            let cured = entity.status.cure(StatusKind::Bleeding);
+            if cured || entity.health < entity.max_hp {
                 entity.health = (entity.health + HEAL_AMOUNT).min(entity.max_hp);
                 audio.play_sound_effect(SoundEffect::ClothRip);
                 return true; // Success
@@ -167,7 +387,7 @@ pub fn use_bandage(
     false
 }
 
-/// bandaid is like medkit but only heals 1 HP
+/// bandaid is like medkit but only heals 1 HP, and slows the bleeding
 pub fn use_bandaid(
     state: &mut State,
     audio: &mut Audio,
@@ -178,8 +398,9 @@ pub fn use_bandaid(
         if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
             const HEAL_AMOUNT: u32 = 1;
 
-            // Use the entity's own max_hp value
-            if entity.health < entity.max_hp {
+            // only covers one wound
+            let cured = entity.status.reduce(StatusKind::Bleeding);
+            if cured || entity.health < entity.max_hp {
                 entity.health = (entity.health + HEAL_AMOUNT).min(entity.max_hp);
                 audio.play_sound_effect(SoundEffect::ClothRip);
                 return true; // Success
@@ -191,6 +412,41 @@ pub fn use_bandaid(
     false
 }
 
+/// Adds slots to the user's backpack, unless it's already as big as it gets.
+pub fn use_backpack(
+    state: &mut State,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    _item: &Item,
+) -> bool {
+    const BACKPACK_SLOTS: usize = 6;
+    if let Some(entity) = user_vid.and_then(|vid| state.entity_manager.get_entity_mut(vid)) {
+        if entity.inventory.add_slots(BACKPACK_SLOTS) {
+            audio.play_sound_effect(SoundEffect::ClothRip);
+            return true;
+        }
+    }
+    audio.play_sound_effect(SoundEffect::CantUse);
+    false
+}
+
+/// Eaten raw, heals a little.
+pub fn use_food(state: &mut State, audio: &mut Audio, user_vid: Option<VID>, item: &Item) -> bool {
+    let heal_amount = match item.type_ {
+        ItemType::Meat => 5,
+        _ => 3,
+    };
+    if let Some(entity) = user_vid.and_then(|vid| state.entity_manager.get_entity_mut(vid)) {
+        if entity.health < entity.max_hp {
+            entity.health = (entity.health + heal_amount).min(entity.max_hp);
+            audio.play_sound_effect(SoundEffect::AnimalCrush1);
+            return true;
+        }
+    }
+    audio.play_sound_effect(SoundEffect::CantUse);
+    false
+}
+
 /// Attacks an entity or damages a tile at the mouse cursor location.
 pub fn use_fist(
     state: &mut State,
@@ -198,6 +454,68 @@ pub fn use_fist(
     audio: &mut Audio,
     user_vid: Option<VID>,
     item: &Item,
+) -> bool {
+    const FIST_DAMAGE: u8 = 10;
+    melee_strike(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        AttackType::FistPunch,
+        FIST_DAMAGE,
+    )
+}
+
+/// Like the fist, but hits zombies and walls a lot harder.
+pub fn use_bat(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    const BAT_DAMAGE: u8 = 20;
+    melee_strike(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        AttackType::BatSwing,
+        BAT_DAMAGE,
+    )
+}
+
+/// Thrown at something a few tiles off. Not much use against walls.
+pub fn use_arrow(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+) -> bool {
+    const ARROW_DAMAGE: u8 = 5;
+    melee_strike(
+        state,
+        graphics,
+        audio,
+        user_vid,
+        item,
+        AttackType::ArrowThrow,
+        ARROW_DAMAGE,
+    )
+}
+
+/// Hits the first entity on the target tile, or the tile itself if there's nobody there.
+fn melee_strike(
+    state: &mut State,
+    graphics: &Graphics,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &Item,
+    attack_type: AttackType,
+    tile_damage: u8,
 ) -> bool {
     let user_vid = match user_vid {
         Some(vid) => vid,
@@ -216,7 +534,11 @@ pub fn use_fist(
     let user_tile_pos = user_pos.as_ivec2();
     let distance = new_york_dist(user_tile_pos, target_tile_pos);
 
-    if distance >= item.min_range as i32 && distance <= item.range as i32 {
+    // nothing gets hit through a wall, arrows included
+    if distance >= item.min_range as i32
+        && distance <= item.range as i32
+        && has_line_of_sight(&state.stage, user_tile_pos, target_tile_pos)
+    {
         // --- 1. Prioritize attacking entities ---
         if let Some(vids_in_cell) = state
             .spatial_grid
@@ -226,25 +548,18 @@ pub fn use_fist(
             // if theres even one, just attack the first one
             if let Some(&attackee_vid) = vids_in_cell.first() {
                 // Perform the attack
-                attack(
-                    state,
-                    audio,
-                    &user_vid,
-                    &attackee_vid,
-                    AttackType::FistPunch,
-                );
+                attack(state, audio, &user_vid, &attackee_vid, attack_type);
                 return true; // Successfully attacked an entity
             }
         }
 
         // --- 2. If no entity, try to damage a tile ---
-        const FIST_DAMAGE: u8 = 10;
         if damage_tile(
             state,
             audio,
             target_tile_pos,
-            FIST_DAMAGE,
-            DamageType::Punch,
+            tile_damage,
+            attack_damage(attack_type).type_,
             user_pos,
         ) {
             tile_shake_area_at(
@@ -261,7 +576,30 @@ pub fn use_fist(
     false
 }
 
-pub fn use_conductor_hat(
+/// Puts the item on. Whatever was already in that slot takes its place in the inventory.
+pub fn use_wearable(
+    state: &mut State,
+    audio: &mut Audio,
+    user_vid: Option<VID>,
+    item: &mut Item,
+) -> bool {
+    let user = match user_vid.and_then(|vid| state.entity_manager.get_entity_mut(vid)) {
+        Some(user) => user,
+        None => return false,
+    };
+    match user.equipment.equip(*item) {
+        Ok(Some(taken_off)) => *item = taken_off,
+        Ok(None) => item.marked_for_destruction = true, // it's on, so the slot empties
+        Err(_) => {
+            audio.play_sound_effect(SoundEffect::CantUse);
+            return false;
+        }
+    }
+    audio.play_sound_effect(SoundEffect::ClothRip);
+    true
+}
+
+pub fn use_whistle(
     state: &mut State,
     audio: &mut Audio,
     user_vid: Option<VID>,
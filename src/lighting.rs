@@ -0,0 +1,309 @@
+/* Light and line of sight.
+   Sight and light both spread with recursive shadowcasting, so walls, rocks and doors throw
+   shadows the same way for each of them. Every step we cast once from the player to find what
+   they have a clear line to, once per light source to build up a colored light map, and then
+   combine the two: a tile is visible if the player has a line to it and it's either close
+   enough to make out or lit up.
+
+   Anything the player has seen once stays on the `explored` memory, which carries over from
+   step to step and into saves. Rendering reads `visible`, `explored` and `light`; AI uses
+   `has_line_of_sight` directly.
+*/
+
+use glam::{IVec2, Vec3};
+
+use crate::{
+    entity::EntityType,
+    render::{view_distance, TILE_SIZE},
+    stage::{Stage, TileLayer},
+    state::State,
+    tile::Tile,
+};
+
+/// Farthest the player can see a lit tile, in tiles.
+pub const SIGHT_RADIUS: i32 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSource {
+    pub pos: IVec2,
+    /// In tiles.
+    pub radius: i32,
+    /// Channels 0.0 to 1.0.
+    pub color: Vec3,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lighting {
+    /// Tiles the player has a clear line to, out to `SIGHT_RADIUS`.
+    pub in_sight: Vec<Vec<bool>>,
+    /// Tiles the player can actually make out.
+    pub visible: Vec<Vec<bool>>,
+    /// Light falling on each tile.
+    pub light: Vec<Vec<Vec3>>,
+    /// Tiles the player has made out at some point this run.
+    pub explored: Vec<Vec<bool>>,
+    /// Every tile in `visible`, so the map can tell what came into and went out of view.
+    pub visible_tiles: Vec<IVec2>,
+}
+
+impl Lighting {
+    pub fn new(width: usize, height: usize) -> Lighting {
+        Lighting {
+            in_sight: vec![vec![false; height]; width],
+            visible: vec![vec![false; height]; width],
+            light: vec![vec![Vec3::ZERO; height]; width],
+            explored: vec![vec![false; height]; width],
+            visible_tiles: Vec::new(),
+        }
+    }
+
+    pub fn is_explored(&self, pos: IVec2) -> bool {
+        self.explored
+            .get(pos.x as usize)
+            .and_then(|column| column.get(pos.y as usize))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub fn is_visible(&self, pos: IVec2) -> bool {
+        self.visible
+            .get(pos.x as usize)
+            .and_then(|column| column.get(pos.y as usize))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Brightest channel of the light on a tile, 0.0 to 1.0.
+    pub fn light_level(&self, pos: IVec2) -> f32 {
+        self.light
+            .get(pos.x as usize)
+            .and_then(|column| column.get(pos.y as usize))
+            .map_or(0.0, |light| light.max_element())
+    }
+}
+
+/// Walls, rocks, doors and the like can't be seen through. Out of bounds can't either.
+pub fn blocks_sight(stage: &Stage, pos: IVec2) -> bool {
+    if !stage.in_bounds(pos) {
+        return true;
+    }
+    stage
+        .get_layer_tile(TileLayer::Structure, pos.x as usize, pos.y as usize)
+        .is_some_and(|td| !td.tile.transparent())
+}
+
+// (xx, xy, yx, yy) multipliers that map the first octant onto each of the eight
+const OCTANTS: [(i32, i32, i32, i32); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+/// Calls `visit` for every tile within `radius` of `origin` that isn't in the shadow of
+/// something opaque, including the origin and the opaque tiles themselves. Tiles on the
+/// straight lines and diagonals out from the origin border two octants and come up twice.
+pub fn shadowcast(stage: &Stage, origin: IVec2, radius: i32, visit: &mut dyn FnMut(IVec2)) {
+    if stage.in_bounds(origin) {
+        visit(origin);
+    }
+    for octant in OCTANTS {
+        cast_octant(stage, origin, radius, octant, 1, (1.0, 0.0), visit);
+    }
+}
+
+/// One octant of the scan, row by row outwards, recursing past each opaque run.
+fn cast_octant(
+    stage: &Stage,
+    origin: IVec2,
+    radius: i32,
+    octant: (i32, i32, i32, i32),
+    row: i32,
+    (mut start_slope, end_slope): (f32, f32),
+    visit: &mut dyn FnMut(IVec2),
+) {
+    let (xx, xy, yx, yy) = octant;
+    if start_slope < end_slope {
+        return;
+    }
+    let mut next_start_slope = start_slope;
+    for distance in row..=radius {
+        let dy = -distance;
+        let mut blocked = false;
+        for dx in -distance..=0 {
+            let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+            let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+            if start_slope < right_slope {
+                continue;
+            } else if end_slope > left_slope {
+                break;
+            }
+
+            let pos = origin + IVec2::new(dx * xx + dy * xy, dx * yx + dy * yy);
+            if dx * dx + dy * dy <= radius * radius && stage.in_bounds(pos) {
+                visit(pos);
+            }
+
+            let opaque = blocks_sight(stage, pos);
+            if blocked {
+                if opaque {
+                    next_start_slope = right_slope;
+                } else {
+                    blocked = false;
+                    start_slope = next_start_slope;
+                }
+            } else if opaque && distance < radius {
+                blocked = true;
+                cast_octant(
+                    stage,
+                    origin,
+                    radius,
+                    octant,
+                    distance + 1,
+                    (start_slope, left_slope),
+                    visit,
+                );
+                next_start_slope = right_slope;
+            }
+        }
+        if blocked {
+            break;
+        }
+    }
+}
+
+/// True if nothing opaque sits on the straight line between two tiles. The ends don't count.
+pub fn has_line_of_sight(stage: &Stage, from: IVec2, to: IVec2) -> bool {
+    let delta = to - from;
+    let steps = delta.x.abs().max(delta.y.abs());
+    for i in 1..steps {
+        let t = i as f32 / steps as f32;
+        let pos = IVec2::new(
+            (from.x as f32 + delta.x as f32 * t).round() as i32,
+            (from.y as f32 + delta.y as f32 * t).round() as i32,
+        );
+        if blocks_sight(stage, pos) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Everything giving off light right now: torches, fires, train headlights, crossing lamps.
+pub fn collect_light_sources(state: &State) -> Vec<LightSource> {
+    const TORCH_COLOR: Vec3 = Vec3::new(1.0, 0.65, 0.3);
+    const FIRE_COLOR: Vec3 = Vec3::new(1.0, 0.45, 0.15);
+    const HEADLIGHT_COLOR: Vec3 = Vec3::new(1.0, 0.95, 0.75);
+    const SIGNAL_COLOR: Vec3 = Vec3::new(1.0, 0.1, 0.05);
+
+    let mut sources = Vec::new();
+    for x in 0..state.stage.get_width() {
+        for y in 0..state.stage.get_height() {
+            let td = match state.stage.get_layer_tile(TileLayer::Structure, x, y) {
+                Some(td) => td,
+                None => continue,
+            };
+            let pos = IVec2::new(x as i32, y as i32);
+            match td.tile {
+                Tile::Torch => sources.push(LightSource {
+                    pos,
+                    radius: 6,
+                    color: TORCH_COLOR,
+                }),
+                Tile::Campfire => sources.push(LightSource {
+                    pos,
+                    radius: 8,
+                    color: FIRE_COLOR,
+                }),
+                // the lamp on a closed crossing, on its lit beat
+                Tile::Crossing if td.switched && td.variant == 0 => sources.push(LightSource {
+                    pos,
+                    radius: 3,
+                    color: SIGNAL_COLOR,
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    // headlights shine from a little way ahead of the engine
+    for train in state
+        .entity_manager
+        .iter()
+        .filter(|e| e.active && e.type_ == EntityType::Train)
+    {
+        if let Some(head) = train.consist.as_ref().and_then(|c| c.head()) {
+            let ahead = head.pos + head.direction * 2;
+            let pos = if state.stage.in_bounds(ahead) && !blocks_sight(&state.stage, ahead) {
+                ahead
+            } else {
+                head.pos
+            };
+            sources.push(LightSource {
+                pos,
+                radius: 7,
+                color: HEADLIGHT_COLOR,
+            });
+        }
+    }
+    sources
+}
+
+/// Recomputes the light map and what the player can see, and adds it to what they've explored.
+pub fn step_lighting(state: &mut State) {
+    const LIT_THRESHOLD: f32 = 0.2;
+
+    let width = state.stage.get_width();
+    let height = state.stage.get_height();
+    let mut lighting = Lighting::new(width, height);
+    if state.lighting.explored.len() == width {
+        lighting.explored = std::mem::take(&mut state.lighting.explored);
+    }
+
+    // which source last lit each tile, so the octant edges only get lit once per source
+    let mut lit_by = vec![vec![usize::MAX; height]; width];
+    for (index, source) in collect_light_sources(state).into_iter().enumerate() {
+        let radius = source.radius as f32;
+        shadowcast(&state.stage, source.pos, source.radius, &mut |pos| {
+            let (x, y) = (pos.x as usize, pos.y as usize);
+            if lit_by[x][y] == index {
+                return;
+            }
+            lit_by[x][y] = index;
+            let distance = (pos - source.pos).as_vec2().length();
+            let intensity = (1.0 - distance / (radius + 1.0)).max(0.0);
+            let light = &mut lighting.light[x][y];
+            *light = (*light + source.color * intensity).min(Vec3::ONE);
+        });
+    }
+
+    let player_pos = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .map(|player| player.pos.as_ivec2());
+    if let Some(player_pos) = player_pos {
+        let view_tiles = view_distance(state) / TILE_SIZE;
+        shadowcast(&state.stage, player_pos, SIGHT_RADIUS, &mut |pos| {
+            let (x, y) = (pos.x as usize, pos.y as usize);
+            lighting.in_sight[x][y] = true;
+            let distance = (pos - player_pos).as_vec2().length();
+            let visible =
+                distance <= view_tiles || lighting.light[x][y].max_element() >= LIT_THRESHOLD;
+            lighting.visible[x][y] = visible;
+            lighting.explored[x][y] |= visible;
+            if visible {
+                lighting.visible_tiles.push(pos);
+            }
+        });
+    }
+
+    // what just went out of view and what came into it changes on the map
+    let map_dirty = &mut state.stage.map_dirty;
+    map_dirty.extend_from_slice(&state.lighting.visible_tiles);
+    map_dirty.extend_from_slice(&lighting.visible_tiles);
+    state.lighting = lighting;
+}
@@ -0,0 +1,140 @@
+/* Loot.
+   What gets left behind when something dies or breaks. Every kind of zombie and chicken, and
+   the tiles worth breaking, has a table in a data file. A table is rolled some number of times,
+   each roll picking one drop by weight (a drop with no item is a roll that comes up empty) and
+   a count in its range. Whatever comes out lands on the ground where it died, spilling onto
+   the tiles around it once the pile there is full. Zombies are the exception, they keep it on
+   them and leave a body to search. Crates in the ruins are filled from a table too.
+*/
+
+use std::collections::HashMap;
+
+use glam::IVec2;
+use rand::random_range;
+use serde::Deserialize;
+
+use crate::{
+    audio::Audio,
+    ground::drop_item,
+    item::{Item, ItemType},
+    state::State,
+    tile::Tile,
+};
+
+pub const LOOT_PATH: &str = "assets/data/loot.toml";
+
+/// Who or what a loot table belongs to. These are the table names in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LootSource {
+    Zombie,
+    Runner,
+    Brute,
+    Chick,
+    Hen,
+    Rooster,
+    Wall,
+    Door,
+    /// Rolled once per crate to fill it, see `containers`.
+    Crate,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LootDrop {
+    /// None for a roll that drops nothing.
+    pub item: Option<ItemType>,
+    pub weight: u32,
+    #[serde(default = "one")]
+    pub min: u32,
+    #[serde(default = "one")]
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LootTable {
+    #[serde(default = "one")]
+    pub rolls: u32,
+    pub drops: Vec<LootDrop>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl LootTable {
+    /// Rolls the table, one item stack per roll that comes up with something.
+    pub fn roll(&self) -> Vec<Item> {
+        let total_weight: u32 = self.drops.iter().map(|drop| drop.weight).sum();
+        if total_weight == 0 {
+            return Vec::new();
+        }
+        let mut items = Vec::new();
+        for _ in 0..self.rolls {
+            // walk the drops until the pick falls inside one's weight
+            let mut pick = random_range(0..total_weight);
+            let mut picked = None;
+            for drop in &self.drops {
+                if pick < drop.weight {
+                    picked = Some(drop);
+                    break;
+                }
+                pick -= drop.weight;
+            }
+            let drop = match picked {
+                Some(drop) => drop,
+                None => continue,
+            };
+            if let Some(item_type) = drop.item {
+                let count = random_range(drop.min..=drop.max.max(drop.min));
+                if count > 0 {
+                    items.push(Item::new_stack(item_type, count));
+                }
+            }
+        }
+        items
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LootTables {
+    tables: HashMap<LootSource, LootTable>,
+}
+
+impl LootTables {
+    pub fn new() -> LootTables {
+        LootTables {
+            tables: HashMap::new(),
+        }
+    }
+
+    pub fn load() -> Result<LootTables, String> {
+        let text = std::fs::read_to_string(LOOT_PATH)
+            .map_err(|e| format!("couldn't read {}: {}", LOOT_PATH, e))?;
+        let tables =
+            toml::from_str(&text).map_err(|e| format!("couldn't parse {}: {}", LOOT_PATH, e))?;
+        Ok(LootTables { tables })
+    }
+
+    /// Rolls a source's table. Sources without one drop nothing.
+    pub fn roll(&self, source: LootSource) -> Vec<Item> {
+        self.tables
+            .get(&source)
+            .map_or(Vec::new(), |table| table.roll())
+    }
+}
+
+/// Which table a tile drops from when it breaks, if any.
+pub fn tile_loot_source(tile: Tile) -> Option<LootSource> {
+    match tile {
+        Tile::Wall => Some(LootSource::Wall),
+        Tile::Door => Some(LootSource::Door),
+        _ => None,
+    }
+}
+
+/// Rolls a source's table and drops the lot around a tile.
+pub fn drop_loot(state: &mut State, audio: &mut Audio, source: LootSource, tile_pos: IVec2) {
+    for item in state.loot.roll(source) {
+        drop_item(state, audio, item, tile_pos);
+    }
+}
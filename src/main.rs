@@ -1,28 +1,49 @@
 mod audio;
+mod autotile;
+mod backpack;
+mod containers;
+mod crafting;
+mod damage;
+mod day_night;
 mod entity;
 mod entity_behavior;
 mod entity_manager;
 mod entity_templates;
+mod equipment;
 mod graphics;
 mod grid;
+mod ground;
 mod inputs;
 mod inventory;
+mod inventory_mouse;
 mod item;
 mod item_use;
+mod lighting;
+mod loot;
 mod particle;
 mod particle_templates;
+mod physics;
+mod rail;
 mod render;
 mod render_entities;
+mod render_map;
 mod render_primitives;
 mod render_tiles;
 mod render_ui;
+mod save;
 mod settings;
+mod shop;
 mod sprite;
 mod stage;
+mod stage_gen;
 mod state;
+mod status;
 mod step;
 mod tile;
+mod train;
 mod utils;
+mod waves;
+mod weather;
 
 use raylib::{audio::RaylibAudio, ffi::SetTraceLogLevel, prelude::TraceLogLevel};
 use render::render;
@@ -67,6 +88,20 @@ fn main() {
     ////////////////        MAIN LOOP        ////////////////
     let mut state = state::State::new();
     state.running = true;
+    state.crafting = match crafting::Crafting::load() {
+        Ok(crafting) => crafting,
+        Err(e) => {
+            println!("Error loading recipes: {}", e);
+            std::process::exit(1);
+        }
+    };
+    state.loot = match loot::LootTables::load() {
+        Ok(loot) => loot,
+        Err(e) => {
+            println!("Error loading loot tables: {}", e);
+            std::process::exit(1);
+        }
+    };
     // DEBUG: this is temporary to auto jump into start
     // state.mode = Mode::Playing;
     let mut render_texture = match rl.load_render_texture(&rlt, graphics.dims.x, graphics.dims.y) {
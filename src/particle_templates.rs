@@ -174,7 +174,8 @@ pub fn spawn_weather_clouds(
     let spawn_y = random_range(top_left_world.y..bottom_right_world.y);
     let spawn_pos = Vec2::new(spawn_x, spawn_y);
 
-    let speed = random_range(MIN_CLOUD_SPEED..=MAX_CLOUD_SPEED);
+    // the wind pushes them along faster
+    let speed = random_range(MIN_CLOUD_SPEED..=MAX_CLOUD_SPEED) * (1.0 + 2.0 * state.weather.wind);
     // Lifetime is the time it takes to cross the screen plus its own width
     let lifetime_in_frames = ((screen_width_world + (MAX_CLOUD_SIZE / 16.0)) / speed) as u32;
 
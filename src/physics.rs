@@ -0,0 +1,190 @@
+/* Knockback and explosions.
+   Entities normally hop from tile to tile, but a hard enough hit sends them sliding: the hit
+   sets `vel`, and every tick they travel along it while friction bleeds it off. They change
+   cells in the spatial grid as their center crosses into the next tile, stop dead against
+   walls and anything impassable (hitting a wall hurts), and snap back to the middle of their
+   tile once they've slowed down. Nobody gets to walk while they're still sliding.
+*/
+
+use glam::{IVec2, Vec2};
+use rand::random_range;
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    damage::{deal_damage, Damage, DamageType},
+    entity::{EntityType, VID},
+    particle::{ParticleData, ParticleLayer},
+    sprite::Sprite,
+    state::State,
+    status::touch_fire,
+    step::TIMESTEP,
+    tile::{damage_tile, is_tile_occupied, tile_shake_area_at},
+};
+
+/// Fraction of its speed a sliding entity keeps each tick.
+const FRICTION: f32 = 0.85;
+/// Tiles per second below which it stops sliding.
+const MIN_SPEED: f32 = 0.5;
+
+/// Shoves an entity in a direction, far enough that it would slide about `distance` tiles if
+/// nothing is in the way. Bigger things go less far.
+pub fn knock_back(state: &mut State, vid: VID, direction: Vec2, distance: f32) {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return;
+    }
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        if entity.marked_for_destruction
+            || !matches!(
+                entity.type_,
+                EntityType::Player | EntityType::Zombie | EntityType::Chicken
+            )
+        {
+            return;
+        }
+        let mass = entity.size.x * entity.size.y;
+        // the slide adds up to speed * TIMESTEP / (1 - FRICTION)
+        let speed = distance * (1.0 - FRICTION) / TIMESTEP / mass;
+        entity.vel += direction * speed;
+    }
+}
+
+pub fn is_sliding(state: &State, vid: VID) -> bool {
+    state
+        .entity_manager
+        .get_entity(vid)
+        .is_some_and(|e| e.vel != Vec2::ZERO)
+}
+
+/// Moves a sliding entity along, handling what it runs into.
+pub fn step_knockback(state: &mut State, audio: &mut Audio, vid: VID) {
+    const IMPACT_DAMAGE_PER_SPEED: f32 = 1.5;
+    const MIN_IMPACT_SPEED: f32 = 4.0;
+
+    let (pos, vel) = match state.entity_manager.get_entity(vid) {
+        Some(entity) if entity.vel != Vec2::ZERO => (entity.pos, entity.vel),
+        _ => return,
+    };
+    let old_tile = pos.as_ivec2();
+    let new_pos = pos + vel * TIMESTEP;
+    let new_tile = new_pos.as_ivec2();
+
+    if new_tile != old_tile {
+        let blocked = !state.stage.in_bounds(new_tile)
+            || !state
+                .stage
+                .is_walkable(new_tile.x as usize, new_tile.y as usize)
+            || is_tile_occupied(state, new_tile);
+        if blocked {
+            settle(state, vid);
+            touch_fire(state, vid, new_tile);
+            let speed = vel.length();
+            if speed >= MIN_IMPACT_SPEED {
+                let impact =
+                    Damage::new((speed * IMPACT_DAMAGE_PER_SPEED) as u32, DamageType::Blunt);
+                deal_damage(state, vid, &impact);
+                if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+                    entity.shake += 0.2;
+                }
+                tile_shake_area_at(state, new_tile, 0.3, 1.0);
+                audio.play_sound_effect(SoundEffect::HitBlock1);
+            }
+            return;
+        }
+        state.move_entity_in_grid(vid, old_tile, new_tile);
+    }
+
+    let mut slowed_down = false;
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        entity.pos = new_pos;
+        entity.vel *= FRICTION;
+        slowed_down = entity.vel.length() < MIN_SPEED;
+    }
+    if slowed_down {
+        settle(state, vid);
+    }
+}
+
+/// Stops an entity and puts it back in the middle of whichever tile it's on.
+fn settle(state: &mut State, vid: VID) {
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        entity.vel = Vec2::ZERO;
+        entity.pos = entity.pos.floor() + Vec2::splat(0.5);
+    }
+}
+
+/// Blows up at `center`, hurting and throwing back everything within `radius` tiles and
+/// breaking what it can of the tiles around it. It all falls off toward the edge.
+pub fn explode(state: &mut State, audio: &mut Audio, center: Vec2, radius: f32, amount: u32) {
+    const MAX_THROW: f32 = 4.0;
+
+    audio.play_sound_effect(SoundEffect::Explosion);
+    let center_tile = center.as_ivec2();
+    tile_shake_area_at(state, center_tile, 2.0, radius * 2.0);
+
+    // --- Entities ---
+    let reach = IVec2::splat(radius.ceil() as i32);
+    let mut caught: Vec<(VID, Vec2)> = Vec::new();
+    for vid in state.get_vids_in_rect(center_tile - reach, center_tile + reach + IVec2::ONE) {
+        // long trains show up once per car
+        if caught.iter().any(|&(other, _)| other == vid) {
+            continue;
+        }
+        if let Some(entity) = state.entity_manager.get_entity(vid) {
+            if entity.active && !entity.marked_for_destruction {
+                caught.push((vid, entity.pos));
+            }
+        }
+    }
+    for (vid, pos) in caught {
+        let offset = pos - center;
+        let falloff = 1.0 - offset.length() / radius;
+        if falloff <= 0.0 {
+            continue;
+        }
+        let mut blast = Damage::new((amount as f32 * falloff) as u32, DamageType::Explosive);
+        blast.variance = 0.2;
+        deal_damage(state, vid, &blast);
+        // anything right on top of it goes flying in a random direction
+        let direction = if offset.length() < 0.1 {
+            Vec2::from_angle(random_range(0.0..std::f32::consts::TAU))
+        } else {
+            offset
+        };
+        knock_back(state, vid, direction, MAX_THROW * falloff);
+    }
+
+    // --- Tiles ---
+    for x in (center_tile.x - reach.x)..=(center_tile.x + reach.x) {
+        for y in (center_tile.y - reach.y)..=(center_tile.y + reach.y) {
+            let tile_pos = IVec2::new(x, y);
+            if !state.stage.in_bounds(tile_pos) {
+                continue;
+            }
+            let falloff = 1.0 - (tile_pos.as_vec2() + Vec2::splat(0.5) - center).length() / radius;
+            if falloff <= 0.0 {
+                continue;
+            }
+            let tile_damage = (amount as f32 * falloff).min(u8::MAX as f32) as u8;
+            damage_tile(
+                state,
+                audio,
+                tile_pos,
+                tile_damage,
+                DamageType::Explosive,
+                center,
+            );
+        }
+    }
+
+    // --- Flash ---
+    state.particles.spawn_static(ParticleData::new(
+        center,
+        Vec2::splat(radius * 32.0),
+        random_range(0.0..360.0),
+        1.0,
+        20,
+        Sprite::Explosion,
+        ParticleLayer::Foreground,
+    ));
+}
@@ -0,0 +1,109 @@
+/* Rail topology.
+   Rails connect through the autotile mask, so a train only ever needs to look at the tile
+   it's on to know where it can go next. Switches pick between branches at junctions.
+*/
+
+use glam::IVec2;
+
+use crate::{
+    autotile::MASK_DIRECTIONS,
+    stage::{Stage, TileLayer},
+    tile::Tile,
+};
+
+pub fn is_rail(tile: Tile) -> bool {
+    matches!(
+        tile,
+        Tile::Rail | Tile::RailSwitch | Tile::Station | Tile::Crossing
+    )
+}
+
+pub fn is_rail_at(stage: &Stage, pos: IVec2) -> bool {
+    stage.in_bounds(pos)
+        && stage
+            .get_layer_tile(TileLayer::Structure, pos.x as usize, pos.y as usize)
+            .is_some_and(|td| is_rail(td.tile))
+}
+
+pub fn is_station_at(stage: &Stage, pos: IVec2) -> bool {
+    stage.in_bounds(pos)
+        && stage
+            .get_layer_tile(TileLayer::Structure, pos.x as usize, pos.y as usize)
+            .is_some_and(|td| td.tile == Tile::Station)
+}
+
+/// True while a crossing's barriers are down.
+pub fn is_crossing_closed(stage: &Stage, pos: IVec2) -> bool {
+    stage.in_bounds(pos)
+        && stage
+            .get_layer_tile(TileLayer::Structure, pos.x as usize, pos.y as usize)
+            .is_some_and(|td| td.tile == Tile::Crossing && td.switched)
+}
+
+/// Every direction the track at `pos` connects to.
+pub fn rail_exits(stage: &Stage, pos: IVec2) -> Vec<IVec2> {
+    if !is_rail_at(stage, pos) {
+        return vec![];
+    }
+    let mask = stage
+        .get_layer_tile(TileLayer::Structure, pos.x as usize, pos.y as usize)
+        .map_or(0, |td| td.mask);
+
+    MASK_DIRECTIONS
+        .iter()
+        .filter(|(bit, _, _)| mask & bit != 0)
+        .map(|&(_, dx, dy)| IVec2::new(dx, dy))
+        .collect()
+}
+
+/// Where a train on `pos` moving along `heading` goes next.
+/// Junctions run straight through when they can; a thrown switch takes the turn instead.
+/// Returns None if the track dead-ends, and keeps the heading at the map edge so trains can leave.
+pub fn next_rail_direction(stage: &Stage, pos: IVec2, heading: IVec2) -> Option<IVec2> {
+    let exits: Vec<IVec2> = rail_exits(stage, pos)
+        .into_iter()
+        .filter(|dir| *dir != -heading)
+        .collect();
+
+    if exits.is_empty() {
+        if heading != IVec2::ZERO && !stage.in_bounds(pos + heading) {
+            return Some(heading);
+        }
+        return None;
+    }
+    if exits.len() == 1 {
+        return Some(exits[0]);
+    }
+
+    // branches in priority order: straight on, then the right turn, then the left
+    let right = IVec2::new(-heading.y, heading.x);
+    let mut branches = Vec::new();
+    for dir in [heading, right, -right] {
+        if exits.contains(&dir) {
+            branches.push(dir);
+        }
+    }
+
+    let switched = stage
+        .get_layer_tile(TileLayer::Structure, pos.x as usize, pos.y as usize)
+        .is_some_and(|td| td.tile == Tile::RailSwitch && td.switched);
+    if switched && branches.len() > 1 {
+        Some(branches[1])
+    } else {
+        branches.first().copied()
+    }
+}
+
+/// Flips a switch. Returns false if there's no switch at `pos`.
+pub fn toggle_switch(stage: &mut Stage, pos: IVec2) -> bool {
+    if !stage.in_bounds(pos) {
+        return false;
+    }
+    match stage.get_layer_tile_mut(TileLayer::Structure, pos.x as usize, pos.y as usize) {
+        Some(td) if td.tile == Tile::RailSwitch => {
+            td.switched = !td.switched;
+            true
+        }
+        _ => false,
+    }
+}
@@ -8,17 +8,25 @@ use rand::random_range;
 use raylib::prelude::*;
 
 use crate::{
+    day_night::scene_tint,
     entity::EntityType,
-    graphics::Graphics,
+    graphics::{Graphics, GRAYSCALE_SHADER},
     particle::{render_parallaxing_particles, render_particles, ParticleLayer},
-    render_entities, render_tiles,
+    render_entities,
+    render_map::{render_full_map, render_minimap, update_map_texture},
+    render_tiles,
     render_ui::{
-        draw_cursor, render_debug_info, render_hand_item, render_health_bar, render_inventory,
-        render_item_below_player, render_item_range_indicator_base,
-        render_item_range_indicator_top, render_selected_item_details,
+        draw_cursor, render_backpack, render_clock, render_container, render_crafting,
+        render_debug_info, render_hand_item, render_health_bar, render_inventory,
+        render_inventory_mouse, render_item_below_player, render_item_range_indicator_base,
+        render_item_range_indicator_top, render_pickup_menu, render_score,
+        render_selected_item_details, render_shop, render_status_effects, render_train_status,
+        render_wave_status,
     },
+    stage::{Stage, TileLayer},
     state::{Mode, State},
-    tile::get_tile_sprite,
+    tile::{get_tile_sprite, Tile},
+    weather::{lightning_flash, visibility_multiplier, weather_tint},
 };
 
 pub const TILE_SIZE: f32 = 16.0;
@@ -76,6 +84,20 @@ pub fn render(
 ) {
     // This is the primary handle for all drawing operations that happen on the final window.
     let mut draw_handle = rl.begin_drawing(rlt);
+
+    // the map has its own texture, which has to be drawn to before the screen texture is
+    if matches!(
+        state.mode,
+        Mode::Playing
+            | Mode::Shop
+            | Mode::Crafting
+            | Mode::Pickup
+            | Mode::Container
+            | Mode::Backpack
+            | Mode::Map
+    ) {
+        update_map_texture(&mut draw_handle, rlt, state, graphics);
+    }
     {
         // We begin a texture mode, which redirects all subsequent drawing commands
         // to our off-screen render texture.
@@ -84,9 +106,34 @@ pub fn render(
 
         match state.mode {
             Mode::Title => render_title(state, graphics, &mut screen),
+            Mode::StagePreview => render_stage_preview(state, graphics, &mut screen),
             Mode::Settings => render_settings_menu(state, graphics, &mut screen),
             Mode::VideoSettings => render_video_settings_menu(state, graphics, &mut screen),
             Mode::Playing => render_playing(state, graphics, &mut screen),
+            Mode::Shop => {
+                render_playing(state, graphics, &mut screen);
+                render_shop(state, graphics, &mut screen);
+            }
+            Mode::Crafting => {
+                render_playing(state, graphics, &mut screen);
+                render_crafting(state, graphics, &mut screen);
+            }
+            Mode::Pickup => {
+                render_playing(state, graphics, &mut screen);
+                render_pickup_menu(state, graphics, &mut screen);
+            }
+            Mode::Container => {
+                render_playing(state, graphics, &mut screen);
+                render_container(state, graphics, &mut screen);
+            }
+            Mode::Backpack => {
+                render_playing(state, graphics, &mut screen);
+                render_backpack(state, graphics, &mut screen);
+            }
+            Mode::Map => {
+                render_playing(state, graphics, &mut screen);
+                render_full_map(state, graphics, &mut screen);
+            }
             Mode::GameOver => render_game_over(state, graphics, &mut screen),
             Mode::Win => render_win(state, graphics, &mut screen),
             // Add other states like StageTransition if they exist in the Mode enum
@@ -129,6 +176,112 @@ pub fn render_title(
         sub_font_size,
         Color::LIGHTGRAY,
     );
+
+    let hint = "P to Preview Stages";
+    let hint_font_size = 16;
+    let hint_text_width = screen.measure_text(hint, hint_font_size);
+    screen.draw_text(
+        hint,
+        (graphics.dims.x / 2) as i32 - (hint_text_width / 2),
+        (graphics.dims.y / 2) as i32 + 50,
+        hint_font_size,
+        Color::GRAY,
+    );
+}
+
+/// Flat color for a tile, for map-style views where sprites would be too small to read.
+pub fn tile_map_color(tile: Tile) -> Color {
+    match tile {
+        Tile::None => PLAY_AREA_BACKGROUND_COLOR,
+        Tile::Grass => Color::new(52, 110, 48, 255),
+        Tile::Wall => Color::new(150, 140, 130, 255),
+        Tile::Ruin => Color::new(95, 85, 75, 255),
+        Tile::Water => Color::new(40, 80, 160, 255),
+        Tile::Rail => Color::new(120, 90, 60, 255),
+        Tile::RailSwitch => Color::new(200, 60, 50, 255),
+        Tile::Station => Color::new(220, 190, 50, 255),
+        Tile::Crossing => Color::new(230, 230, 230, 255),
+        Tile::Door => Color::new(160, 110, 60, 255),
+        Tile::Mud => Color::new(80, 60, 35, 255),
+        Tile::Rock => Color::new(110, 110, 118, 255),
+        Tile::Spikes => Color::new(70, 70, 76, 255),
+        Tile::Torch => Color::new(250, 170, 70, 255),
+        Tile::Campfire => Color::new(240, 110, 40, 255),
+        Tile::Ashes => Color::new(60, 58, 56, 255),
+    }
+}
+
+/// What a spot looks like from above: the structure, else any decal, else the floor.
+pub fn map_tile_type(stage: &Stage, x: usize, y: usize) -> Option<Tile> {
+    let structure = stage.get_layer_tile(TileLayer::Structure, x, y)?.tile;
+    let overlay = stage.get_layer_tile(TileLayer::Overlay, x, y)?.tile;
+    if structure != Tile::None {
+        Some(structure)
+    } else if overlay != Tile::None {
+        Some(overlay)
+    } else {
+        stage.get_tile_type(x, y)
+    }
+}
+
+/// Draws the whole generated stage as a map so seeds can be compared quickly.
+pub fn render_stage_preview(
+    state: &mut State,
+    graphics: &mut Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    screen.clear_background(BACKGROUND_COLOR);
+
+    const MARGIN: i32 = 40;
+    let screen_dims = graphics.dims.as_ivec2();
+    let dims = state.stage.get_dims();
+    let cell = ((screen_dims.y - MARGIN * 2) / dims.y.max(1)).max(1);
+    let origin_x = screen_dims.x / 2 - (dims.x * cell) / 2;
+    let origin_y = MARGIN;
+
+    for x in 0..dims.x {
+        for y in 0..dims.y {
+            if let Some(tile) = map_tile_type(&state.stage, x as usize, y as usize) {
+                screen.draw_rectangle(
+                    origin_x + x * cell,
+                    origin_y + y * cell,
+                    cell,
+                    cell,
+                    tile_map_color(tile),
+                );
+            }
+        }
+    }
+
+    // mark where chickens get penned and where the player starts
+    for coop in &state.stage_layout.coops {
+        screen.draw_rectangle_lines(
+            origin_x + (coop.x - 2) * cell,
+            origin_y + (coop.y - 2) * cell,
+            cell * 5,
+            cell * 5,
+            Color::YELLOW,
+        );
+    }
+    let spawn = state.stage_layout.spawn;
+    screen.draw_circle(
+        origin_x + spawn.x * cell + cell / 2,
+        origin_y + spawn.y * cell + cell / 2,
+        cell as f32,
+        Color::RED,
+    );
+
+    let seed_text = format!("Seed: {}", state.stage.seed);
+    screen.draw_text(&seed_text, 10, 10, 20, Color::WHITE);
+    let hint = "R: Reroll   ENTER: Play   BACKSPACE: Back";
+    let hint_width = screen.measure_text(hint, 16);
+    screen.draw_text(
+        hint,
+        screen_dims.x / 2 - hint_width / 2,
+        screen_dims.y - MARGIN + 12,
+        16,
+        Color::LIGHTGRAY,
+    );
 }
 
 /// get alpha based on player view distance and position
@@ -146,11 +299,36 @@ pub fn get_alpha_from_distance(root: Vec2, target: Vec2, view_distance: f32) ->
 }
 
 pub const VIEW_DISTANCE: f32 = 12.0 * TILE_SIZE;
+
+/// How far the player can see right now. Night closes it in to half.
+pub fn view_distance(state: &State) -> f32 {
+    const NIGHT_SHRINK: f32 = 0.5;
+    let gear_bonus = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .map_or(0.0, |player| player.equipment.stats().view_distance);
+    (VIEW_DISTANCE + gear_bonus * TILE_SIZE)
+        * (1.0 - NIGHT_SHRINK * state.clock.darkness())
+        * visibility_multiplier(&state.weather)
+}
+
+/// Alpha for something at `target` (pixels) as the player sees it. Fades with distance,
+/// light lets the player make things out past the fade, and out of sight is fully hidden.
+pub fn get_alpha_from_visibility(state: &State, root: Vec2, target: Vec2) -> u8 {
+    let tile = (target / TILE_SIZE).floor().as_ivec2();
+    if !state.lighting.is_visible(tile) {
+        return 0;
+    }
+    let falloff = get_alpha_from_distance(root, target, view_distance(state));
+    let lit = (state.lighting.light_level(tile) * 255.0) as u8;
+    falloff.max(lit)
+}
+
 /// wrapper for above that takes in state, and target
 pub fn get_alpha_from_state(state: &State, target: Vec2) -> u8 {
     if let Some(player_vid) = state.player_vid {
         if let Some(player) = state.entity_manager.get_entity(player_vid) {
-            get_alpha_from_distance(player.pos * TILE_SIZE, target, VIEW_DISTANCE)
+            get_alpha_from_visibility(state, player.pos * TILE_SIZE, target)
         } else {
             0 // Player not found, return fully transparent
         }
@@ -198,6 +376,16 @@ pub fn render_playing(
             None
         };
 
+        // what the player remembers goes down in grayscale, what they can see goes over it
+        {
+            let Graphics {
+                shaders,
+                sprite_textures,
+                ..
+            } = &mut *graphics;
+            let mut d = d.begin_shader_mode(&mut shaders[GRAYSCALE_SHADER]);
+            render_tiles::render_explored_tiles(&mut d, state, sprite_textures);
+        }
         render_tiles::render_tiles(&mut d, state, graphics, player_pos_pixels);
 
         render_particles(&mut d, state, graphics, ParticleLayer::Background);
@@ -207,18 +395,55 @@ pub fn render_playing(
         render_entities::render_entities(&mut d, state, graphics, player_pos_pixels);
 
         render_particles(&mut d, state, graphics, ParticleLayer::Foreground);
+
+        // time of day tint over the whole play area, with lights glowing through it
+        let tint = scene_tint(&state.clock);
+        if tint.a > 0 {
+            d.draw_rectangle(
+                0,
+                0,
+                world_width_pixels as i32,
+                world_height_pixels as i32,
+                tint,
+            );
+        }
+        render_tiles::render_light(&mut d, state);
+
+        // fog and rain haze, and the odd lightning flash lighting everything up
+        for overlay in [
+            weather_tint(&state.weather),
+            lightning_flash(&state.weather),
+        ] {
+            if overlay.a > 0 {
+                d.draw_rectangle(
+                    0,
+                    0,
+                    world_width_pixels as i32,
+                    world_height_pixels as i32,
+                    overlay,
+                );
+            }
+        }
+
         render_parallaxing_particles(&mut d, state, graphics);
         render_item_range_indicator_top(&mut d, state, graphics);
         render_hand_item(&mut d, state, graphics);
     }
 
     render_health_bar(state, graphics, screen);
+    render_clock(state, graphics, screen);
+    render_score(state, graphics, screen);
+    render_status_effects(state, graphics, screen);
+    render_train_status(state, graphics, screen);
+    render_wave_status(state, graphics, screen);
+    render_minimap(state, graphics, screen);
     // render_debug_info(state, graphics, screen);
 
     // draw inventory
     render_inventory(state, graphics, screen);
     render_selected_item_details(state, graphics, screen);
     render_item_below_player(state, graphics, screen);
+    render_inventory_mouse(state, graphics, screen);
 }
 
 // --- Stub Functions ---
@@ -239,18 +464,74 @@ pub fn render_video_settings_menu(
     screen.draw_text("VIDEO SETTINGS (STUB)", 20, 20, 30, Color::WHITE);
 }
 pub fn render_game_over(
-    _state: &mut State,
-    _graphics: &mut Graphics,
+    state: &mut State,
+    graphics: &mut Graphics,
     screen: &mut RaylibTextureMode<RaylibDrawHandle>,
 ) {
-    screen.clear_background(Color::MAROON);
-    screen.draw_text("GAME OVER (STUB)", 20, 20, 30, Color::WHITE);
+    screen.clear_background(Color::new(40, 8, 8, 255));
+    render_run_summary(state, graphics, screen, "YOU DIED", Color::RED);
 }
 pub fn render_win(
-    _state: &mut State,
-    _graphics: &mut Graphics,
+    state: &mut State,
+    graphics: &mut Graphics,
     screen: &mut RaylibTextureMode<RaylibDrawHandle>,
 ) {
-    screen.clear_background(Color::GOLD);
-    screen.draw_text("YOU WIN! (STUB)", 20, 20, 30, Color::WHITE);
+    screen.clear_background(Color::new(30, 26, 8, 255));
+    render_run_summary(state, graphics, screen, "YOU SURVIVED", Color::GOLD);
+}
+
+/// Big title over the stats for the run, shared by the game over and win screens.
+fn render_run_summary(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+    title: &str,
+    title_color: Color,
+) {
+    let center_x = (graphics.dims.x / 2) as i32;
+    let center_y = (graphics.dims.y / 2) as i32;
+
+    let font_size = 60;
+    let text_width = screen.measure_text(title, font_size);
+    screen.draw_text(
+        title,
+        center_x - (text_width / 2),
+        center_y - 110,
+        font_size,
+        title_color,
+    );
+
+    let seconds = state.run_stats.time as u32;
+    let lines = [
+        format!(
+            "Waves survived: {} / {}",
+            state.waves.waves_survived(),
+            state.waves.total_waves
+        ),
+        format!("Kills: {}", state.run_stats.kills),
+        format!("Points: {}", state.points),
+        format!("Time: {}:{:02}", seconds / 60, seconds % 60),
+    ];
+    let line_font_size = 22;
+    for (i, line) in lines.iter().enumerate() {
+        let line_width = screen.measure_text(line, line_font_size);
+        screen.draw_text(
+            line,
+            center_x - (line_width / 2),
+            center_y - 20 + i as i32 * (line_font_size + 8),
+            line_font_size,
+            Color::WHITE,
+        );
+    }
+
+    let hint = "Press ENTER to return to the title";
+    let hint_font_size = 16;
+    let hint_width = screen.measure_text(hint, hint_font_size);
+    screen.draw_text(
+        hint,
+        center_x - (hint_width / 2),
+        center_y + 110,
+        hint_font_size,
+        Color::GRAY,
+    );
 }
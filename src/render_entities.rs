@@ -7,11 +7,13 @@ use raylib::{
 };
 
 use crate::{
-    entity::EntityType,
+    entity::{Entity, EntityType},
+    equipment::EquipSlot,
     graphics::Graphics,
-    render::{get_alpha_from_distance, TILE_SIZE, VIEW_DISTANCE},
+    render::{get_alpha_from_visibility, TILE_SIZE},
     state::State,
     tile::get_tile_sprite,
+    train::car_orientation,
 };
 
 /// Renders a health bar above a single non-player entity if it's damaged.
@@ -60,6 +62,164 @@ pub fn render_entity_health_bar(
     );
 }
 
+/// Small icons for an entity's status effects in a row above where its health bar goes. The
+/// player's are drawn bigger in the HUD instead.
+fn render_entity_status_icons(
+    d: &mut RaylibTextureMode<RaylibDrawHandle>,
+    entity: &crate::entity::Entity,
+    graphics: &Graphics,
+    alpha: u8,
+) {
+    const ICON_SCALE: f32 = 0.5;
+    const ICON_SPACING: f32 = 1.0;
+    const Y_OFFSET: f32 = TILE_SIZE * 0.5 + 1.0; // just over the health bar
+
+    if entity.type_ == EntityType::Player || entity.status.effects.is_empty() {
+        return;
+    }
+
+    let icon_size = TILE_SIZE * ICON_SCALE;
+    let count = entity.status.effects.len() as f32;
+    let row_width = count * icon_size + (count - 1.0) * ICON_SPACING;
+    let entity_pixel_pos = entity.pos * TILE_SIZE;
+    let mut x = entity_pixel_pos.x - row_width / 2.0;
+    let y = entity_pixel_pos.y - Y_OFFSET - icon_size;
+    for effect in &entity.status.effects {
+        if let Some(texture) = graphics.get_sprite_texture(effect.kind.icon()) {
+            d.draw_texture_ex(
+                texture,
+                Vector2::new(x, y),
+                0.0,
+                ICON_SCALE,
+                Color::new(255, 255, 255, alpha),
+            );
+        }
+        x += icon_size + ICON_SPACING;
+    }
+}
+
+/// Draws each car of a train consist on its own tile, turned to face the way it's going.
+fn render_train_cars(
+    d: &mut RaylibTextureMode<RaylibDrawHandle>,
+    entity: &crate::entity::Entity,
+    graphics: &Graphics,
+    player_pos_pixels: Option<Vec2>,
+    state: &State,
+) {
+    let consist = match &entity.consist {
+        Some(consist) => consist,
+        None => return,
+    };
+
+    // draw back to front so the head ends up on top
+    for car in consist.cars.iter().rev() {
+        let car_pixel_pos = (car.pos.as_vec2() + Vec2::splat(0.5)) * TILE_SIZE;
+        let alpha = match player_pos_pixels {
+            Some(player_pos) => get_alpha_from_visibility(state, player_pos, car_pixel_pos),
+            None => 255,
+        };
+        if alpha == 0 {
+            continue;
+        }
+
+        if let Some(texture) = graphics.get_sprite_texture(car.sprite) {
+            let (rot, flip) = car_orientation(car.direction);
+            let source_width = if flip {
+                -(texture.width as f32)
+            } else {
+                texture.width as f32
+            };
+            let source_rec = Rectangle::new(0.0, 0.0, source_width, texture.height as f32);
+
+            let position = if entity.shake > 0.0 {
+                let shake_offset = entity.shake * TILE_SIZE;
+                let shake_x = random_range(-shake_offset..shake_offset);
+                let shake_y = random_range(-shake_offset..shake_offset);
+                car_pixel_pos + Vec2::new(shake_x, shake_y)
+            } else {
+                car_pixel_pos
+            };
+
+            let render_size_pixels = entity.size * TILE_SIZE;
+            let dest_rec = Rectangle::new(
+                position.x,
+                position.y,
+                render_size_pixels.x,
+                render_size_pixels.y,
+            );
+            let origin = Vector2::new(render_size_pixels.x / 2.0, render_size_pixels.y / 2.0);
+
+            d.draw_texture_pro(
+                texture,
+                source_rec,
+                dest_rec,
+                origin,
+                rot + entity.rot,
+                Color::new(255, 255, 255, alpha),
+            );
+        }
+
+        // show what the car is hauling
+        if let Some(texture) = car
+            .cargo
+            .and_then(|cargo| cargo.item.sprite)
+            .and_then(|sprite| graphics.get_sprite_texture(sprite))
+        {
+            let source_rec = Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32);
+            let cargo_size = TILE_SIZE * 0.5;
+            let dest_rec = Rectangle::new(car_pixel_pos.x, car_pixel_pos.y, cargo_size, cargo_size);
+            let origin = Vector2::new(cargo_size / 2.0, cargo_size / 2.0);
+            d.draw_texture_pro(
+                texture,
+                source_rec,
+                dest_rec,
+                origin,
+                0.0,
+                Color::new(255, 255, 255, alpha),
+            );
+        }
+    }
+}
+
+/// Draws what an entity is wearing over its sprite, each piece shrunk down where it's worn.
+/// Leans along with the entity by rotating around the same center.
+fn render_worn_equipment(
+    d: &mut RaylibTextureMode<RaylibDrawHandle>,
+    entity: &Entity,
+    graphics: &Graphics,
+    position: Vec2,
+    alpha: u8,
+) {
+    const SCALE: f32 = 0.55;
+    for (slot, item) in entity.equipment.iter() {
+        let texture = match item.sprite.and_then(|s| graphics.get_sprite_texture(s)) {
+            Some(texture) => texture,
+            None => continue,
+        };
+        // offset from the entity's center, in tiles
+        let offset = match slot {
+            EquipSlot::Head => Vec2::new(0.0, -0.35),
+            EquipSlot::Body => Vec2::new(0.0, 0.1),
+            EquipSlot::Hands => Vec2::new(0.35, 0.15),
+            EquipSlot::Offhand => Vec2::new(-0.35, 0.1),
+        } * entity.size
+            * TILE_SIZE;
+        let size = entity.size * TILE_SIZE * SCALE;
+        let source_rec = Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32);
+        let dest_rec = Rectangle::new(position.x, position.y, size.x, size.y);
+        // pivot on the entity's center rather than the piece's own
+        let origin = Vector2::new(size.x / 2.0 - offset.x, size.y / 2.0 - offset.y);
+        d.draw_texture_pro(
+            texture,
+            source_rec,
+            dest_rec,
+            origin,
+            entity.rot,
+            Color::new(255, 255, 255, alpha),
+        );
+    }
+}
+
 /// Iterates through all active entities and renders them and their health bars.
 pub fn render_entities(
     d: &mut RaylibTextureMode<RaylibDrawHandle>,
@@ -73,15 +233,21 @@ pub fn render_entities(
             continue;
         }
 
-        // Player is always fully visible; other entities fade with distance.
+        // Player is always fully visible; other entities fade with distance and hide out of sight.
         let alpha = if entity.type_ == EntityType::Player {
             255
         } else if let Some(player_pos) = player_pos_pixels {
-            get_alpha_from_distance(player_pos, entity.pos * TILE_SIZE, VIEW_DISTANCE)
+            get_alpha_from_visibility(state, player_pos, entity.pos * TILE_SIZE)
         } else {
             255 // If no player, everything is fully visible.
         };
 
+        // Trains draw every car they pull instead of a single sprite.
+        if entity.consist.is_some() {
+            render_train_cars(d, entity, graphics, player_pos_pixels, state);
+            continue;
+        }
+
         // Only draw the entity if it's visible.
         if alpha > 0 {
             let sprite = entity.sprite.unwrap();
@@ -122,9 +288,11 @@ pub fn render_entities(
                     entity.rot,
                     Color::new(255, 255, 255, alpha),
                 );
+                render_worn_equipment(d, entity, graphics, position, alpha);
             }
 
             render_entity_health_bar(d, entity, alpha);
+            render_entity_status_icons(d, entity, graphics, alpha);
         }
     }
 }
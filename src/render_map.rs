@@ -0,0 +1,303 @@
+/* The minimap and the full map.
+   Both draw from one small render texture with a pixel per tile. The stage keeps a list of
+   tiles that might look different on the map, the ones that were changed and the ones that
+   came into or went out of view, and each frame we only work out what those should look like
+   now (their type, and whether they're in view, remembered or unexplored) and redraw the
+   pixels that changed. A new stage redraws the lot. Entities move all the time, so they go on
+   top as dots every frame instead, and only where the player can see them.
+*/
+
+use glam::{IVec2, Vec2};
+use raylib::{
+    color::Color,
+    math::{Rectangle, Vector2},
+    prelude::{
+        RaylibDraw, RaylibDrawHandle, RaylibTextureMode, RaylibTextureModeExt, RaylibThread,
+        RenderTexture2D,
+    },
+};
+
+use crate::{
+    entity::EntityType,
+    graphics::Graphics,
+    render::{map_tile_type, tile_map_color},
+    state::State,
+    tile::Tile,
+    train::occupied_tiles,
+};
+
+/// How much the player knows about a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MapKnowledge {
+    Unexplored,
+    Remembered,
+    InView,
+}
+
+/// The map texture and what each of its pixels was last drawn as.
+pub struct MapTexture {
+    pub texture: RenderTexture2D,
+    pub dims: IVec2,
+    cells: Vec<(Option<Tile>, MapKnowledge)>,
+}
+
+/// Where the full map is looking.
+#[derive(Debug, Clone, Copy)]
+pub struct MapView {
+    /// Tile at the center of the screen.
+    pub pan: Vec2,
+    pub zoom: f32,
+}
+
+impl MapView {
+    pub fn new() -> MapView {
+        MapView {
+            pan: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+}
+
+fn map_cell(state: &State, x: usize, y: usize) -> (Option<Tile>, MapKnowledge) {
+    let pos = IVec2::new(x as i32, y as i32);
+    let knowledge = if state.lighting.is_visible(pos) {
+        MapKnowledge::InView
+    } else if state.lighting.is_explored(pos) {
+        MapKnowledge::Remembered
+    } else {
+        return (None, MapKnowledge::Unexplored);
+    };
+    (map_tile_type(&state.stage, x, y), knowledge)
+}
+
+fn map_cell_color(tile: Option<Tile>, knowledge: MapKnowledge) -> Color {
+    const REMEMBERED_SHADE: f32 = 0.5;
+    let color = match tile {
+        Some(tile) => tile_map_color(tile),
+        None => Color::new(0, 0, 0, 255),
+    };
+    match knowledge {
+        MapKnowledge::Unexplored => Color::new(0, 0, 0, 255),
+        MapKnowledge::InView => color,
+        MapKnowledge::Remembered => Color::new(
+            (color.r as f32 * REMEMBERED_SHADE) as u8,
+            (color.g as f32 * REMEMBERED_SHADE) as u8,
+            (color.b as f32 * REMEMBERED_SHADE) as u8,
+            255,
+        ),
+    }
+}
+
+/// Brings the map texture up to date with the stage, making it first if needed.
+/// Has to run outside any other texture mode, so call it before drawing to the screen texture.
+pub fn update_map_texture(
+    d: &mut RaylibDrawHandle,
+    rlt: &RaylibThread,
+    state: &mut State,
+    graphics: &mut Graphics,
+) {
+    if state.player_vid.is_none() {
+        return;
+    }
+    let dims = state.stage.get_dims();
+    if graphics.map.as_ref().is_none_or(|map| map.dims != dims) {
+        match d.load_render_texture(rlt, dims.x as u32, dims.y as u32) {
+            Ok(texture) => {
+                graphics.map = Some(MapTexture {
+                    texture,
+                    dims,
+                    cells: Vec::new(),
+                })
+            }
+            Err(e) => {
+                println!("Error creating map texture: {}", e);
+                return;
+            }
+        }
+    }
+    let map = match graphics.map.as_mut() {
+        Some(map) => map,
+        None => return,
+    };
+
+    let (width, height) = (dims.x as usize, dims.y as usize);
+    let dirty = std::mem::take(&mut state.stage.map_dirty);
+
+    // a fresh texture or a new stage, it's quicker to go over everything once
+    if map.cells.len() != width * height || dirty.len() >= width * height / 2 {
+        let mut cells = Vec::with_capacity(width * height);
+        for x in 0..width {
+            for y in 0..height {
+                cells.push(map_cell(state, x, y));
+            }
+        }
+        let mut t = d.begin_texture_mode(rlt, &mut map.texture);
+        for x in 0..width {
+            for y in 0..height {
+                let (tile, knowledge) = cells[x * height + y];
+                t.draw_pixel(x as i32, y as i32, map_cell_color(tile, knowledge));
+            }
+        }
+        drop(t);
+        map.cells = cells;
+        return;
+    }
+
+    let mut changed = Vec::new();
+    for pos in dirty {
+        if !state.stage.in_bounds(pos) {
+            continue;
+        }
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        let cell = map_cell(state, x, y);
+        if map.cells[x * height + y] != cell {
+            map.cells[x * height + y] = cell;
+            changed.push((pos, cell));
+        }
+    }
+    if changed.is_empty() {
+        return; // nothing on the map changed
+    }
+
+    let mut t = d.begin_texture_mode(rlt, &mut map.texture);
+    for (pos, (tile, knowledge)) in changed {
+        t.draw_pixel(pos.x, pos.y, map_cell_color(tile, knowledge));
+    }
+}
+
+/// Draws the map texture into `dest` and the entities the player can see on top of it.
+fn draw_map(
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+    state: &State,
+    map: &MapTexture,
+    dest: Rectangle,
+) {
+    // render textures come out upside down
+    let source = Rectangle::new(0.0, 0.0, map.dims.x as f32, -map.dims.y as f32);
+    screen.draw_texture_pro(
+        &map.texture,
+        source,
+        dest,
+        Vector2::new(0.0, 0.0),
+        0.0,
+        Color::WHITE,
+    );
+
+    let scale = dest.width / map.dims.x as f32;
+    let dot_size = scale.max(2.0);
+    for entity in state.entity_manager.iter() {
+        if !entity.active || entity.marked_for_destruction {
+            continue;
+        }
+        let color = match entity.type_ {
+            EntityType::Player => continue, // drawn last so it's never covered
+            EntityType::Zombie => Color::RED,
+            EntityType::Chicken => Color::YELLOW,
+            EntityType::Train => Color::ORANGE,
+            EntityType::Item => Color::SKYBLUE,
+            EntityType::Container => Color::BROWN,
+            _ => continue,
+        };
+        for tile in occupied_tiles(entity) {
+            if !state.lighting.is_visible(tile) {
+                continue;
+            }
+            screen.draw_rectangle_rec(
+                Rectangle::new(
+                    dest.x + tile.x as f32 * scale,
+                    dest.y + tile.y as f32 * scale,
+                    dot_size,
+                    dot_size,
+                ),
+                color,
+            );
+        }
+    }
+
+    if let Some(player) = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+    {
+        let center = Vec2::new(dest.x, dest.y) + player.pos * scale;
+        screen.draw_circle(
+            center.x as i32,
+            center.y as i32,
+            dot_size * 1.5,
+            Color::WHITE,
+        );
+    }
+}
+
+/// Small map in the top right corner, under the train status.
+pub fn render_minimap(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const PIXELS_PER_TILE: f32 = 2.0;
+    const MARGIN: f32 = 10.0;
+    const TOP: f32 = 40.0;
+
+    let map = match graphics.map.as_ref() {
+        Some(map) => map,
+        None => return,
+    };
+    let size = map.dims.as_vec2() * PIXELS_PER_TILE;
+    let dest = Rectangle::new(
+        graphics.dims.x as f32 - size.x - MARGIN,
+        TOP,
+        size.x,
+        size.y,
+    );
+
+    screen.draw_rectangle_rec(
+        Rectangle::new(dest.x - 2.0, dest.y - 2.0, size.x + 4.0, size.y + 4.0),
+        Color::new(0, 0, 0, 200),
+    );
+    draw_map(screen, state, map, dest);
+    screen.draw_rectangle_lines_ex(dest, 1.0, Color::new(200, 200, 200, 160));
+}
+
+/// The whole stage over the game, panned and zoomed with `state.map_view`.
+pub fn render_full_map(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const MARGIN: f32 = 40.0;
+
+    let screen_dims = graphics.dims.as_vec2();
+    screen.draw_rectangle(
+        0,
+        0,
+        screen_dims.x as i32,
+        screen_dims.y as i32,
+        Color::new(0, 0, 0, 220),
+    );
+
+    let map = match graphics.map.as_ref() {
+        Some(map) => map,
+        None => return,
+    };
+    // at zoom 1 the whole stage fits on screen
+    let fit = (screen_dims - Vec2::splat(MARGIN * 2.0)) / map.dims.as_vec2();
+    let scale = fit.x.min(fit.y) * state.map_view.zoom;
+    let top_left = screen_dims / 2.0 - state.map_view.pan * scale;
+    let dest = Rectangle::new(
+        top_left.x,
+        top_left.y,
+        map.dims.x as f32 * scale,
+        map.dims.y as f32 * scale,
+    );
+    draw_map(screen, state, map, dest);
+
+    let hint = "WASD: Pan   Wheel / - / =: Zoom   M: Close";
+    let hint_width = screen.measure_text(hint, 16);
+    screen.draw_text(
+        hint,
+        (screen_dims.x as i32 - hint_width) / 2,
+        screen_dims.y as i32 - 28,
+        16,
+        Color::LIGHTGRAY,
+    );
+}
@@ -1,16 +1,20 @@
-use glam::Vec2;
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec2};
 use rand::random_range;
 use raylib::{
     color::Color,
     math::{Rectangle, Vector2},
-    prelude::{RaylibDraw, RaylibDrawHandle, RaylibMode2D, RaylibTextureMode},
+    prelude::{RaylibDraw, RaylibDrawHandle, RaylibMode2D, RaylibTextureMode, Texture2D},
 };
 
 use crate::{
+    autotile::get_autotile_layers,
     graphics::Graphics,
-    render::{TILE_SIZE, VIEW_DISTANCE},
+    render::{view_distance, TILE_SIZE},
+    sprite::Sprite,
+    stage::TILE_LAYERS,
     state::State,
-    tile::get_tile_sprite,
 };
 
 /// Renders the health bar for a single tile if it's damaged.
@@ -64,75 +68,166 @@ pub fn render_tiles(
     graphics: &Graphics,
     player_pos_pixels: Option<Vec2>,
 ) {
+    let max_steps = (view_distance(state) / TILE_SIZE) as u32;
     for y in 0..state.stage.get_height() {
-        'row: for x in 0..state.stage.get_width() {
-            if let Some(tile_data) = state.stage.get_tile(x, y) {
-                let tile_pixel_pos = Vec2::new(x as f32, y as f32) * TILE_SIZE;
+        for x in 0..state.stage.get_width() {
+            let tile_pixel_pos = Vec2::new(x as f32, y as f32) * TILE_SIZE;
+
+            // Calculate alpha based on distance from player for a fog-of-war effect.
+            let alpha = if let Some(player_pos) = player_pos_pixels {
+                let pos = IVec2::new(x as i32, y as i32);
+                if !state.lighting.is_visible(pos) {
+                    continue; // out of sight, the explored memory covers it if anything does
+                }
+
+                let distance = (tile_pixel_pos - player_pos).length();
+                let tile_distance = (distance / TILE_SIZE).floor() as u32;
 
-                let sprite = match get_tile_sprite(&tile_data) {
-                    Some(s) => s,
-                    None => continue 'row, // Skip if tile has no sprite (e.g., Tile::None)
+                let falloff = if tile_distance >= max_steps {
+                    0
+                } else {
+                    // Alpha falls off linearly from 255 to 0 based on distance.
+                    (((max_steps - tile_distance) as f32 / max_steps as f32) * 255.0) as u8
                 };
 
-                if let Some(texture) = graphics.get_sprite_texture(sprite) {
-                    // Calculate alpha based on distance from player for a fog-of-war effect.
-                    let alpha = if let Some(player_pos) = player_pos_pixels {
-                        let distance = (tile_pixel_pos - player_pos).length();
-                        let tile_distance = (distance / TILE_SIZE).floor() as u32;
-                        let max_steps = (VIEW_DISTANCE / TILE_SIZE) as u32;
-
-                        if tile_distance >= max_steps {
-                            0
-                        } else {
-                            // Alpha falls off linearly from 255 to 0 based on distance.
-                            (((max_steps - tile_distance) as f32 / max_steps as f32) * 255.0) as u8
-                        }
-                    } else {
-                        255 // If there's no player, everything is fully visible.
-                    };
-
-                    // Only draw the tile and its health bar if it's visible at all.
-                    if alpha > 0 {
-                        // --- NEW: Apply shake effect ---
-                        let final_pixel_pos = if tile_data.shake > 0.0 {
-                            let shake_offset = tile_data.shake * TILE_SIZE * 0.1; // Make tile shake less intense
-                            let shake_x = random_range(-shake_offset..shake_offset);
-                            let shake_y = random_range(-shake_offset..shake_offset);
-                            tile_pixel_pos + Vec2::new(shake_x, shake_y)
-                        } else {
-                            tile_pixel_pos
-                        };
-
-                        let source_rec =
-                            Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32);
-
-                        // The destination rectangle's x/y should be the *center* of the tile for rotation.
-                        // Use the final, shaken position here.
-                        let dest_rec = Rectangle::new(
-                            final_pixel_pos.x + (TILE_SIZE / 2.0),
-                            final_pixel_pos.y + (TILE_SIZE / 2.0),
-                            TILE_SIZE,
-                            TILE_SIZE,
-                        );
-
-                        // The origin for rotation is the center of the sprite itself.
-                        let origin = Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
-
-                        d.draw_texture_pro(
-                            texture,
-                            source_rec,
-                            dest_rec,
-                            origin,
-                            tile_data.rot, // Use the rotation from tile_data
-                            Color::new(255, 255, 255, alpha),
-                        );
-
-                        // Call the dedicated function to render the health bar.
-                        // Pass the final, shaken position so the bar moves with the tile.
-                        render_tile_health_bar(d, &tile_data, final_pixel_pos, alpha);
-                    }
-                }
+                // lit tiles show up past the falloff
+                let lit = (state.lighting.light_level(pos) * 255.0) as u8;
+                falloff.max(lit)
+            } else {
+                255 // If there's no player, everything is fully visible.
+            };
+
+            // Only draw the tile and its health bar if it's visible at all.
+            if alpha == 0 {
+                continue;
+            }
+
+            render_tile_layers(
+                d,
+                state,
+                &graphics.sprite_textures,
+                x,
+                y,
+                Color::new(255, 255, 255, alpha),
+                true,
+            );
+        }
+    }
+}
+
+/// Draws every explored tile as the player remembers it. Meant to go under `render_tiles` with
+/// the grayscale shader on, so the edge of sight fades from color into memory.
+pub fn render_explored_tiles(
+    d: &mut RaylibTextureMode<RaylibDrawHandle>,
+    state: &State,
+    sprite_textures: &HashMap<Sprite, Texture2D>,
+) {
+    const MEMORY_TINT: Color = Color::new(110, 110, 120, 255);
+
+    if state.player_vid.is_none() {
+        return; // nobody to remember anything, render_tiles shows it all
+    }
+    for y in 0..state.stage.get_height() {
+        for x in 0..state.stage.get_width() {
+            if state.lighting.explored[x][y] {
+                render_tile_layers(d, state, sprite_textures, x, y, MEMORY_TINT, false);
+            }
+        }
+    }
+}
+
+/// Draws the floor, structure and overlay of one tile, shaken if it's been hit.
+fn render_tile_layers(
+    d: &mut RaylibTextureMode<RaylibDrawHandle>,
+    state: &State,
+    sprite_textures: &HashMap<Sprite, Texture2D>,
+    x: usize,
+    y: usize,
+    tint: Color,
+    health_bars: bool,
+) {
+    let tile_pixel_pos = Vec2::new(x as f32, y as f32) * TILE_SIZE;
+
+    // Floor first, then whatever is built on it, then decals on top.
+    for layer in TILE_LAYERS {
+        let tile_data = match state.stage.get_layer_tile(layer, x, y) {
+            Some(td) => td,
+            None => continue,
+        };
+
+        let pieces = get_autotile_layers(&tile_data);
+        if pieces.is_empty() {
+            continue; // Skip if tile has no sprite (e.g., Tile::None)
+        }
+
+        // --- NEW: Apply shake effect ---
+        let final_pixel_pos = if tile_data.shake > 0.0 {
+            let shake_offset = tile_data.shake * TILE_SIZE * 0.1; // Make tile shake less intense
+            let shake_x = random_range(-shake_offset..shake_offset);
+            let shake_y = random_range(-shake_offset..shake_offset);
+            tile_pixel_pos + Vec2::new(shake_x, shake_y)
+        } else {
+            tile_pixel_pos
+        };
+
+        // The destination rectangle's x/y should be the *center* of the tile for rotation.
+        // Use the final, shaken position here.
+        let dest_rec = Rectangle::new(
+            final_pixel_pos.x + (TILE_SIZE / 2.0),
+            final_pixel_pos.y + (TILE_SIZE / 2.0),
+            TILE_SIZE,
+            TILE_SIZE,
+        );
+
+        // The origin for rotation is the center of the sprite itself.
+        let origin = Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
+
+        // Autotiled pieces stack, e.g. wall edges on top of the wall itself.
+        for (sprite, rot) in pieces {
+            if let Some(texture) = sprite_textures.get(&sprite) {
+                let source_rec =
+                    Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32);
+
+                d.draw_texture_pro(texture, source_rec, dest_rec, origin, rot, tint);
+            }
+        }
+
+        // Call the dedicated function to render the health bar.
+        // Pass the final, shaken position so the bar moves with the tile.
+        if health_bars {
+            render_tile_health_bar(d, &tile_data, final_pixel_pos, tint.a);
+        }
+    }
+}
+
+/// Colored glow from light sources over the time of day tint. Only shows once it gets dark.
+pub fn render_light(d: &mut RaylibMode2D<RaylibTextureMode<RaylibDrawHandle>>, state: &State) {
+    const MAX_GLOW_ALPHA: f32 = 110.0;
+
+    let darkness = state.clock.darkness();
+    if darkness <= 0.0 {
+        return;
+    }
+    for x in 0..state.stage.get_width() {
+        for y in 0..state.stage.get_height() {
+            let light = state.lighting.light[x][y];
+            let level = light.max_element();
+            if level <= 0.0 || !state.lighting.in_sight[x][y] {
+                continue;
             }
+            let color = Color::new(
+                (light.x * 255.0) as u8,
+                (light.y * 255.0) as u8,
+                (light.z * 255.0) as u8,
+                (level * darkness * MAX_GLOW_ALPHA) as u8,
+            );
+            d.draw_rectangle(
+                (x as f32 * TILE_SIZE) as i32,
+                (y as f32 * TILE_SIZE) as i32,
+                TILE_SIZE as i32,
+                TILE_SIZE as i32,
+                color,
+            );
         }
     }
 }
@@ -6,33 +6,63 @@ use raylib::{
 };
 
 use crate::{
-    entity::EntityType,
+    backpack::BACKPACK_COLUMNS,
+    containers::ContainerPane,
+    crafting::{crafting_entries, has_ingredients},
+    entity::{Entity, EntityType},
+    equipment::{equip_info, EQUIP_SLOTS},
     graphics::Graphics,
+    ground::ground_items,
+    inventory::Inventory,
     item::Item,
     render::TILE_SIZE,
     render_primitives::{
         draw_manhattan_range_fill, draw_manhattan_range_outline, draw_manhattan_ring_fill,
         draw_manhattan_ring_outline,
     },
+    shop::{shop_entries, ShopGoods},
     sprite::Sprite,
     state::State,
     utils::new_york_dist,
+    waves::WavePhase,
 };
 
+// hotbar layout, shared by the drawing and the mouse hit test
+const HOTBAR_START_X: f32 = 40.0; // Pushed right to make space for hotkeys
+const HOTBAR_START_Y: f32 = 120.0;
+const HOTBAR_SLOT_WIDTH: f32 = 200.0;
+const HOTBAR_SLOT_HEIGHT: f32 = 30.0;
+const HOTBAR_SLOT_SPACING: f32 = 35.0;
+const HOTBAR_SELECTION_OFFSET_X: f32 = 25.0;
+
+/// Where a hotbar slot's left edge and vertical center are drawn.
+fn hotbar_slot_pos(index: usize, is_selected: bool) -> Vec2 {
+    let x = if is_selected {
+        HOTBAR_START_X + HOTBAR_SELECTION_OFFSET_X
+    } else {
+        HOTBAR_START_X
+    };
+    Vec2::new(x, HOTBAR_START_Y + index as f32 * HOTBAR_SLOT_SPACING)
+}
+
+/// The hotbar slot under a point on the render texture, if any. Ignores the slight tilt.
+pub fn hotbar_slot_at(inventory: &Inventory, pos: Vec2) -> Option<usize> {
+    (0..inventory.hotbar_len()).find(|&i| {
+        let slot_pos = hotbar_slot_pos(i, i == inventory.selected_index);
+        pos.x >= slot_pos.x
+            && pos.x <= slot_pos.x + HOTBAR_SLOT_WIDTH
+            && (pos.y - slot_pos.y).abs() <= HOTBAR_SLOT_HEIGHT / 2.0
+    })
+}
+
 pub fn render_inventory(
     state: &State,
     graphics: &Graphics,
     screen: &mut RaylibTextureMode<RaylibDrawHandle>,
 ) {
     // --- UI Layout & Style Constants ---
-    const START_X: f32 = 40.0; // Pushed right to make space for hotkeys
-    const START_Y: f32 = 120.0;
-    const SLOT_WIDTH: f32 = 200.0;
-    const SLOT_HEIGHT: f32 = 30.0;
-    const SLOT_SPACING: f32 = 35.0;
-    const SELECTION_OFFSET_X: f32 = 25.0;
     const ICON_SIZE: f32 = 24.0;
-    const ICON_PADDING: f32 = (SLOT_HEIGHT - ICON_SIZE) / 2.0;
+    const ICON_PADDING: f32 = (HOTBAR_SLOT_HEIGHT - ICON_SIZE) / 2.0;
     const FONT_SIZE: i32 = 20;
 
     const BASE_ANGLE: f32 = -2.0;
@@ -41,6 +71,12 @@ pub fn render_inventory(
     const BG_COLOR: Color = Color::new(10, 10, 10, 180);
     const ITEM_TEXT_COLOR: Color = Color::WHITE;
     const HOTKEY_COLOR: Color = Color::new(150, 150, 150, 200);
+    const FAVORITE_COLOR: Color = Color::new(230, 190, 60, 220);
+    const FAVORITE_MARK_WIDTH: f32 = 4.0;
+    const DURABILITY_BAR_HEIGHT: f32 = 3.0;
+    const DURABILITY_HIGH_COLOR: Color = Color::new(90, 200, 90, 220);
+    const DURABILITY_MID_COLOR: Color = Color::new(220, 190, 60, 220);
+    const DURABILITY_LOW_COLOR: Color = Color::new(220, 60, 50, 220);
 
     if let Some(player_vid) = state.player_vid {
         if let Some(player) = state.entity_manager.get_entity(player_vid) {
@@ -51,37 +87,45 @@ pub fn render_inventory(
                 .map(|e| (e.index, e))
                 .collect();
 
-            // Always loop up to MAX_SLOTS to draw all 10 slots
-            for i in 0..crate::inventory::MAX_SLOTS {
+            // Always loop over every hotbar slot, empty or not. The rest is in the backpack.
+            for i in 0..player.inventory.hotbar_len() {
                 let is_selected = i == player.inventory.selected_index;
-                let y_pos = START_Y + (i as f32 * SLOT_SPACING);
+                let slot_pos = hotbar_slot_pos(i, is_selected);
+                let y_pos = slot_pos.y;
 
                 // --- 1. Draw Hotkey Number ---
-                // Map index 9 to "0" for the 10th slot, otherwise it's index + 1
-                let hotkey_text = if i == 9 {
-                    "0".to_string()
-                } else {
-                    (i + 1).to_string()
-                };
+                // Map index 9 to "0" for the 10th slot, otherwise it's index + 1.
+                let hotkey_text = ((i + 1) % 10).to_string();
                 screen.draw_text(
                     &hotkey_text,
-                    (START_X - 20.0) as i32,
+                    (HOTBAR_START_X - 20.0) as i32,
                     (y_pos - 10.0) as i32,
                     FONT_SIZE,
                     HOTKEY_COLOR,
                 );
 
                 // --- 2. Calculate position and angle ---
-                let (x_pos, angle) = if is_selected {
-                    (START_X + SELECTION_OFFSET_X, SELECTED_ANGLE)
+                let x_pos = slot_pos.x;
+                let angle = if is_selected {
+                    SELECTED_ANGLE
                 } else {
-                    (START_X, BASE_ANGLE)
+                    BASE_ANGLE
                 };
 
                 // --- 3. Draw Angled Background ---
-                let bg_rect = Rectangle::new(x_pos, y_pos, SLOT_WIDTH, SLOT_HEIGHT);
-                let origin = Vector2::new(0.0, SLOT_HEIGHT / 2.0); // Rotate from left-center
+                let bg_rect = Rectangle::new(x_pos, y_pos, HOTBAR_SLOT_WIDTH, HOTBAR_SLOT_HEIGHT);
+                let origin = Vector2::new(0.0, HOTBAR_SLOT_HEIGHT / 2.0); // Rotate from left-center
                 screen.draw_rectangle_pro(bg_rect, origin, angle, BG_COLOR);
+                if player.inventory.is_favorite(i) {
+                    // a strip down the right edge for slots insert leaves alone
+                    let mark_rect =
+                        Rectangle::new(x_pos, y_pos, FAVORITE_MARK_WIDTH, HOTBAR_SLOT_HEIGHT);
+                    let mark_origin = Vector2::new(
+                        FAVORITE_MARK_WIDTH - HOTBAR_SLOT_WIDTH,
+                        HOTBAR_SLOT_HEIGHT / 2.0,
+                    );
+                    screen.draw_rectangle_pro(mark_rect, mark_origin, angle, FAVORITE_COLOR);
+                }
 
                 // --- 4. Draw Contents (Icon and Text) ---
                 if let Some(entry) = entries.get(&i) {
@@ -120,6 +164,28 @@ pub fn render_inventory(
                         FONT_SIZE,
                         ITEM_TEXT_COLOR,
                     );
+
+                    // Durability bar along the bottom edge, once it's taken some wear
+                    if item.max_durability > 0 && item.durability < item.max_durability {
+                        let fraction = item.durability as f32 / item.max_durability as f32;
+                        let bar_color = if fraction > 0.5 {
+                            DURABILITY_HIGH_COLOR
+                        } else if fraction > 0.25 {
+                            DURABILITY_MID_COLOR
+                        } else {
+                            DURABILITY_LOW_COLOR
+                        };
+                        let bar_rect = Rectangle::new(
+                            x_pos,
+                            y_pos,
+                            HOTBAR_SLOT_WIDTH * fraction,
+                            DURABILITY_BAR_HEIGHT,
+                        );
+                        // same pivot as the slot background, shifted down to its bottom edge
+                        let bar_origin =
+                            Vector2::new(0.0, DURABILITY_BAR_HEIGHT - HOTBAR_SLOT_HEIGHT / 2.0);
+                        screen.draw_rectangle_pro(bar_rect, bar_origin, angle, bar_color);
+                    }
                 }
                 // If the slot is empty, we simply don't draw anything inside it.
             }
@@ -155,14 +221,13 @@ pub fn render_health_bar(
     screen: &mut RaylibTextureMode<RaylibDrawHandle>,
 ) {
     // --- 1. Get Player's Health Percentage ---
-    const MAX_HEALTH: f32 = 100.0;
     let mut health_percentage = 0.0; // Default to 75% for visualization
 
     if let Some(player_vid) = state.player_vid {
         if let Some(player) = state.entity_manager.get_entity(player_vid) {
             // Check if health is > 0 to avoid using the default visualization value
-            if player.health > 0 {
-                health_percentage = (player.health as f32 / MAX_HEALTH).clamp(0.0, 1.0);
+            if player.health > 0 && player.max_hp > 0 {
+                health_percentage = (player.health as f32 / player.max_hp as f32).clamp(0.0, 1.0);
             }
         }
     }
@@ -226,6 +291,101 @@ pub fn render_health_bar(
     }
 }
 
+/// Score and coins, sitting just above the health bar.
+pub fn render_score(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const FONT_SIZE: i32 = 20;
+    const GAP: i32 = 20;
+    // keep in step with the health bar layout
+    let screen_width = graphics.dims.x as f32;
+    let screen_height = graphics.dims.y as f32;
+    let bar_height = 30.0;
+    let x = (screen_width * 0.05) as i32;
+    let y = (screen_height - bar_height * 1.5 - (screen_height * 0.05)) as i32 - FONT_SIZE - 8;
+
+    let score_text = format!("Score: {}", state.points);
+    screen.draw_text(&score_text, x, y, FONT_SIZE, Color::WHITE);
+    let score_width = screen.measure_text(&score_text, FONT_SIZE);
+    screen.draw_text(
+        &format!("Coins: {}", state.coins),
+        x + score_width + GAP,
+        y,
+        FONT_SIZE,
+        Color::GOLD,
+    );
+}
+
+/// Icons for whatever is ailing the player, in a row above the score and health bar.
+pub fn render_status_effects(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const ICON_SIZE: f32 = 16.0;
+    const SCALE: f32 = 2.0;
+    const SPACING: f32 = 8.0;
+    const FONT_SIZE: i32 = 10;
+
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+    {
+        Some(player) => player,
+        None => return,
+    };
+
+    // keep in step with the score and health bar layout
+    let screen_width = graphics.dims.x as f32;
+    let screen_height = graphics.dims.y as f32;
+    let bar_height = 30.0;
+    let score_y = screen_height - bar_height * 1.5 - (screen_height * 0.05) - 20.0 - 8.0;
+    let size = ICON_SIZE * SCALE;
+    let y = score_y - size - 8.0;
+    let mut x = screen_width * 0.05;
+
+    for effect in &player.status.effects {
+        let texture = match graphics.get_sprite_texture(effect.kind.icon()) {
+            Some(texture) => texture,
+            None => continue,
+        };
+        screen.draw_rectangle_rec(
+            Rectangle::new(x - 2.0, y - 2.0, size + 4.0, size + 4.0),
+            Color::new(10, 10, 10, 180),
+        );
+        // flicker when it's about to wear off
+        let tint = if effect.remaining < 1.0 && state.frame % 10 < 5 {
+            Color::new(255, 255, 255, 120)
+        } else {
+            Color::WHITE
+        };
+        screen.draw_texture_ex(texture, Vector2::new(x, y), 0.0, SCALE, tint);
+
+        if effect.stacks > 1 {
+            screen.draw_text(
+                &format!("x{}", effect.stacks),
+                (x + 1.0) as i32,
+                (y + 1.0) as i32,
+                FONT_SIZE,
+                Color::WHITE,
+            );
+        }
+        let seconds = format!("{}", effect.remaining.ceil() as u32);
+        let seconds_width = screen.measure_text(&seconds, FONT_SIZE);
+        screen.draw_text(
+            &seconds,
+            (x + size) as i32 - seconds_width - 1,
+            (y + size) as i32 - FONT_SIZE,
+            FONT_SIZE,
+            Color::WHITE,
+        );
+
+        x += size + SPACING;
+    }
+}
+
 /// Renders a semi-transparent overlay on all tiles within the player's item range.
 pub fn render_item_range_indicator_base(
     d: &mut RaylibTextureMode<RaylibDrawHandle>,
@@ -347,6 +507,49 @@ pub fn render_hand_item(
     }
 }
 
+/// Points in the top right, and when the next train is due or how long the current one is boarding.
+pub fn render_train_status(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const FONT_SIZE: i32 = 20;
+    const MARGIN: i32 = 10;
+    let screen_width = graphics.dims.x as i32;
+
+    if state.stage_layout.rail_row.is_none() {
+        return;
+    }
+
+    // a train waiting at a station takes priority over the timetable
+    let boarding = state
+        .entity_manager
+        .iter()
+        .filter(|e| e.active && e.type_ == EntityType::Train)
+        .filter_map(|e| e.consist.as_ref())
+        .filter(|consist| consist.is_dwelling())
+        .map(|consist| consist.dwell)
+        .reduce(f32::max);
+    let (train_text, color) = match boarding {
+        Some(dwell) => (format!("Train boarding: {:.0}s", dwell.ceil()), Color::GOLD),
+        None => (
+            format!(
+                "Next train: {:.0}s",
+                state.timetable.countdown.max(0.0).ceil()
+            ),
+            Color::LIGHTGRAY,
+        ),
+    };
+    let train_width = screen.measure_text(&train_text, FONT_SIZE);
+    screen.draw_text(
+        &train_text,
+        screen_width - train_width - MARGIN,
+        MARGIN,
+        FONT_SIZE,
+        color,
+    );
+}
+
 pub fn render_debug_info(
     state: &State,
     graphics: &Graphics,
@@ -419,21 +622,77 @@ pub fn render_selected_item_details(
         return;
     }
 
-    // 1. Fetch the item from the player's inventory
-    let selected_item =
-        if let Some(player) = state.entity_manager.get_entity(state.player_vid.unwrap()) {
-            player.inventory.selected_entry().map(|entry| &entry.item) // Get a reference
-        } else {
-            None
-        };
+    // 1. Fetch the player
+    let player = match state.entity_manager.get_entity(state.player_vid.unwrap()) {
+        Some(player) => player,
+        None => return,
+    };
+
+    const PANEL_WIDTH: f32 = 250.0;
+    let x_pos = graphics.dims.x as f32 - PANEL_WIDTH - 30.0;
+    let y_pos = graphics.dims.y as f32 * 0.6;
 
-    // 2. If an item is selected, calculate the position and call the generic function
-    if let Some(item) = selected_item {
-        const PANEL_WIDTH: f32 = 250.0;
-        let x_pos = graphics.dims.x as f32 - PANEL_WIDTH - 30.0;
-        let y_pos = graphics.dims.y as f32 * 0.6;
+    // 2. What's being worn sits just above the panel
+    render_equipment_list(screen, graphics, player, x_pos, y_pos);
 
-        render_item_details_panel(screen, graphics, item, x_pos, y_pos, "Selected");
+    // 3. If an item is selected, call the generic function
+    if let Some(entry) = player.inventory.selected_entry() {
+        render_item_details_panel(screen, graphics, &entry.item, x_pos, y_pos, "Selected");
+    }
+}
+
+/// One row per equipment slot with what's in it, ending just above `bottom_y`.
+fn render_equipment_list(
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+    graphics: &Graphics,
+    player: &Entity,
+    x_pos: f32,
+    bottom_y: f32,
+) {
+    const WIDTH: f32 = 240.0;
+    const ROW_HEIGHT: f32 = 20.0;
+    const PADDING: f32 = 8.0;
+    const FONT_SIZE: i32 = 16;
+    const GAP: f32 = 30.0; // leave room for the details banner
+    const KEY_COLOR: Color = Color::new(150, 150, 150, 255);
+    const EMPTY_COLOR: Color = Color::new(90, 90, 90, 255);
+
+    let rows = EQUIP_SLOTS.len() as f32 + 1.0;
+    let height = rows * ROW_HEIGHT + PADDING * 2.0;
+    let top = bottom_y - GAP - height;
+    screen.draw_rectangle_rec(
+        Rectangle::new(x_pos - 8.0, top, WIDTH, height),
+        Color::new(10, 10, 10, 200),
+    );
+
+    let mut y = top + PADDING;
+    screen.draw_text(
+        "Equipped   (R: take off)",
+        x_pos as i32,
+        y as i32,
+        FONT_SIZE,
+        Color::WHITE,
+    );
+    y += ROW_HEIGHT;
+    for slot in EQUIP_SLOTS {
+        screen.draw_text(slot.name(), x_pos as i32, y as i32, FONT_SIZE, KEY_COLOR);
+        let text_x = x_pos + 70.0;
+        match player.equipment.get(slot) {
+            Some(item) => {
+                if let Some(texture) = item.sprite.and_then(|s| graphics.get_sprite_texture(s)) {
+                    screen.draw_texture(texture, text_x as i32, y as i32 - 1, Color::WHITE);
+                }
+                screen.draw_text(
+                    item.name,
+                    (text_x + 20.0) as i32,
+                    y as i32,
+                    FONT_SIZE,
+                    Color::WHITE,
+                );
+            }
+            None => screen.draw_text("-", text_x as i32, y as i32, FONT_SIZE, EMPTY_COLOR),
+        }
+        y += ROW_HEIGHT;
     }
 }
 
@@ -479,7 +738,14 @@ pub fn render_item_below_player(
                     let x_pos = graphics.dims.x as f32 - PANEL_WIDTH * 2.2 - 30.0;
                     let y_pos = graphics.dims.y as f32 * 0.6;
 
-                    render_item_details_panel(screen, graphics, &item, x_pos, y_pos, "Item Below");
+                    // the first stack in a pile, with a count of the rest
+                    let pile_size = ground_items(state, player_tile_pos).len();
+                    let label = if pile_size > 1 {
+                        format!("Item Below (+{} more)", pile_size - 1)
+                    } else {
+                        "Item Below".to_string()
+                    };
+                    render_item_details_panel(screen, graphics, &item, x_pos, y_pos, &label);
                 }
             }
         }
@@ -648,6 +914,59 @@ pub fn render_item_details_panel(
         );
     }
 
+    current_y = draw_stat_if(
+        screen,
+        item.max_durability > 0,
+        "Durability",
+        &format!("{}/{}", item.durability, item.max_durability),
+        content_x,
+        current_y,
+    );
+
+    // What it does when worn
+    if let Some((slot, stats)) = equip_info(item.type_) {
+        current_y = draw_stat(
+            screen,
+            "Worn On",
+            slot.name(),
+            content_x,
+            current_y,
+            STAT_VALUE_COLOR,
+        );
+        current_y = draw_stat_if(
+            screen,
+            stats.armor > 0,
+            "Armor",
+            &format!("+{}", stats.armor),
+            content_x,
+            current_y,
+        );
+        current_y = draw_stat_if(
+            screen,
+            stats.move_speed != 0.0,
+            "Speed",
+            &format!("{:+}%", (stats.move_speed * 100.0).round()),
+            content_x,
+            current_y,
+        );
+        current_y = draw_stat_if(
+            screen,
+            stats.view_distance > 0.0,
+            "Sight",
+            &format!("+{} tiles", stats.view_distance),
+            content_x,
+            current_y,
+        );
+        current_y = draw_stat_if(
+            screen,
+            stats.damage > 0.0,
+            "Damage",
+            &format!("+{}%", (stats.damage * 100.0).round()),
+            content_x,
+            current_y,
+        );
+    }
+
     current_y += SECTION_SPACING;
 
     // Properties
@@ -762,3 +1081,758 @@ fn draw_stat_if(
         y
     }
 }
+
+/// Wave counter along the top middle, with the build countdown or how many zombies are left.
+pub fn render_wave_status(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const FONT_SIZE: i32 = 20;
+    const MARGIN: i32 = 10;
+    let center_x = graphics.dims.x as i32 / 2;
+
+    let waves = &state.waves;
+    let (text, color) = match waves.phase {
+        WavePhase::Build => (
+            format!(
+                "Wave {}/{} - build: {:.0}s - B to shop",
+                waves.wave,
+                waves.total_waves,
+                waves.build_timer.max(0.0).ceil()
+            ),
+            Color::LIGHTGRAY,
+        ),
+        WavePhase::Combat => (
+            format!(
+                "Wave {}/{} - {} zombies left",
+                waves.wave,
+                waves.total_waves,
+                waves.remaining()
+            ),
+            Color::RED,
+        ),
+    };
+    let text_width = screen.measure_text(&text, FONT_SIZE);
+    screen.draw_text(&text, center_x - text_width / 2, MARGIN, FONT_SIZE, color);
+}
+
+/// The shop panel, drawn over the paused game.
+pub fn render_shop(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const PANEL_WIDTH: f32 = 420.0;
+    const ROW_HEIGHT: f32 = 34.0;
+    const PADDING: f32 = 20.0;
+    const FONT_SIZE: i32 = 20;
+    const TITLE_FONT_SIZE: i32 = 30;
+    const ICON_SIZE: f32 = 16.0;
+
+    const BG_COLOR: Color = Color::new(10, 10, 10, 220);
+    const SELECTED_COLOR: Color = Color::new(80, 70, 20, 220);
+
+    let entries = shop_entries(state);
+    let panel_height = PADDING * 3.0
+        + TITLE_FONT_SIZE as f32
+        + ROW_HEIGHT * entries.len() as f32
+        + FONT_SIZE as f32 * 2.0;
+    let panel_x = (graphics.dims.x as f32 - PANEL_WIDTH) / 2.0;
+    let panel_y = (graphics.dims.y as f32 - panel_height) / 2.0;
+    screen.draw_rectangle_rec(
+        Rectangle::new(panel_x, panel_y, PANEL_WIDTH, panel_height),
+        BG_COLOR,
+    );
+
+    // --- Header ---
+    let text_x = (panel_x + PADDING) as i32;
+    screen.draw_text(
+        "SHOP",
+        text_x,
+        (panel_y + PADDING) as i32,
+        TITLE_FONT_SIZE,
+        Color::WHITE,
+    );
+    let coins_text = format!("Coins: {}", state.coins);
+    let coins_width = screen.measure_text(&coins_text, FONT_SIZE);
+    screen.draw_text(
+        &coins_text,
+        (panel_x + PANEL_WIDTH - PADDING) as i32 - coins_width,
+        (panel_y + PADDING) as i32 + (TITLE_FONT_SIZE - FONT_SIZE) / 2,
+        FONT_SIZE,
+        Color::GOLD,
+    );
+
+    // --- Entries ---
+    let rows_y = panel_y + PADDING * 2.0 + TITLE_FONT_SIZE as f32;
+    for (i, entry) in entries.iter().enumerate() {
+        let row_y = rows_y + i as f32 * ROW_HEIGHT;
+        if i == state.shop.selected {
+            screen.draw_rectangle_rec(
+                Rectangle::new(
+                    panel_x + PADDING / 2.0,
+                    row_y,
+                    PANEL_WIDTH - PADDING,
+                    ROW_HEIGHT,
+                ),
+                SELECTED_COLOR,
+            );
+        }
+
+        let text_y = (row_y + (ROW_HEIGHT - FONT_SIZE as f32) / 2.0) as i32;
+        let mut name_x = text_x;
+        if let ShopGoods::Item(item_type, _) = entry.goods {
+            if let Some(texture) = Item::new(item_type)
+                .sprite
+                .and_then(|sprite| graphics.get_sprite_texture(sprite))
+            {
+                screen.draw_texture(
+                    texture,
+                    text_x,
+                    (row_y + (ROW_HEIGHT - ICON_SIZE) / 2.0) as i32,
+                    Color::WHITE,
+                );
+            }
+        }
+        name_x += ICON_SIZE as i32 + 8;
+
+        let affordable = entry.price.is_some_and(|price| price <= state.coins);
+        let name_color = if affordable {
+            Color::WHITE
+        } else {
+            Color::GRAY
+        };
+        screen.draw_text(entry.name, name_x, text_y, FONT_SIZE, name_color);
+
+        let price_text = match entry.price {
+            Some(price) => format!("{}", price),
+            None => "MAX".to_string(),
+        };
+        let price_width = screen.measure_text(&price_text, FONT_SIZE);
+        screen.draw_text(
+            &price_text,
+            (panel_x + PANEL_WIDTH - PADDING) as i32 - price_width,
+            text_y,
+            FONT_SIZE,
+            if affordable { Color::GOLD } else { Color::GRAY },
+        );
+    }
+
+    // --- Footer ---
+    screen.draw_text(
+        "ENTER to buy, B to close",
+        text_x,
+        (rows_y + ROW_HEIGHT * entries.len() as f32 + PADDING) as i32,
+        FONT_SIZE,
+        Color::LIGHTGRAY,
+    );
+}
+
+/// The recipe list, with repairs for worn gear under it. Rows the player has everything for are
+/// lit up, and each ingredient shows how many are on hand against how many it takes.
+pub fn render_crafting(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const PANEL_WIDTH: f32 = 520.0;
+    const ROW_HEIGHT: f32 = 34.0;
+    const PADDING: f32 = 20.0;
+    const FONT_SIZE: i32 = 20;
+    const TITLE_FONT_SIZE: i32 = 30;
+    const ICON_SIZE: f32 = 16.0;
+    const INGREDIENT_WIDTH: i32 = 64;
+
+    const BG_COLOR: Color = Color::new(10, 10, 10, 220);
+    const SELECTED_COLOR: Color = Color::new(80, 70, 20, 220);
+
+    let inventory = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .map(|player| &player.inventory);
+    let entries = crafting_entries(state);
+    let panel_height = PADDING * 3.0
+        + TITLE_FONT_SIZE as f32
+        + ROW_HEIGHT * entries.len() as f32
+        + FONT_SIZE as f32 * 2.0;
+    let panel_x = (graphics.dims.x as f32 - PANEL_WIDTH) / 2.0;
+    let panel_y = (graphics.dims.y as f32 - panel_height) / 2.0;
+    screen.draw_rectangle_rec(
+        Rectangle::new(panel_x, panel_y, PANEL_WIDTH, panel_height),
+        BG_COLOR,
+    );
+
+    // --- Header ---
+    let text_x = (panel_x + PADDING) as i32;
+    screen.draw_text(
+        "CRAFTING",
+        text_x,
+        (panel_y + PADDING) as i32,
+        TITLE_FONT_SIZE,
+        Color::WHITE,
+    );
+
+    // --- Entries ---
+    let rows_y = panel_y + PADDING * 2.0 + TITLE_FONT_SIZE as f32;
+    for (i, entry) in entries.iter().enumerate() {
+        let row_y = rows_y + i as f32 * ROW_HEIGHT;
+        if i == state.crafting.selected {
+            screen.draw_rectangle_rec(
+                Rectangle::new(
+                    panel_x + PADDING / 2.0,
+                    row_y,
+                    PANEL_WIDTH - PADDING,
+                    ROW_HEIGHT,
+                ),
+                SELECTED_COLOR,
+            );
+        }
+
+        let text_y = (row_y + (ROW_HEIGHT - FONT_SIZE as f32) / 2.0) as i32;
+        let icon_y = (row_y + (ROW_HEIGHT - ICON_SIZE) / 2.0) as i32;
+        if let Some(texture) = Item::new(entry.item)
+            .sprite
+            .and_then(|sprite| graphics.get_sprite_texture(sprite))
+        {
+            screen.draw_texture(texture, text_x, icon_y, Color::WHITE);
+        }
+        let affordable =
+            inventory.is_some_and(|inventory| has_ingredients(inventory, &entry.ingredients));
+        let name_color = if affordable {
+            Color::WHITE
+        } else {
+            Color::GRAY
+        };
+        screen.draw_text(
+            &entry.name,
+            text_x + ICON_SIZE as i32 + 8,
+            text_y,
+            FONT_SIZE,
+            name_color,
+        );
+
+        // ingredients are right aligned, last one against the edge
+        let mut ingredient_x = (panel_x + PANEL_WIDTH - PADDING) as i32
+            - INGREDIENT_WIDTH * entry.ingredients.len() as i32;
+        for ingredient in &entry.ingredients {
+            if let Some(texture) = Item::new(ingredient.item)
+                .sprite
+                .and_then(|sprite| graphics.get_sprite_texture(sprite))
+            {
+                screen.draw_texture(texture, ingredient_x, icon_y, Color::WHITE);
+            }
+            let have = inventory.map_or(0, |inventory| inventory.count_of(ingredient.item));
+            let count_color = if have >= ingredient.count {
+                Color::LIGHTGRAY
+            } else {
+                Color::RED
+            };
+            screen.draw_text(
+                &format!("{}/{}", have, ingredient.count),
+                ingredient_x + ICON_SIZE as i32 + 4,
+                text_y,
+                FONT_SIZE,
+                count_color,
+            );
+            ingredient_x += INGREDIENT_WIDTH;
+        }
+    }
+
+    // --- Footer ---
+    screen.draw_text(
+        "ENTER to craft, C to close",
+        text_x,
+        (rows_y + ROW_HEIGHT * entries.len() as f32 + PADDING) as i32,
+        FONT_SIZE,
+        Color::LIGHTGRAY,
+    );
+}
+
+/// The stacks lying on the tile the pickup menu was opened on, and a row to take all of them.
+pub fn render_pickup_menu(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const PANEL_WIDTH: f32 = 320.0;
+    const ROW_HEIGHT: f32 = 34.0;
+    const PADDING: f32 = 20.0;
+    const FONT_SIZE: i32 = 20;
+    const TITLE_FONT_SIZE: i32 = 30;
+    const ICON_SIZE: f32 = 16.0;
+
+    const BG_COLOR: Color = Color::new(10, 10, 10, 220);
+    const SELECTED_COLOR: Color = Color::new(80, 70, 20, 220);
+
+    let items: Vec<Item> = ground_items(state, state.pickup_menu.tile)
+        .iter()
+        .filter_map(|vid| state.entity_manager.get_entity(*vid))
+        .filter_map(|entity| entity.item)
+        .collect();
+    let row_count = items.len() + 1;
+    let panel_height = PADDING * 3.0
+        + TITLE_FONT_SIZE as f32
+        + ROW_HEIGHT * row_count as f32
+        + FONT_SIZE as f32 * 2.0;
+    let panel_x = (graphics.dims.x as f32 - PANEL_WIDTH) / 2.0;
+    let panel_y = (graphics.dims.y as f32 - panel_height) / 2.0;
+    screen.draw_rectangle_rec(
+        Rectangle::new(panel_x, panel_y, PANEL_WIDTH, panel_height),
+        BG_COLOR,
+    );
+
+    // --- Header ---
+    let text_x = (panel_x + PADDING) as i32;
+    screen.draw_text(
+        "ON THE GROUND",
+        text_x,
+        (panel_y + PADDING) as i32,
+        TITLE_FONT_SIZE,
+        Color::WHITE,
+    );
+
+    // --- Rows ---
+    let rows_y = panel_y + PADDING * 2.0 + TITLE_FONT_SIZE as f32;
+    for i in 0..row_count {
+        let row_y = rows_y + i as f32 * ROW_HEIGHT;
+        if i == state.pickup_menu.selected {
+            screen.draw_rectangle_rec(
+                Rectangle::new(
+                    panel_x + PADDING / 2.0,
+                    row_y,
+                    PANEL_WIDTH - PADDING,
+                    ROW_HEIGHT,
+                ),
+                SELECTED_COLOR,
+            );
+        }
+
+        let text_y = (row_y + (ROW_HEIGHT - FONT_SIZE as f32) / 2.0) as i32;
+        let name_x = text_x + ICON_SIZE as i32 + 8;
+        let item = match items.get(i) {
+            Some(item) => item,
+            None => {
+                screen.draw_text("Take all", name_x, text_y, FONT_SIZE, Color::GOLD);
+                continue;
+            }
+        };
+        if let Some(texture) = item
+            .sprite
+            .and_then(|sprite| graphics.get_sprite_texture(sprite))
+        {
+            screen.draw_texture(
+                texture,
+                text_x,
+                (row_y + (ROW_HEIGHT - ICON_SIZE) / 2.0) as i32,
+                Color::WHITE,
+            );
+        }
+        screen.draw_text(item.name, name_x, text_y, FONT_SIZE, Color::WHITE);
+        if item.count > 1 {
+            let count_text = format!("x{}", item.count);
+            let count_width = screen.measure_text(&count_text, FONT_SIZE);
+            screen.draw_text(
+                &count_text,
+                (panel_x + PANEL_WIDTH - PADDING) as i32 - count_width,
+                text_y,
+                FONT_SIZE,
+                Color::LIGHTGRAY,
+            );
+        }
+    }
+
+    // --- Footer ---
+    screen.draw_text(
+        "ENTER to take, E to close",
+        text_x,
+        (rows_y + ROW_HEIGHT * row_count as f32 + PADDING) as i32,
+        FONT_SIZE,
+        Color::LIGHTGRAY,
+    );
+}
+
+/// Day count and time of day, just under the wave counter.
+pub fn render_clock(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const FONT_SIZE: i32 = 20;
+    const MARGIN: i32 = 10;
+    let center_x = graphics.dims.x as i32 / 2;
+
+    let clock = &state.clock;
+    let text = format!(
+        "Day {} - {:02}:{:02} - {}",
+        clock.day,
+        clock.hour(),
+        clock.minute(),
+        state.weather.kind.name()
+    );
+    let color = if clock.is_night() {
+        Color::SKYBLUE
+    } else {
+        Color::LIGHTGRAY
+    };
+    let text_width = screen.measure_text(&text, FONT_SIZE);
+    screen.draw_text(
+        &text,
+        center_x - text_width / 2,
+        MARGIN + FONT_SIZE + 5,
+        FONT_SIZE,
+        color,
+    );
+}
+
+/// The player's inventory on the left and the container's on the right, a slot per row.
+pub fn render_container(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const PANE_WIDTH: f32 = 280.0;
+    const PANE_GAP: f32 = 20.0;
+    const ROW_HEIGHT: f32 = 30.0;
+    const PADDING: f32 = 20.0;
+    const FONT_SIZE: i32 = 20;
+    const TITLE_FONT_SIZE: i32 = 30;
+    const ICON_SIZE: f32 = 16.0;
+    const VISIBLE_ROWS: usize = 12; // a bigger backpack scrolls
+
+    const BG_COLOR: Color = Color::new(10, 10, 10, 220);
+    const SELECTED_COLOR: Color = Color::new(80, 70, 20, 220);
+    // where the cursor sits on the side it isn't on
+    const IDLE_SELECTED_COLOR: Color = Color::new(45, 40, 20, 220);
+    const FAVORITE_COLOR: Color = Color::new(230, 190, 60, 220);
+
+    let player = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid));
+    let container = state
+        .container_view
+        .vid
+        .and_then(|vid| state.entity_manager.get_entity(vid));
+    let (player, container) = match (player, container) {
+        (Some(player), Some(container)) => (player, container),
+        _ => return,
+    };
+    let title = container.container.map_or("CONTAINER", |kind| kind.name());
+    let panes = [
+        ("YOU", &player.inventory, ContainerPane::Player),
+        (title, &container.inventory, ContainerPane::Container),
+    ];
+
+    let row_count = player
+        .inventory
+        .capacity
+        .max(container.inventory.capacity)
+        .min(VISIBLE_ROWS);
+    let panel_width = PANE_WIDTH * 2.0 + PANE_GAP + PADDING * 2.0;
+    let panel_height = PADDING * 3.0
+        + TITLE_FONT_SIZE as f32
+        + ROW_HEIGHT * row_count as f32
+        + FONT_SIZE as f32 * 2.0;
+    let panel_x = (graphics.dims.x as f32 - panel_width) / 2.0;
+    let panel_y = (graphics.dims.y as f32 - panel_height) / 2.0;
+    screen.draw_rectangle_rec(
+        Rectangle::new(panel_x, panel_y, panel_width, panel_height),
+        BG_COLOR,
+    );
+
+    let rows_y = panel_y + PADDING * 2.0 + TITLE_FONT_SIZE as f32;
+    for (i, (name, inventory, pane)) in panes.iter().enumerate() {
+        let pane_x = panel_x + PADDING + i as f32 * (PANE_WIDTH + PANE_GAP);
+        let is_active = state.container_view.pane == *pane;
+
+        // --- Header ---
+        screen.draw_text(
+            name,
+            pane_x as i32,
+            (panel_y + PADDING) as i32,
+            TITLE_FONT_SIZE,
+            if is_active { Color::WHITE } else { Color::GRAY },
+        );
+
+        // --- Slots ---
+        // scrolled just far enough to keep the cursor in view
+        let first_row = (inventory.selected_index + 1).saturating_sub(VISIBLE_ROWS);
+        for (row, slot) in (first_row..inventory.capacity)
+            .take(VISIBLE_ROWS)
+            .enumerate()
+        {
+            let row_y = rows_y + row as f32 * ROW_HEIGHT;
+            if slot == inventory.selected_index {
+                screen.draw_rectangle_rec(
+                    Rectangle::new(
+                        pane_x - PADDING / 2.0,
+                        row_y,
+                        PANE_WIDTH + PADDING,
+                        ROW_HEIGHT,
+                    ),
+                    if is_active {
+                        SELECTED_COLOR
+                    } else {
+                        IDLE_SELECTED_COLOR
+                    },
+                );
+            }
+            if inventory.is_favorite(slot) {
+                screen.draw_rectangle_rec(
+                    Rectangle::new(pane_x - PADDING / 2.0, row_y, 4.0, ROW_HEIGHT),
+                    FAVORITE_COLOR,
+                );
+            }
+
+            let text_y = (row_y + (ROW_HEIGHT - FONT_SIZE as f32) / 2.0) as i32;
+            let name_x = pane_x as i32 + ICON_SIZE as i32 + 8;
+            let item = match inventory.get(slot) {
+                Some(entry) => &entry.item,
+                None => {
+                    screen.draw_text("-", name_x, text_y, FONT_SIZE, Color::DARKGRAY);
+                    continue;
+                }
+            };
+            if let Some(texture) = item
+                .sprite
+                .and_then(|sprite| graphics.get_sprite_texture(sprite))
+            {
+                screen.draw_texture(
+                    texture,
+                    pane_x as i32,
+                    (row_y + (ROW_HEIGHT - ICON_SIZE) / 2.0) as i32,
+                    Color::WHITE,
+                );
+            }
+            screen.draw_text(item.name, name_x, text_y, FONT_SIZE, Color::WHITE);
+            if item.count > 1 {
+                let count_text = format!("x{}", item.count);
+                let count_width = screen.measure_text(&count_text, FONT_SIZE);
+                screen.draw_text(
+                    &count_text,
+                    (pane_x + PANE_WIDTH) as i32 - count_width,
+                    text_y,
+                    FONT_SIZE,
+                    Color::LIGHTGRAY,
+                );
+            }
+        }
+    }
+
+    // --- Footer ---
+    screen.draw_text(
+        "ENTER to move stack, X one, TAB all, Q matching, F to close",
+        (panel_x + PADDING) as i32,
+        (rows_y + ROW_HEIGHT * row_count as f32 + PADDING) as i32,
+        FONT_SIZE,
+        Color::LIGHTGRAY,
+    );
+}
+
+/// Every slot of the player's inventory as a grid, the hotbar along the top, with what's under
+/// the cursor spelled out below it.
+pub fn render_backpack(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const CELL_SIZE: f32 = 44.0;
+    const CELL_GAP: f32 = 6.0;
+    const HOTBAR_GAP: f32 = 12.0; // sets the hotbar row apart from the rest
+    const PADDING: f32 = 20.0;
+    const FONT_SIZE: i32 = 20;
+    const COUNT_FONT_SIZE: i32 = 10;
+    const TITLE_FONT_SIZE: i32 = 30;
+    const ICON_SIZE: f32 = 16.0;
+
+    const BG_COLOR: Color = Color::new(10, 10, 10, 220);
+    const CELL_COLOR: Color = Color::new(40, 40, 40, 220);
+    const SELECTED_COLOR: Color = Color::new(80, 70, 20, 220);
+    const HELD_COLOR: Color = Color::new(60, 90, 120, 220);
+    const FAVORITE_COLOR: Color = Color::new(230, 190, 60, 220);
+
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+    {
+        Some(player) => player,
+        None => return,
+    };
+    let inventory = &player.inventory;
+    let view = &state.backpack_view;
+
+    let footer = "ENTER to move, F favorite, TAB/R sort, Q stack to chests, I to close";
+    let rows = inventory.capacity.div_ceil(BACKPACK_COLUMNS);
+    let grid_width = BACKPACK_COLUMNS as f32 * (CELL_SIZE + CELL_GAP) - CELL_GAP;
+    let grid_height = rows as f32 * (CELL_SIZE + CELL_GAP) - CELL_GAP + HOTBAR_GAP;
+    let panel_width = grid_width.max(screen.measure_text(footer, FONT_SIZE) as f32) + PADDING * 2.0;
+    let panel_height =
+        PADDING * 4.0 + TITLE_FONT_SIZE as f32 + grid_height + FONT_SIZE as f32 * 3.0;
+    let panel_x = (graphics.dims.x as f32 - panel_width) / 2.0;
+    let panel_y = (graphics.dims.y as f32 - panel_height) / 2.0;
+    screen.draw_rectangle_rec(
+        Rectangle::new(panel_x, panel_y, panel_width, panel_height),
+        BG_COLOR,
+    );
+
+    // --- Title ---
+    screen.draw_text(
+        &format!(
+            "BACKPACK  {}/{}",
+            inventory.entries.len(),
+            inventory.capacity
+        ),
+        (panel_x + PADDING) as i32,
+        (panel_y + PADDING) as i32,
+        TITLE_FONT_SIZE,
+        Color::WHITE,
+    );
+
+    // --- Grid ---
+    let grid_y = panel_y + PADDING * 2.0 + TITLE_FONT_SIZE as f32;
+    let grid_x = panel_x + (panel_width - grid_width) / 2.0;
+    for slot in 0..inventory.capacity {
+        let row = slot / BACKPACK_COLUMNS;
+        let column = slot % BACKPACK_COLUMNS;
+        let cell_x = grid_x + column as f32 * (CELL_SIZE + CELL_GAP);
+        let mut cell_y = grid_y + row as f32 * (CELL_SIZE + CELL_GAP);
+        if row > 0 {
+            cell_y += HOTBAR_GAP;
+        }
+        let cell_color = if view.held == Some(slot) {
+            HELD_COLOR
+        } else if slot == view.cursor {
+            SELECTED_COLOR
+        } else {
+            CELL_COLOR
+        };
+        let cell = Rectangle::new(cell_x, cell_y, CELL_SIZE, CELL_SIZE);
+        screen.draw_rectangle_rec(cell, cell_color);
+        if inventory.is_favorite(slot) {
+            screen.draw_rectangle_lines_ex(cell, 2.0, FAVORITE_COLOR);
+        }
+
+        if let Some(entry) = inventory.get(slot) {
+            if let Some(texture) = entry
+                .item
+                .sprite
+                .and_then(|sprite| graphics.get_sprite_texture(sprite))
+            {
+                screen.draw_texture(
+                    texture,
+                    (cell_x + (CELL_SIZE - ICON_SIZE) / 2.0) as i32,
+                    (cell_y + (CELL_SIZE - ICON_SIZE) / 2.0) as i32,
+                    Color::WHITE,
+                );
+            }
+            if entry.item.count > 1 {
+                let count_text = entry.item.count.to_string();
+                let count_width = screen.measure_text(&count_text, COUNT_FONT_SIZE);
+                screen.draw_text(
+                    &count_text,
+                    (cell_x + CELL_SIZE) as i32 - count_width - 3,
+                    (cell_y + CELL_SIZE) as i32 - COUNT_FONT_SIZE - 2,
+                    COUNT_FONT_SIZE,
+                    Color::LIGHTGRAY,
+                );
+            }
+        }
+    }
+
+    // --- What's under the cursor ---
+    let details_y = grid_y + grid_height + PADDING;
+    let details = match inventory.get(view.cursor) {
+        Some(entry) if entry.item.count > 1 => {
+            format!("{} x{}", entry.item.name, entry.item.count)
+        }
+        Some(entry) => entry.item.name.to_string(),
+        None => "-".to_string(),
+    };
+    screen.draw_text(
+        &details,
+        (panel_x + PADDING) as i32,
+        details_y as i32,
+        FONT_SIZE,
+        Color::WHITE,
+    );
+    let sort_text = format!("sort by {}", view.sort_key.name());
+    let sort_width = screen.measure_text(&sort_text, FONT_SIZE);
+    screen.draw_text(
+        &sort_text,
+        (panel_x + panel_width - PADDING) as i32 - sort_width,
+        details_y as i32,
+        FONT_SIZE,
+        Color::GRAY,
+    );
+
+    // --- Footer ---
+    screen.draw_text(
+        footer,
+        (panel_x + PADDING) as i32,
+        (details_y + FONT_SIZE as f32 + PADDING) as i32,
+        FONT_SIZE,
+        Color::LIGHTGRAY,
+    );
+}
+
+/// The stack being dragged follows the cursor. Otherwise hovering a hotbar slot shows what's in it.
+pub fn render_inventory_mouse(
+    state: &State,
+    graphics: &Graphics,
+    screen: &mut RaylibTextureMode<RaylibDrawHandle>,
+) {
+    const ICON_SIZE: f32 = 16.0;
+    const FONT_SIZE: i32 = 20;
+    const PANEL_GAP: f32 = 60.0; // clear of the slots and the details banner
+    const DRAGGED_TINT: Color = Color::new(255, 255, 255, 200);
+
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+    {
+        Some(player) => player,
+        None => return,
+    };
+    let mouse_pos = graphics.screen_to_texture(state.mouse_inputs.pos.as_vec2());
+
+    if let Some(drag) = state.inventory_drag {
+        let item = match player.inventory.get(drag.slot) {
+            Some(entry) => entry.item,
+            None => return,
+        };
+        // a split only takes half along
+        let count = if drag.split {
+            item.count.div_ceil(2)
+        } else {
+            item.count
+        };
+        if let Some(texture) = item
+            .sprite
+            .and_then(|sprite| graphics.get_sprite_texture(sprite))
+        {
+            screen.draw_texture(
+                texture,
+                (mouse_pos.x - ICON_SIZE / 2.0) as i32,
+                (mouse_pos.y - ICON_SIZE / 2.0) as i32,
+                DRAGGED_TINT,
+            );
+        }
+        if count > 1 {
+            screen.draw_text(
+                &format!("x{}", count),
+                (mouse_pos.x + ICON_SIZE / 2.0 + 2.0) as i32,
+                mouse_pos.y as i32,
+                FONT_SIZE,
+                Color::WHITE,
+            );
+        }
+        return;
+    }
+
+    if let Some(entry) =
+        hotbar_slot_at(&player.inventory, mouse_pos).and_then(|slot| player.inventory.get(slot))
+    {
+        let x_pos = HOTBAR_START_X + HOTBAR_SELECTION_OFFSET_X + HOTBAR_SLOT_WIDTH + PANEL_GAP;
+        render_item_details_panel(screen, graphics, &entry.item, x_pos, mouse_pos.y, "Hovered");
+    }
+}
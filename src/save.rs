@@ -0,0 +1,140 @@
+/* Saving and loading a run.
+   A save is a checkpoint, not a snapshot: it keeps the stage seed and the run's progress
+   (wave, score, coins, upgrades, time of day, weather, what's been explored) and rebuilds the
+   stage from the seed on load.
+   Whatever was built or dropped on the map since is lost, which is fine for a save taken
+   between waves.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    day_night::set_chickens_roosting,
+    graphics::Graphics,
+    inventory::MAX_SLOTS,
+    shop::{apply_upgrade, Upgrade},
+    stage::init_playing_state,
+    state::State,
+    waves::WavePhase,
+    weather::Weather,
+};
+
+pub const SAVE_PATH: &str = "save.toml";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    pub seed: u32,
+    pub wave: u32,
+    pub points: u32,
+    pub coins: u32,
+    pub kills: u32,
+    pub play_time: f32,
+    pub max_hp_level: u32,
+    pub move_speed_level: u32,
+    pub inventory_slots_level: u32,
+    /// Counts the slots backpacks added as well as the upgrades.
+    #[serde(default)]
+    pub inventory_capacity: usize,
+    pub clock_time: f32,
+    pub day: u32,
+    /// One string per column of the stage, `#` for explored and `.` for not.
+    #[serde(default)]
+    pub explored: Vec<String>,
+    #[serde(default)]
+    pub weather: Option<Weather>,
+}
+
+impl SaveData {
+    pub fn from_state(state: &State) -> SaveData {
+        SaveData {
+            seed: state.stage.seed,
+            wave: state.waves.wave,
+            points: state.points,
+            coins: state.coins,
+            kills: state.run_stats.kills,
+            play_time: state.run_stats.time,
+            max_hp_level: state.upgrades.max_hp,
+            move_speed_level: state.upgrades.move_speed,
+            inventory_slots_level: state.upgrades.inventory_slots,
+            inventory_capacity: state
+                .player_vid
+                .and_then(|vid| state.entity_manager.get_entity(vid))
+                .map_or(0, |player| player.inventory.capacity),
+            clock_time: state.clock.time,
+            day: state.clock.day,
+            explored: state
+                .lighting
+                .explored
+                .iter()
+                .map(|column| column.iter().map(|&e| if e { '#' } else { '.' }).collect())
+                .collect(),
+            weather: Some(state.weather),
+        }
+    }
+}
+
+/// Only save between waves, so a load never drops the player into the middle of a fight.
+pub fn can_save(state: &State) -> bool {
+    state.waves.phase == WavePhase::Build && state.player_vid.is_some()
+}
+
+pub fn save_game(state: &State) -> Result<(), String> {
+    let text = toml::to_string(&SaveData::from_state(state)).map_err(|e| e.to_string())?;
+    std::fs::write(SAVE_PATH, text).map_err(|e| e.to_string())
+}
+
+/// Rebuilds the saved stage and puts the run back where it was.
+pub fn load_game(state: &mut State, graphics: &mut Graphics) -> Result<(), String> {
+    let text = std::fs::read_to_string(SAVE_PATH).map_err(|e| e.to_string())?;
+    let data: SaveData = toml::from_str(&text).map_err(|e| e.to_string())?;
+
+    state.next_stage_seed = Some(data.seed);
+    init_playing_state(state, graphics);
+
+    state.waves.wave = data.wave;
+    state.points = data.points;
+    state.coins = data.coins;
+    state.run_stats.kills = data.kills;
+    state.run_stats.time = data.play_time;
+    state.clock.time = data.clock_time;
+    state.clock.day = data.day;
+    if let Some(weather) = data.weather {
+        state.weather = weather;
+        state.cloud_density = weather.clouds;
+    }
+    let night = state.clock.is_night();
+    set_chickens_roosting(state, night);
+
+    // older saves don't have it, and a different sized stage can't use it
+    if data.explored.len() == state.lighting.explored.len() {
+        for (column, saved) in state.lighting.explored.iter_mut().zip(&data.explored) {
+            for (explored, c) in column.iter_mut().zip(saved.chars()) {
+                *explored |= c == '#';
+            }
+        }
+    }
+
+    // replay the upgrades onto the fresh player
+    state.upgrades.max_hp = data.max_hp_level;
+    state.upgrades.move_speed = data.move_speed_level;
+    state.upgrades.inventory_slots = data.inventory_slots_level;
+    if let Some(player) = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        for (upgrade, level) in [
+            (Upgrade::MaxHp, data.max_hp_level),
+            (Upgrade::MoveSpeed, data.move_speed_level),
+            (Upgrade::InventorySlots, data.inventory_slots_level),
+        ] {
+            for _ in 0..level {
+                apply_upgrade(player, upgrade);
+            }
+        }
+        player.inventory.capacity = player
+            .inventory
+            .capacity
+            .max(data.inventory_capacity.min(MAX_SLOTS));
+    }
+    Ok(())
+}
@@ -78,3 +78,4 @@ impl ControlsSettings {
 
 pub const INVENTORY_SELECTION_DEBOUNCE_INTERVAL: f32 = 0.1;
 pub const MENU_SELECTION_DEBOUNCE_INTERVAL: f32 = 0.2;
+pub const INTERACT_DEBOUNCE_INTERVAL: f32 = 0.25;
@@ -0,0 +1,22 @@
+#version 330
+
+// Input vertex attributes (from vertex shader)
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+// Input uniform values
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+// Output fragment color
+out vec4 finalColor;
+
+void main()
+{
+    vec4 texelColor = texture(texture0, fragTexCoord) * colDiffuse * fragColor;
+
+    // Luminance weights for the eye's sensitivity to each channel
+    float gray = dot(texelColor.rgb, vec3(0.299, 0.587, 0.114));
+
+    finalColor = vec4(gray, gray, gray, texelColor.a);
+}
@@ -0,0 +1,231 @@
+/* Score, coins and the shop.
+   Everything worth points also pays out the same number of coins. Points only go up and make
+   the final score, coins get spent here between waves on supplies or on upgrades that last the
+   rest of the run. The shop only opens during a build phase, and the game waits while it's up.
+*/
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    entity::Entity,
+    inventory::MAX_SLOTS,
+    item::{Item, ItemType},
+    state::State,
+    waves::WavePhase,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upgrade {
+    MaxHp,
+    MoveSpeed,
+    InventorySlots,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShopGoods {
+    /// An item stack of the given count.
+    Item(ItemType, u32),
+    Upgrade(Upgrade),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShopEntry {
+    pub goods: ShopGoods,
+    pub name: &'static str,
+    /// None once an upgrade is maxed out.
+    pub price: Option<u32>,
+}
+
+/// How many times each upgrade has been bought this run.
+#[derive(Debug, Clone, Copy)]
+pub struct Upgrades {
+    pub max_hp: u32,
+    pub move_speed: u32,
+    pub inventory_slots: u32,
+}
+
+impl Upgrades {
+    pub fn new() -> Upgrades {
+        Upgrades {
+            max_hp: 0,
+            move_speed: 0,
+            inventory_slots: 0,
+        }
+    }
+
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        match upgrade {
+            Upgrade::MaxHp => self.max_hp,
+            Upgrade::MoveSpeed => self.move_speed,
+            Upgrade::InventorySlots => self.inventory_slots,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Shop {
+    pub selected: usize,
+}
+
+impl Shop {
+    pub fn new() -> Shop {
+        Shop { selected: 0 }
+    }
+}
+
+/// Adds to the score and pays out the same in coins.
+pub fn award_points(state: &mut State, points: u32) {
+    state.points += points;
+    state.coins += points;
+}
+
+/// The price of the next level of an upgrade, or None if there are no more levels.
+pub fn upgrade_price(upgrade: Upgrade, level: u32) -> Option<u32> {
+    let (base_price, max_level) = match upgrade {
+        Upgrade::MaxHp => (60, 4),
+        Upgrade::MoveSpeed => (80, 3),
+        Upgrade::InventorySlots => (100, 2),
+    };
+    if level >= max_level {
+        return None;
+    }
+    Some(base_price * (level + 1))
+}
+
+/// Everything for sale, supplies first and then upgrades.
+pub fn shop_entries(state: &State) -> Vec<ShopEntry> {
+    let mut entries = vec![
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Wall, 10),
+            name: "Walls x10",
+            price: Some(15),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Medkit, 1),
+            name: "Medkit",
+            price: Some(40),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Bat, 1),
+            name: "Bat",
+            price: Some(60),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::SpikeTrap, 3),
+            name: "Spike Traps x3",
+            price: Some(30),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Torch, 5),
+            name: "Torches x5",
+            price: Some(20),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Helmet, 1),
+            name: "Helmet",
+            price: Some(70),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Jacket, 1),
+            name: "Jacket",
+            price: Some(55),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Gloves, 1),
+            name: "Gloves",
+            price: Some(45),
+        },
+        ShopEntry {
+            goods: ShopGoods::Item(ItemType::Lantern, 1),
+            name: "Lantern",
+            price: Some(40),
+        },
+    ];
+
+    for (upgrade, name) in [
+        (Upgrade::MaxHp, "Max HP +25"),
+        (Upgrade::MoveSpeed, "Move Speed +15%"),
+        (Upgrade::InventorySlots, "Backpack +2 Slots"),
+    ] {
+        entries.push(ShopEntry {
+            goods: ShopGoods::Upgrade(upgrade),
+            name,
+            price: upgrade_price(upgrade, state.upgrades.level(upgrade)),
+        });
+    }
+    entries
+}
+
+/// The shop is open for business between waves.
+pub fn can_open_shop(state: &State) -> bool {
+    state.waves.phase == WavePhase::Build && state.player_vid.is_some()
+}
+
+/// Buys the entry at `index` if the player can afford it and has room for it.
+pub fn buy(state: &mut State, audio: &mut Audio, index: usize) -> bool {
+    let entry = match shop_entries(state).get(index) {
+        Some(entry) => *entry,
+        None => return false,
+    };
+    let price = match entry.price {
+        Some(price) if price <= state.coins => price,
+        _ => {
+            audio.play_sound_effect(SoundEffect::CantUse);
+            return false;
+        }
+    };
+    let player = match state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+    {
+        Some(player) => player,
+        None => return false,
+    };
+
+    match entry.goods {
+        ShopGoods::Item(item_type, count) => {
+            let mut item = Item::new(item_type);
+            item.count = count.min(item.max_count);
+            // don't take the money for something that won't fit, and don't swap anything out
+            if player.inventory.room_for(&item) < item.count {
+                audio.play_sound_effect(SoundEffect::CantUse);
+                return false;
+            }
+            player.inventory.insert(item);
+        }
+        ShopGoods::Upgrade(upgrade) => {
+            // backpacks can get the inventory as big as it goes before the upgrades run out
+            if upgrade == Upgrade::InventorySlots && player.inventory.capacity >= MAX_SLOTS {
+                audio.play_sound_effect(SoundEffect::CantUse);
+                return false;
+            }
+            apply_upgrade(player, upgrade);
+            match upgrade {
+                Upgrade::MaxHp => state.upgrades.max_hp += 1,
+                Upgrade::MoveSpeed => state.upgrades.move_speed += 1,
+                Upgrade::InventorySlots => state.upgrades.inventory_slots += 1,
+            }
+        }
+    }
+
+    state.coins -= price;
+    audio.play_sound_effect(SoundEffect::Confirm);
+    true
+}
+
+/// One level of an upgrade, applied straight to the player.
+pub fn apply_upgrade(player: &mut Entity, upgrade: Upgrade) {
+    match upgrade {
+        Upgrade::MaxHp => {
+            const HP_PER_LEVEL: u32 = 25;
+            player.max_hp += HP_PER_LEVEL;
+            player.health += HP_PER_LEVEL;
+        }
+        Upgrade::MoveSpeed => {
+            player.move_cooldown *= 0.85;
+        }
+        Upgrade::InventorySlots => {
+            const SLOTS_PER_LEVEL: usize = 2;
+            player.inventory.add_slots(SLOTS_PER_LEVEL);
+        }
+    }
+}
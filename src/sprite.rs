@@ -17,11 +17,20 @@ pub enum Sprite {
     // Tile Sprites
     Grass,
     Wall,
+    WallEdge,
     Ruin,
     Water1,
     Water2,
     Water3,
     Water4,
+    WaterCorner,
+    Door,
+    Mud,
+    Rock,
+    Spikes,
+    Torch,
+    Campfire,
+    Ashes,
 
     // Chicken Sprites
     Chick,
@@ -38,10 +47,12 @@ pub enum Sprite {
 
     BloodSmall,
     BloodMedium,
+    Explosion,
 
     Cloud1,
     Cloud2,
     Cloud3,
+    RainDrop,
 
     // Item Sprites
     Fist,
@@ -49,6 +60,32 @@ pub enum Sprite {
     Bandage,
     Bandaid,
     ConductorHat,
+    Bat,
+    Whistle,
+    Helmet,
+    Jacket,
+    Gloves,
+    Lantern,
+    Stone,
+    Feather,
+    Meat,
+    Cloth,
+    Stick,
+    Arrow,
+    Egg,
+    Scrap,
+    Chest,
+    Backpack,
+
+    // Containers
+    Crate,
+
+    // Status Effect Icons
+    StatusBleeding,
+    StatusBurning,
+    StatusStunned,
+    StatusSlowed,
+    StatusInfected,
 
     // train
     TrainHead,
@@ -56,7 +93,11 @@ pub enum Sprite {
     TrainCarB,
     Caboose,
     Rail,
+    RailCurve,
+    RailSwitch,
     RailCrossing,
+    Station,
     TrainBlinkensign,
+    TrainBlinkensignLit,
     TrainCarBlockPole,
 }
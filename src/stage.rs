@@ -1,16 +1,24 @@
 use glam::{IVec2, Vec2};
-use noise::{NoiseFn, Perlin};
 use rand::{random, random_range};
 
 use crate::{
+    autotile::refresh_masks_around,
+    containers::{spawn_container, ContainerKind},
+    day_night::Clock,
     entity::{self, EntityType, Mood},
     entity_templates::{init_as_chicken, init_as_player, init_as_zombie},
     graphics::Graphics,
     item::{Item, ItemType},
+    lighting::{step_lighting, Lighting},
+    loot::LootSource,
+    shop::{Shop, Upgrades},
     sprite::Sprite,
+    stage_gen::generate_stage,
     state::State,
-    step::FRAMES_PER_SECOND,
     tile::{get_tile_variants, is_tile_walkable, Tile},
+    train::Timetable,
+    waves::{RunStats, Waves},
+    weather::Weather,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -28,6 +36,12 @@ pub struct TileData {
     pub flip_speed: u16,
     pub rot: f32,
     pub shake: f32,
+    /// Which neighbors of the same family this tile connects to, see `autotile`.
+    pub mask: u8,
+    /// Rail switches: thrown, sending trains down the turn.
+    /// Crossings: a train is coming, the lights are flashing and the barriers are down.
+    /// Water: it's a puddle left by the rain and will dry up again.
+    pub switched: bool,
 }
 
 impl Default for TileData {
@@ -41,76 +55,168 @@ impl Default for TileData {
             flip_speed: 0,
             rot: 0.0,
             shake: 0.0,
+            mask: 0,
+            switched: false,
         }
     }
 }
+/// Stages are stacked layers. Structures sit on top of the floor, and removing
+/// one reveals whatever terrain was underneath.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileLayer {
+    Floor,
+    Structure,
+    Overlay,
+}
+
+/// Bottom to top, the order layers are drawn in.
+pub const TILE_LAYERS: [TileLayer; 3] =
+    [TileLayer::Floor, TileLayer::Structure, TileLayer::Overlay];
+
 #[derive(Debug, Clone)]
 pub struct Stage {
     pub stage_type: StageType,
-    pub tiles: Vec<Vec<TileData>>,
+    pub seed: u32,
+    pub layers: [Vec<Vec<TileData>>; 3],
+    /// Tiles whose spot on the map may be out of date: ones that were changed, and ones coming
+    /// into or going out of view. `update_map_texture` works through them.
+    pub map_dirty: Vec<IVec2>,
 }
 
 impl Stage {
     pub fn new(stage_type: StageType, width: usize, height: usize) -> Stage {
         let tiles = vec![vec![TileData::default(); height]; width];
 
-        Stage { stage_type, tiles }
+        Stage {
+            stage_type,
+            seed: 0,
+            layers: [tiles.clone(), tiles.clone(), tiles],
+            map_dirty: Vec::new(),
+        }
     }
 
-    pub fn get_tile_type(&self, x: usize, y: usize) -> Option<Tile> {
-        if x < self.tiles.len() && y < self.tiles[0].len() {
-            Some(self.tiles[x][y].tile)
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.get_width() && y < self.get_height()
+    }
+
+    /// The layer gameplay cares about at a spot: the structure if there is one, otherwise the floor.
+    fn top_layer(&self, x: usize, y: usize) -> TileLayer {
+        if self.layers[TileLayer::Structure as usize][x][y].tile != Tile::None {
+            TileLayer::Structure
+        } else {
+            TileLayer::Floor
+        }
+    }
+
+    pub fn get_layer_tile(&self, layer: TileLayer, x: usize, y: usize) -> Option<TileData> {
+        if self.contains(x, y) {
+            Some(self.layers[layer as usize][x][y])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_layer_tile_mut(
+        &mut self,
+        layer: TileLayer,
+        x: usize,
+        y: usize,
+    ) -> Option<&mut TileData> {
+        if self.contains(x, y) {
+            Some(&mut self.layers[layer as usize][x][y])
         } else {
             None
         }
     }
 
+    /// Type of the topmost floor or structure tile.
+    pub fn get_tile_type(&self, x: usize, y: usize) -> Option<Tile> {
+        self.get_tile(x, y).map(|td| td.tile)
+    }
+
+    /// Topmost floor or structure tile.
     pub fn get_tile(&self, x: usize, y: usize) -> Option<TileData> {
-        if x < self.tiles.len() && y < self.tiles[0].len() {
-            Some(self.tiles[x][y])
+        if self.contains(x, y) {
+            self.get_layer_tile(self.top_layer(x, y), x, y)
         } else {
             None
         }
     }
 
     pub fn get_tile_mut(&mut self, x: usize, y: usize) -> Option<&mut TileData> {
-        if x < self.tiles.len() && y < self.tiles[0].len() {
-            Some(&mut self.tiles[x][y])
+        if self.contains(x, y) {
+            let layer = self.top_layer(x, y);
+            self.get_layer_tile_mut(layer, x, y)
         } else {
             None
         }
     }
 
+    /// Places a tile on whichever layer its type belongs to, leaving the other layers alone.
     pub fn set_tile(&mut self, x: usize, y: usize, tile_data: TileData) {
-        if x < self.tiles.len() && y < self.tiles[0].len() {
-            self.tiles[x][y] = tile_data;
+        self.set_layer_tile(tile_data.tile.layer(), x, y, tile_data);
+    }
+
+    pub fn set_layer_tile(&mut self, layer: TileLayer, x: usize, y: usize, tile_data: TileData) {
+        if self.contains(x, y) {
+            self.layers[layer as usize][x][y] = tile_data;
+            refresh_masks_around(self, layer, x, y);
+            self.map_dirty.push(IVec2::new(x as i32, y as i32));
         }
     }
 
+    pub fn clear_layer_tile(&mut self, layer: TileLayer, x: usize, y: usize) {
+        self.set_layer_tile(layer, x, y, TileData::default());
+    }
+
+    /// A structure decides walkability when present (a rail bridges water), otherwise the floor does.
+    pub fn is_walkable(&self, x: usize, y: usize) -> bool {
+        self.get_tile_type(x, y).is_some_and(|t| t.walkable())
+    }
+
+    /// Structures can go on any buildable floor that doesn't already have one.
+    pub fn can_build_on(&self, x: usize, y: usize) -> bool {
+        if !self.contains(x, y) {
+            return false;
+        }
+        self.layers[TileLayer::Structure as usize][x][y].tile == Tile::None
+            && self.layers[TileLayer::Floor as usize][x][y]
+                .tile
+                .can_build_on()
+    }
+
     pub fn clear(&mut self) {
-        for row in &mut self.tiles {
-            for tile in row {
-                *tile = TileData::default()
+        for layer in &mut self.layers {
+            for row in layer {
+                for tile in row {
+                    *tile = TileData::default()
+                }
+            }
+        }
+        self.map_dirty.clear();
+        for x in 0..self.get_width() {
+            for y in 0..self.get_height() {
+                self.map_dirty.push(IVec2::new(x as i32, y as i32));
             }
         }
     }
 
     pub fn get_center_position(&self) -> IVec2 {
-        let width = self.tiles.len() as f32;
-        let height = self.tiles[0].len() as f32;
+        let width = self.get_width() as f32;
+        let height = self.get_height() as f32;
         IVec2::new((width / 2.0) as i32, (height / 2.0) as i32)
     }
 
     pub fn get_height(&self) -> usize {
-        if self.tiles.is_empty() {
+        if self.layers[0].is_empty() {
             0
         } else {
-            self.tiles[0].len()
+            self.layers[0][0].len()
         }
     }
 
     pub fn get_width(&self) -> usize {
-        self.tiles.len()
+        self.layers[0].len()
     }
 
     pub fn get_dims(&self) -> IVec2 {
@@ -134,6 +240,7 @@ pub fn init_playing_state(state: &mut State, _graphics: &mut Graphics) {
     state.pause = false;
     state.win = false;
     state.points = 0;
+    state.coins = 0;
     state.deaths = 0;
     state.frame_pause = 0;
     state.time_since_last_update = 0.0;
@@ -143,62 +250,25 @@ pub fn init_playing_state(state: &mut State, _graphics: &mut Graphics) {
     let height = state.stage.get_height();
     state.spatial_grid = vec![vec![Vec::new(); height]; width];
 
-    // --- NEW: Perlin Noise World Generation ---
-    let perlin = Perlin::new(random::<u32>());
-    let scale = 0.08; // You can tweak this! Lower value = larger features.
-
-    for x in 0..width {
-        for y in 0..height {
-            let nx = x as f64 * scale;
-            let ny = y as f64 * scale;
-
-            // Get a noise value between -1.0 and 1.0
-            let noise_value = perlin.get([nx, ny]);
-
-            // Set tile based on noise value thresholds.
-            // Values around 0 will be void.
-            if noise_value > 0.4 {
-                let mut tile = TileData::default();
-                tile.tile = Tile::Grass;
-                state.stage.set_tile(x, y, tile);
-            } else if noise_value < -0.8 {
-                let mut tile = TileData::default();
-                tile.tile = Tile::Water;
-                tile.variant = if random::<bool>() {
-                    0 // Variant 0 for dirt
-                } else {
-                    1 // Variant 1 for dirt
-                };
-                tile.flip_speed = FRAMES_PER_SECOND as u16; // Flip every 2 seconds
-                state.stage.set_tile(x, y, tile);
-            } else {
-                let tile = TileData::default();
-                state.stage.set_tile(x, y, tile);
-            }
-        }
-    }
-    // --- End of new generation logic ---
+    // --- World Generation ---
+    let seed = state.next_stage_seed.take().unwrap_or_else(random::<u32>);
+    state.stage_layout = generate_stage(&mut state.stage, seed);
+    state.timetable = Timetable::new();
+    state.waves = Waves::new();
+    state.run_stats = RunStats::new();
+    state.clock = Clock::new();
+    state.weather = Weather::new();
+    state.upgrades = Upgrades::new();
+    state.shop = Shop::new();
 
     // --- Make Player ---
     let player_vid = state.entity_manager.new_entity().unwrap();
     state.player_vid = Some(player_vid);
-    let player_grid_pos;
+    let player_grid_pos = state.stage_layout.spawn;
     {
         let player = state.entity_manager.get_entity_mut(player_vid).unwrap();
         init_as_player(player);
-
-        // Try to spawn player on a walkable tile near the center
-        loop {
-            let center = state.stage.get_center_position();
-            let x = random_range(center.x - 5..center.x + 5);
-            let y = random_range(center.y - 5..center.y + 5);
-            if is_tile_walkable(state, IVec2::new(x as i32, y as i32)) {
-                let player = state.entity_manager.get_entity_mut(player_vid).unwrap();
-                player.pos = IVec2::new(x as i32, y as i32).as_vec2() + Vec2::splat(0.5);
-                player_grid_pos = player.pos.as_ivec2();
-                break;
-            }
-        }
+        player.pos = player_grid_pos.as_vec2() + Vec2::splat(0.5);
     }
     state.add_entity_to_grid(player_vid, player_grid_pos);
 
@@ -228,10 +298,12 @@ pub fn init_playing_state(state: &mut State, _graphics: &mut Graphics) {
         }
     }
 
-    // spawn a bunch of chickens
+    // spawn a bunch of chickens, a few of them penned up in coops
     // let num_chickens = 0;
     let num_chickens = 32;
-    for _ in 0..num_chickens {
+    const CHICKENS_PER_COOP: usize = 3;
+    let coops = state.stage_layout.coops.clone();
+    for i in 0..num_chickens {
         if let Some(vid) = state.entity_manager.new_entity() {
             let chicken_grid_pos;
             {
@@ -239,9 +311,15 @@ pub fn init_playing_state(state: &mut State, _graphics: &mut Graphics) {
                 init_as_chicken(chicken);
 
                 // place chicken
+                let coop = coops.get(i / CHICKENS_PER_COOP).copied();
                 loop {
-                    let x = random_range(0..width);
-                    let y = random_range(0..height);
+                    let (x, y) = match coop {
+                        Some(center) => (
+                            random_range(center.x - 1..=center.x + 1) as usize,
+                            random_range(center.y - 1..=center.y + 1) as usize,
+                        ),
+                        None => (random_range(0..width), random_range(0..height)),
+                    };
                     if is_tile_walkable(state, IVec2::new(x as i32, y as i32)) {
                         let chicken = state.entity_manager.get_entity_mut(vid).unwrap();
                         chicken.pos = IVec2::new(x as i32, y as i32).as_vec2() + Vec2::splat(0.5);
@@ -254,17 +332,39 @@ pub fn init_playing_state(state: &mut State, _graphics: &mut Graphics) {
             }
         }
     }
+
+    // --- Crates ---
+    // the ruins still have a few things worth finding in them
+    for pos in state.stage_layout.crates.clone() {
+        if is_tile_walkable(state, pos) {
+            let items = state.loot.roll(LootSource::Crate);
+            spawn_container(state, ContainerKind::Crate, pos, &items);
+        }
+    }
+
+    // a new stage starts unexplored, then work out what the player can see before the first frame
+    state.lighting = Lighting::new(width, height);
+    step_lighting(state);
+}
+
+/// Generates a fresh random stage for the preview screen without spawning anything on it.
+pub fn init_stage_preview(state: &mut State) {
+    state.mode = crate::state::Mode::StagePreview;
+    state.stage = Stage::new(StageType::TestArena, 64, 64);
+    state.stage_layout = generate_stage(&mut state.stage, random::<u32>());
 }
 
 /// check tile data flip speed % state.frame to see if it should flip
 pub fn flip_stage_tiles(state: &mut State) {
-    for x in 0..state.stage.get_width() {
-        for y in 0..state.stage.get_height() {
-            if let Some(tile_data) = state.stage.get_tile_mut(x, y) {
-                if tile_data.flip_speed > 0 && state.frame % tile_data.flip_speed as u32 == 0 {
-                    let new_variant =
-                        (tile_data.variant + 1) % get_tile_variants(tile_data).len() as u8;
-                    tile_data.variant = new_variant;
+    for layer in TILE_LAYERS {
+        for x in 0..state.stage.get_width() {
+            for y in 0..state.stage.get_height() {
+                if let Some(tile_data) = state.stage.get_layer_tile_mut(layer, x, y) {
+                    if tile_data.flip_speed > 0 && state.frame % tile_data.flip_speed as u32 == 0 {
+                        let new_variant =
+                            (tile_data.variant + 1) % get_tile_variants(tile_data).len() as u8;
+                        tile_data.variant = new_variant;
+                    }
                 }
             }
         }
@@ -0,0 +1,566 @@
+/* Layered stage generation.
+   Elevation and moisture come from fractal noise, those pick a biome per tile,
+   then structures get stamped on top, and finally we make sure everything walkable
+   can actually be reached from the player spawn.
+*/
+
+use std::collections::VecDeque;
+
+use glam::IVec2;
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    stage::{Stage, TileData, TileLayer},
+    step::FRAMES_PER_SECOND,
+    tile::Tile,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    Meadow,
+    Marsh,
+    Ruins,
+    Rocky,
+}
+
+/// Where the interesting bits ended up, so the caller can place entities.
+#[derive(Debug, Clone)]
+pub struct StageLayout {
+    pub spawn: IVec2,
+    pub coops: Vec<IVec2>,
+    /// Spots in the ruined houses that get a crate of loot.
+    pub crates: Vec<IVec2>,
+    pub rail_row: Option<i32>,
+    pub stations: Vec<IVec2>,
+    pub crossings: Vec<IVec2>,
+}
+
+impl StageLayout {
+    pub fn new() -> Self {
+        Self {
+            spawn: IVec2::ZERO,
+            coops: Vec::new(),
+            crates: Vec::new(),
+            rail_row: None,
+            stations: Vec::new(),
+            crossings: Vec::new(),
+        }
+    }
+}
+
+const CARDINALS: [IVec2; 4] = [
+    IVec2::new(0, -1),
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 0),
+];
+
+/// Fills the stage from the given seed. The same seed always yields the same map.
+pub fn generate_stage(stage: &mut Stage, seed: u32) -> StageLayout {
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+    let mut layout = StageLayout::new();
+    stage.clear();
+    stage.seed = seed;
+
+    let biomes = generate_terrain(stage, seed, &mut rng);
+
+    // structures claim tiles so they don't get stacked on top of each other
+    let dims = stage.get_dims();
+    let mut claimed = vec![vec![false; dims.y as usize]; dims.x as usize];
+
+    if let Some(row) = place_rail_line(stage, &mut rng, &mut claimed) {
+        layout.rail_row = Some(row);
+        layout.stations = place_stations(stage, &mut rng, row);
+        layout.crossings = place_crossings(stage, &layout.stations, row);
+    }
+    layout.crates = place_houses(stage, &biomes, &mut rng, &mut claimed);
+    layout.coops = place_coops(stage, &biomes, &mut rng, &mut claimed);
+
+    layout.spawn = find_spawn(stage);
+    ensure_connectivity(stage, layout.spawn);
+
+    layout
+}
+
+/// Biome from elevation and moisture, both roughly in -1..1.
+pub fn classify_biome(elevation: f64, moisture: f64, decay: f64) -> Biome {
+    if elevation > 0.3 {
+        Biome::Rocky
+    } else if moisture > 0.25 {
+        Biome::Marsh
+    } else if decay > 0.2 {
+        Biome::Ruins
+    } else {
+        Biome::Meadow
+    }
+}
+
+// --- Terrain ---
+
+fn generate_terrain(stage: &mut Stage, seed: u32, rng: &mut StdRng) -> Vec<Vec<Biome>> {
+    let elevation = Fbm::<Perlin>::new(seed).set_octaves(4).set_frequency(0.04);
+    let moisture = Fbm::<Perlin>::new(seed.wrapping_add(1))
+        .set_octaves(3)
+        .set_frequency(0.05);
+    // a third field decides where old settlements used to be
+    let decay = Fbm::<Perlin>::new(seed.wrapping_add(2))
+        .set_octaves(2)
+        .set_frequency(0.06);
+
+    let width = stage.get_width();
+    let height = stage.get_height();
+    let mut biomes = vec![vec![Biome::Meadow; height]; width];
+
+    for x in 0..width {
+        for y in 0..height {
+            let p = [x as f64, y as f64];
+            let e = elevation.get(p);
+            let m = moisture.get(p);
+            let r = decay.get(p);
+
+            let mut tile = TileData::default();
+
+            // low ground floods regardless of biome
+            if e < -0.35 {
+                stage.set_tile(x, y, water_tile(rng));
+                biomes[x][y] = Biome::Marsh;
+                continue;
+            }
+
+            let biome = classify_biome(e, m, r);
+            biomes[x][y] = biome;
+            match biome {
+                Biome::Meadow => {
+                    if rng.random_bool(0.8) {
+                        tile.tile = Tile::Grass;
+                    }
+                }
+                Biome::Marsh => {
+                    let roll: f64 = rng.random();
+                    if roll < 0.15 {
+                        tile = water_tile(rng);
+                    } else if roll < 0.65 {
+                        tile.tile = Tile::Mud;
+                    } else {
+                        tile.tile = Tile::Grass;
+                    }
+                }
+                Biome::Ruins => {
+                    if rng.random_bool(0.08) {
+                        tile.tile = Tile::Ruin;
+                    } else if rng.random_bool(0.3) {
+                        tile.tile = Tile::Grass;
+                    }
+                }
+                Biome::Rocky => {
+                    // the higher up, the more boulders
+                    let rock_chance = ((e - 0.3) * 2.0).clamp(0.1, 0.6);
+                    if rng.random_bool(rock_chance) {
+                        tile.tile = Tile::Rock;
+                    }
+                }
+            }
+            stage.set_tile(x, y, tile);
+        }
+    }
+
+    biomes
+}
+
+fn water_tile(rng: &mut StdRng) -> TileData {
+    let mut tile = TileData::default();
+    tile.tile = Tile::Water;
+    tile.variant = rng.random_range(0..2);
+    tile.flip_speed = FRAMES_PER_SECOND as u16;
+    tile
+}
+
+fn wall_tile() -> TileData {
+    let mut tile = TileData::default();
+    tile.tile = Tile::Wall;
+    tile.hp = 100;
+    tile.max_hp = 100;
+    tile.breakable = true;
+    tile
+}
+
+fn campfire_tile() -> TileData {
+    let mut tile = TileData::default();
+    tile.tile = Tile::Campfire;
+    tile.hp = 40;
+    tile.max_hp = 40;
+    tile.breakable = true;
+    tile
+}
+
+fn door_tile() -> TileData {
+    let mut tile = TileData::default();
+    tile.tile = Tile::Door;
+    tile.hp = 40;
+    tile.max_hp = 40;
+    tile.breakable = true;
+    tile
+}
+
+// --- Structures ---
+
+/// Lays a horizontal rail line across the whole map, away from the edges.
+fn place_rail_line(stage: &mut Stage, rng: &mut StdRng, claimed: &mut [Vec<bool>]) -> Option<i32> {
+    let height = stage.get_height() as i32;
+    if height < 16 {
+        return None;
+    }
+    let row = rng.random_range(8..height - 8);
+
+    for x in 0..stage.get_width() {
+        let mut tile = TileData::default();
+        tile.tile = Tile::Rail;
+        tile.rot = 90.0;
+        // rails go over anything; across water they double as a bridge
+        stage.clear_layer_tile(TileLayer::Overlay, x, row as usize);
+        stage.set_tile(x, row as usize, tile);
+
+        // keep a bit of clearance so houses don't hug the tracks
+        for dy in -1..=1 {
+            claimed[x][(row + dy) as usize] = true;
+        }
+    }
+
+    Some(row)
+}
+
+/// Swaps a few pieces of the rail line for stations, spread out along it.
+fn place_stations(stage: &mut Stage, rng: &mut StdRng, row: i32) -> Vec<IVec2> {
+    const STATION_COUNT: i32 = 3;
+    let width = stage.get_width() as i32;
+    let spacing = width / (STATION_COUNT + 1);
+    let mut stations = Vec::new();
+
+    for i in 1..=STATION_COUNT {
+        let x = (spacing * i + rng.random_range(-3..=3)).clamp(1, width - 2);
+        let mut tile = TileData::default();
+        tile.tile = Tile::Station;
+        tile.rot = 90.0;
+        stage.set_tile(x as usize, row as usize, tile);
+        // somewhere to stand while loading
+        clear_trackside(stage, x, row);
+        stations.push(IVec2::new(x, row));
+    }
+
+    stations
+}
+
+/// Puts a level crossing halfway between each pair of neighboring stations.
+fn place_crossings(stage: &mut Stage, stations: &[IVec2], row: i32) -> Vec<IVec2> {
+    let mut crossings = Vec::new();
+    for pair in stations.windows(2) {
+        let x = (pair[0].x + pair[1].x) / 2;
+        if x == pair[0].x || x == pair[1].x {
+            continue;
+        }
+        let mut tile = TileData::default();
+        tile.tile = Tile::Crossing;
+        tile.rot = 90.0;
+        stage.set_tile(x as usize, row as usize, tile);
+        // the path the crossing is for
+        clear_trackside(stage, x, row);
+        crossings.push(IVec2::new(x, row));
+    }
+    crossings
+}
+
+/// Makes sure the tiles right above and below a spot on the rail line can be walked on.
+fn clear_trackside(stage: &mut Stage, x: i32, row: i32) {
+    for y in [row - 1, row + 1] {
+        let (px, py) = (x as usize, y as usize);
+        stage.clear_layer_tile(TileLayer::Structure, px, py);
+        if !stage.is_walkable(px, py) {
+            let mut floor = TileData::default();
+            floor.tile = Tile::Grass;
+            stage.set_tile(px, py, floor);
+        }
+    }
+}
+
+/// Returns true if the rect (inclusive of its border) is free of claims and open water.
+fn can_place_structure(stage: &Stage, claimed: &[Vec<bool>], min: IVec2, size: IVec2) -> bool {
+    let max = min + size;
+    if min.x < 1
+        || min.y < 1
+        || max.x >= stage.get_width() as i32
+        || max.y >= stage.get_height() as i32
+    {
+        return false;
+    }
+    for x in min.x - 1..=max.x {
+        for y in min.y - 1..=max.y {
+            if claimed[x as usize][y as usize] {
+                return false;
+            }
+            if stage.get_tile_type(x as usize, y as usize) == Some(Tile::Water) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Stamps a walled rectangle with one door. `decay` is the chance any wall piece is already a ruin.
+/// Returns the interior center.
+fn stamp_building(
+    stage: &mut Stage,
+    rng: &mut StdRng,
+    claimed: &mut [Vec<bool>],
+    min: IVec2,
+    size: IVec2,
+    decay: f64,
+) -> IVec2 {
+    let max = min + size - IVec2::ONE;
+
+    // pick a door spot on one of the four sides, never a corner
+    let door = match rng.random_range(0..4) {
+        0 => IVec2::new(rng.random_range(min.x + 1..max.x), min.y),
+        1 => IVec2::new(max.x, rng.random_range(min.y + 1..max.y)),
+        2 => IVec2::new(rng.random_range(min.x + 1..max.x), max.y),
+        _ => IVec2::new(min.x, rng.random_range(min.y + 1..max.y)),
+    };
+
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            claimed[x as usize][y as usize] = true;
+            let pos = IVec2::new(x, y);
+            let on_edge = x == min.x || x == max.x || y == min.y || y == max.y;
+
+            // the floor underneath stays, only what was built on it gets replaced
+            stage.clear_layer_tile(TileLayer::Structure, x as usize, y as usize);
+            stage.clear_layer_tile(TileLayer::Overlay, x as usize, y as usize);
+
+            let tile = if pos == door {
+                door_tile()
+            } else if on_edge {
+                if rng.random_bool(decay) {
+                    let mut ruin = TileData::default();
+                    ruin.tile = Tile::Ruin;
+                    ruin
+                } else {
+                    wall_tile()
+                }
+            } else {
+                continue;
+            };
+            stage.set_tile(x as usize, y as usize, tile);
+        }
+    }
+
+    (min + max) / 2
+}
+
+/// Houses, mostly in the ruins. Returns spots inside the ruined ones for crates.
+fn place_houses(
+    stage: &mut Stage,
+    biomes: &[Vec<Biome>],
+    rng: &mut StdRng,
+    claimed: &mut [Vec<bool>],
+) -> Vec<IVec2> {
+    const MAX_HOUSES: usize = 8;
+    const ATTEMPTS: usize = 200;
+    const CRATE_CHANCE: f64 = 0.7;
+
+    let dims = stage.get_dims();
+    let mut placed = 0;
+    let mut crates = Vec::new();
+    for _ in 0..ATTEMPTS {
+        if placed >= MAX_HOUSES {
+            break;
+        }
+        let size = IVec2::new(rng.random_range(5..9), rng.random_range(4..8));
+        let min = IVec2::new(
+            rng.random_range(1..dims.x - size.x - 1),
+            rng.random_range(1..dims.y - size.y - 1),
+        );
+        let center = min + size / 2;
+
+        // houses mostly cluster in the ruins, with the odd farmhouse out in the meadow
+        let biome = biomes[center.x as usize][center.y as usize];
+        let chance = match biome {
+            Biome::Ruins => 0.9,
+            Biome::Meadow => 0.2,
+            _ => 0.0,
+        };
+        if !rng.random_bool(chance) || !can_place_structure(stage, claimed, min, size) {
+            continue;
+        }
+
+        let hearth = stamp_building(stage, rng, claimed, min, size, 0.3);
+        // most houses still have a fire going
+        if rng.random_bool(0.6) {
+            stage.set_tile(hearth.x as usize, hearth.y as usize, campfire_tile());
+        }
+        // whoever lived in the ruins left some of their things behind
+        if biome == Biome::Ruins && rng.random_bool(CRATE_CHANCE) {
+            let max = min + size - IVec2::ONE;
+            let spot = IVec2::new(
+                rng.random_range(min.x + 1..max.x),
+                rng.random_range(min.y + 1..max.y),
+            );
+            if spot != hearth {
+                crates.push(spot);
+            }
+        }
+        placed += 1;
+    }
+    crates
+}
+
+/// Small pens for chickens. Returns the interior centers.
+fn place_coops(
+    stage: &mut Stage,
+    biomes: &[Vec<Biome>],
+    rng: &mut StdRng,
+    claimed: &mut [Vec<bool>],
+) -> Vec<IVec2> {
+    const MAX_COOPS: usize = 3;
+    const ATTEMPTS: usize = 200;
+    let size = IVec2::new(5, 5);
+
+    let dims = stage.get_dims();
+    let mut coops = Vec::new();
+    for _ in 0..ATTEMPTS {
+        if coops.len() >= MAX_COOPS {
+            break;
+        }
+        let min = IVec2::new(
+            rng.random_range(1..dims.x - size.x - 1),
+            rng.random_range(1..dims.y - size.y - 1),
+        );
+        let center = min + size / 2;
+        if biomes[center.x as usize][center.y as usize] != Biome::Meadow
+            || !can_place_structure(stage, claimed, min, size)
+        {
+            continue;
+        }
+
+        coops.push(stamp_building(stage, rng, claimed, min, size, 0.0));
+    }
+    coops
+}
+
+// --- Connectivity ---
+
+fn is_open(stage: &Stage, pos: IVec2) -> bool {
+    stage.in_bounds(pos) && stage.is_walkable(pos.x as usize, pos.y as usize)
+}
+
+/// Nearest walkable tile to the center of the map, spiraling outward.
+fn find_spawn(stage: &mut Stage) -> IVec2 {
+    let center = stage.get_center_position();
+    let max_radius = stage.get_width().max(stage.get_height()) as i32;
+    for radius in 0..max_radius {
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                let pos = center + IVec2::new(dx, dy);
+                if is_open(stage, pos) {
+                    return pos;
+                }
+            }
+        }
+    }
+
+    // a map with no floor at all; just clear the center
+    stage.clear_layer_tile(TileLayer::Structure, center.x as usize, center.y as usize);
+    stage.set_tile(center.x as usize, center.y as usize, TileData::default());
+    center
+}
+
+/// Flood fill from `start` over walkable tiles.
+fn flood_fill(stage: &Stage, start: IVec2) -> Vec<Vec<bool>> {
+    let dims = stage.get_dims();
+    let mut reached = vec![vec![false; dims.y as usize]; dims.x as usize];
+    let mut queue = VecDeque::new();
+    reached[start.x as usize][start.y as usize] = true;
+    queue.push_back(start);
+
+    while let Some(pos) = queue.pop_front() {
+        for dir in CARDINALS {
+            let next = pos + dir;
+            if is_open(stage, next) && !reached[next.x as usize][next.y as usize] {
+                reached[next.x as usize][next.y as usize] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    reached
+}
+
+/// Turns whatever is in the way into walkable ground. Walls get a door, so houses
+/// keep their shape.
+fn carve(stage: &mut Stage, pos: IVec2) {
+    if is_open(stage, pos) {
+        return;
+    }
+    let (x, y) = (pos.x as usize, pos.y as usize);
+    match stage
+        .get_layer_tile(TileLayer::Structure, x, y)
+        .map(|td| td.tile)
+    {
+        Some(Tile::Wall) => stage.set_tile(x, y, door_tile()),
+        Some(Tile::None) | None => {}
+        _ => stage.clear_layer_tile(TileLayer::Structure, x, y),
+    }
+
+    // still blocked means the floor itself is the problem, e.g. water
+    if !is_open(stage, pos) {
+        let mut grass = TileData::default();
+        grass.tile = Tile::Grass;
+        stage.set_tile(x, y, grass);
+    }
+}
+
+/// Digs an L-shaped path between every pocket of walkable tiles and the spawn region.
+fn ensure_connectivity(stage: &mut Stage, spawn: IVec2) {
+    let dims = stage.get_dims();
+    loop {
+        let reached = flood_fill(stage, spawn);
+
+        let mut orphan = None;
+        'search: for x in 0..dims.x {
+            for y in 0..dims.y {
+                let pos = IVec2::new(x, y);
+                if is_open(stage, pos) && !reached[x as usize][y as usize] {
+                    orphan = Some(pos);
+                    break 'search;
+                }
+            }
+        }
+        let orphan = match orphan {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        // closest reached tile by manhattan distance
+        let mut target = spawn;
+        let mut best = i32::MAX;
+        for x in 0..dims.x {
+            for y in 0..dims.y {
+                if reached[x as usize][y as usize] {
+                    let dist = (x - orphan.x).abs() + (y - orphan.y).abs();
+                    if dist < best {
+                        best = dist;
+                        target = IVec2::new(x, y);
+                    }
+                }
+            }
+        }
+
+        let mut pos = orphan;
+        while pos.x != target.x {
+            pos.x += (target.x - pos.x).signum();
+            carve(stage, pos);
+        }
+        while pos.y != target.y {
+            pos.y += (target.y - pos.y).signum();
+            carve(stage, pos);
+        }
+    }
+}
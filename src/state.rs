@@ -1,20 +1,41 @@
 use glam::IVec2;
 
 use crate::{
+    backpack::BackpackView,
+    containers::ContainerView,
+    crafting::Crafting,
+    day_night::Clock,
     entity::VID,
     entity_manager::EntityManager,
+    ground::PickupMenu,
     inputs::{
         MenuInputDebounceTimers, MenuInputs, MouseInputs, PlayingInputDebounceTimers, PlayingInputs,
     },
+    inventory_mouse::InventoryDrag,
+    lighting::Lighting,
+    loot::LootTables,
     particle::Particles,
+    render_map::MapView,
+    shop::{Shop, Upgrades},
     stage::Stage,
+    stage_gen::StageLayout,
+    train::Timetable,
+    waves::{RunStats, Waves},
+    weather::Weather,
 };
 
 pub enum Mode {
     Title,
     Settings,
     VideoSettings,
+    StagePreview,
     Playing,
+    Shop,
+    Crafting,
+    Pickup,
+    Container,
+    Backpack,
+    Map,
     GameOver,
     Win,
 }
@@ -42,6 +63,8 @@ pub struct State {
     pub win: bool,
 
     pub points: u32,
+    /// Spent in the shop, earned alongside points.
+    pub coins: u32,
     pub deaths: u32,
     pub frame_pause: u32,
 
@@ -49,6 +72,24 @@ pub struct State {
     pub player_vid: Option<VID>,
     pub particles: Particles,
     pub stage: Stage,
+    pub stage_layout: StageLayout,
+    /// If set, the next stage is generated from this seed instead of a random one.
+    pub next_stage_seed: Option<u32>,
+    pub timetable: Timetable,
+    pub waves: Waves,
+    pub run_stats: RunStats,
+    pub clock: Clock,
+    pub weather: Weather,
+    pub lighting: Lighting,
+    pub upgrades: Upgrades,
+    pub shop: Shop,
+    pub crafting: Crafting,
+    pub loot: LootTables,
+    pub pickup_menu: PickupMenu,
+    pub container_view: ContainerView,
+    pub backpack_view: BackpackView,
+    pub inventory_drag: Option<InventoryDrag>,
+    pub map_view: MapView,
 
     pub spatial_grid: Vec<Vec<Vec<VID>>>,
 
@@ -80,6 +121,7 @@ impl State {
             win: false,
 
             points: 0,
+            coins: 0,
             deaths: 0,
             frame_pause: 0,
 
@@ -88,6 +130,23 @@ impl State {
             particles: Particles::new(),
 
             stage: Stage::new(crate::stage::StageType::TestArena, 64, 64),
+            stage_layout: StageLayout::new(),
+            next_stage_seed: None,
+            timetable: Timetable::new(),
+            waves: Waves::new(),
+            run_stats: RunStats::new(),
+            clock: Clock::new(),
+            weather: Weather::new(),
+            lighting: Lighting::new(0, 0),
+            upgrades: Upgrades::new(),
+            shop: Shop::new(),
+            crafting: Crafting::new(),
+            loot: LootTables::new(),
+            pickup_menu: PickupMenu::new(),
+            container_view: ContainerView::new(),
+            backpack_view: BackpackView::new(),
+            inventory_drag: None,
+            map_view: MapView::new(),
 
             spatial_grid: vec![vec![vec![]; 64]; 64], // Adjust size as needed
             rebuild_render_texture: true,
@@ -0,0 +1,271 @@
+/* Status effects.
+   Lasting conditions on an entity, each with a time left and, for the ones that deal damage,
+   a tick timer. How a new dose combines with one already running depends on the effect:
+   bleeding stacks up to a limit, the rest keep whichever would last longer.
+
+   Where they come from:
+   - Bleeding and infection: zombie scratches, by chance.
+   - Burning: walking or getting knocked into a fire.
+   - Stunned: heavy hits, on anything that can be stunned.
+   - Slowed: wading through mud.
+   Bandages and bandaids stop bleeding, a medkit also clears infection.
+   The player's effects show in the HUD, everyone else's over their heads.
+*/
+
+use glam::{IVec2, Vec2};
+use rand::random_range;
+
+use crate::{
+    audio::Audio,
+    damage::{mitigate, DamageType},
+    entity::{EntityType, VID},
+    particle_templates::blood_puddle,
+    sprite::Sprite,
+    stage::TileLayer,
+    state::State,
+    step::TIMESTEP,
+    tile::Tile,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    Bleeding,
+    Burning,
+    Stunned,
+    Slowed,
+    Infected,
+}
+
+impl StatusKind {
+    pub fn icon(self) -> Sprite {
+        match self {
+            StatusKind::Bleeding => Sprite::StatusBleeding,
+            StatusKind::Burning => Sprite::StatusBurning,
+            StatusKind::Stunned => Sprite::StatusStunned,
+            StatusKind::Slowed => Sprite::StatusSlowed,
+            StatusKind::Infected => Sprite::StatusInfected,
+        }
+    }
+
+    /// How many doses can pile up at once.
+    fn max_stacks(self) -> u32 {
+        match self {
+            StatusKind::Bleeding => 3,
+            _ => 1,
+        }
+    }
+
+    /// Seconds between ticks, and damage per stack each tick. None for effects that don't hurt.
+    fn damage_tick(self) -> Option<(f32, u32)> {
+        match self {
+            StatusKind::Bleeding => Some((1.0, 1)),
+            StatusKind::Burning => Some((0.5, 2)),
+            StatusKind::Infected => Some((5.0, 1)),
+            StatusKind::Stunned | StatusKind::Slowed => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Seconds left.
+    pub remaining: f32,
+    pub stacks: u32,
+    /// Seconds until it next deals damage.
+    pub tick: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn new() -> StatusEffects {
+        StatusEffects {
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    /// Adds a dose. Stacking effects gain a stack and start their timer over, the others
+    /// keep whichever runs out later.
+    pub fn apply(&mut self, kind: StatusKind, duration: f32) {
+        if let Some(effect) = self.effects.iter_mut().find(|e| e.kind == kind) {
+            if effect.stacks < kind.max_stacks() {
+                effect.stacks += 1;
+                effect.remaining = duration;
+            } else {
+                effect.remaining = effect.remaining.max(duration);
+            }
+            return;
+        }
+        let tick = kind.damage_tick().map_or(0.0, |(interval, _)| interval);
+        self.effects.push(StatusEffect {
+            kind,
+            remaining: duration,
+            stacks: 1,
+            tick,
+        });
+    }
+
+    /// Takes one stack off, removing the effect once none are left. Returns false if it
+    /// wasn't there.
+    pub fn reduce(&mut self, kind: StatusKind) -> bool {
+        let index = match self.effects.iter().position(|e| e.kind == kind) {
+            Some(index) => index,
+            None => return false,
+        };
+        self.effects[index].stacks -= 1;
+        if self.effects[index].stacks == 0 {
+            self.effects.remove(index);
+        }
+        true
+    }
+
+    /// Clears every stack of an effect. Returns false if it wasn't there.
+    pub fn cure(&mut self, kind: StatusKind) -> bool {
+        let before = self.effects.len();
+        self.effects.retain(|e| e.kind != kind);
+        self.effects.len() != before
+    }
+}
+
+/// Rolls for what a zombie scratch leaves behind.
+pub fn apply_scratch_effects(state: &mut State, vid: VID) {
+    const BLEED_CHANCE: f32 = 0.35;
+    const BLEED_SECONDS: f32 = 8.0;
+    const INFECTION_CHANCE: f32 = 0.1;
+    const INFECTION_SECONDS: f32 = 90.0;
+
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        // zombies have nothing left to bleed or catch
+        if entity.type_ == EntityType::Zombie {
+            return;
+        }
+        if random_range(0.0..1.0) < BLEED_CHANCE {
+            entity.status.apply(StatusKind::Bleeding, BLEED_SECONDS);
+        }
+        if random_range(0.0..1.0) < INFECTION_CHANCE {
+            entity.status.apply(StatusKind::Infected, INFECTION_SECONDS);
+        }
+    }
+}
+
+/// Sets an entity alight if the tile it just ran into is a fire. Fires can't be walked through,
+/// so bumping into one is as close as anything gets.
+pub fn touch_fire(state: &mut State, vid: VID, tile_pos: IVec2) {
+    const BURN_SECONDS: f32 = 4.0;
+
+    let is_fire = state
+        .stage
+        .get_layer_tile(
+            TileLayer::Structure,
+            tile_pos.x as usize,
+            tile_pos.y as usize,
+        )
+        .is_some_and(|td| td.tile == Tile::Campfire);
+    if !is_fire {
+        return;
+    }
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        if matches!(
+            entity.type_,
+            EntityType::Player | EntityType::Zombie | EntityType::Chicken
+        ) {
+            entity.status.apply(StatusKind::Burning, BURN_SECONDS);
+        }
+    }
+}
+
+/// Knocks an entity senseless for a moment, if it can be.
+pub fn stun(state: &mut State, vid: VID, duration: f32) {
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        if entity.can_be_stunned {
+            entity.status.apply(StatusKind::Stunned, duration);
+        }
+    }
+}
+
+pub fn is_stunned(state: &State, vid: VID) -> bool {
+    state
+        .entity_manager
+        .get_entity(vid)
+        .is_some_and(|e| e.status.has(StatusKind::Stunned))
+}
+
+/// Picks up effects from the ground underfoot, deals damage over time and runs out the timers.
+pub fn step_status_effects(state: &mut State, _audio: &mut Audio, vid: VID) {
+    let (pos, type_) = match state.entity_manager.get_entity(vid) {
+        Some(entity) if entity.health > 0 => (entity.pos, entity.type_),
+        _ => return,
+    };
+    if !matches!(
+        type_,
+        EntityType::Player | EntityType::Zombie | EntityType::Chicken
+    ) {
+        return;
+    }
+
+    // --- Terrain ---
+    let tile_pos = pos.as_ivec2();
+    let (x, y) = (tile_pos.x as usize, tile_pos.y as usize);
+    let structure = state
+        .stage
+        .get_layer_tile(TileLayer::Structure, x, y)
+        .map(|td| td.tile);
+    let floor = state
+        .stage
+        .get_layer_tile(TileLayer::Floor, x, y)
+        .map(|td| td.tile);
+    // a rail or anything else built over it keeps feet out of the muck
+    let wading = structure.is_none_or(|t| t.empty()) && floor == Some(Tile::Mud);
+
+    // rain puts burning things out quicker
+    let burn_rate = 1.0 + 2.0 * state.weather.rain;
+
+    let mut bled = false;
+    if let Some(entity) = state.entity_manager.get_entity_mut(vid) {
+        const SLOW_SECONDS: f32 = 0.5;
+        if wading {
+            entity.status.apply(StatusKind::Slowed, SLOW_SECONDS);
+        }
+
+        // --- Ticks ---
+        let mut damage = 0;
+        let mut fire = 0;
+        for effect in &mut entity.status.effects {
+            let rate = if effect.kind == StatusKind::Burning {
+                burn_rate
+            } else {
+                1.0
+            };
+            effect.remaining -= TIMESTEP * rate;
+            if let Some((interval, per_stack)) = effect.kind.damage_tick() {
+                effect.tick -= TIMESTEP;
+                if effect.tick <= 0.0 {
+                    effect.tick += interval;
+                    if effect.kind == StatusKind::Burning {
+                        fire += per_stack * effect.stacks;
+                    } else {
+                        damage += per_stack * effect.stacks;
+                    }
+                    bled |= effect.kind == StatusKind::Bleeding;
+                }
+            }
+        }
+        entity.status.effects.retain(|e| e.remaining > 0.0);
+        // fire cares what you're made of, the rest is already under the skin
+        damage += mitigate(fire, DamageType::Fire, &entity.resistances, 0);
+        entity.health = entity.health.saturating_sub(damage);
+    }
+
+    if bled {
+        const DRIP_SIZE: f32 = 0.15;
+        blood_puddle(&mut state.particles, pos + Vec2::new(0.0, 0.5), DRIP_SIZE);
+    }
+}
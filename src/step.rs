@@ -7,22 +7,33 @@ pub const TIMESTEP: f32 = 1.0 / FRAMES_PER_SECOND as f32;
 
 use crate::{
     audio::{Audio, SoundEffect},
+    containers::{container_at, open_container, step_corpse_decay},
+    day_night::step_clock,
     entity::{self, Entity, EntityType, StepSound, VID},
     entity_behavior::{
-        die_if_health_zero, growl_sometimes, indiscriminately_attack_nearby, move_entity_on_grid,
-        ready_to_move, step_attack_cooldown, step_inventory_item_cooldowns, step_move_cooldown,
-        step_rail_layer, step_train, wander,
+        chase_target, die_if_health_zero, dispatch_trains, growl_sometimes,
+        indiscriminately_attack_nearby, move_entity_on_grid, notice_target, ready_to_move,
+        step_attack_cooldown, step_crossings, step_inventory_item_cooldowns, step_move_cooldown,
+        step_rail_layer, step_spike_traps, step_train, wander,
     },
     entity_manager,
-    entity_templates::init_as_item,
     graphics::Graphics,
+    ground::{drop_item, ground_items, pick_up_item},
     item::Item,
     item_use,
+    lighting::step_lighting,
     particle_templates::spawn_weather_clouds,
+    physics::step_knockback,
+    rail,
     render::TILE_SIZE,
+    settings::INTERACT_DEBOUNCE_INTERVAL,
     stage::{flip_stage_tiles, TileData},
     state::{Mode, State},
+    status::step_status_effects,
     tile::{self, can_build_on, flip_tile, tile_shake_attenuation, Tile},
+    train::{self, occupied_tiles},
+    waves::step_waves,
+    weather::step_weather,
 };
 
 pub const PLACE_TILE_COOLDOWN: f32 = 0.05; // Cooldown for placing tiles in seconds
@@ -144,10 +155,8 @@ fn step_playing(state: &mut State, audio: &mut Audio, graphics: &mut Graphics) {
     /*
        if no item in the selected slot, do nothing
        if theres an item in the players inventory in the selected slot
-       check the tile to see if it has an entity on it
-       go through the entities on the tile to see if any of them are item type
-       if yes, cant drop
-       else, drop the item, and remove it from the inventory
+       drop it onto the pile on the player's tile, spilling over if that's full
+       remove it from the inventory once it's on the ground
     */
     let mut item_to_try_to_drop: Option<Item> = None;
     let mut drop_location: Option<IVec2> = None;
@@ -173,118 +182,90 @@ fn step_playing(state: &mut State, audio: &mut Audio, graphics: &mut Graphics) {
         }
     }
     if let (Some(item), Some(location)) = (item_to_try_to_drop, drop_location) {
-        // Check if the tile is empty or has no item entities
-        let tile_pos = location;
-        let entities_on_tile = state.spatial_grid[tile_pos.x as usize][tile_pos.y as usize]
-            .iter()
-            .filter_map(|vid| state.entity_manager.get_entity(*vid))
-            .collect::<Vec<&Entity>>();
-
-        if entities_on_tile.iter().all(|e| e.type_ != EntityType::Item) {
-            // make a new entity
-            if let Some(new_entity_vid) = state.entity_manager.new_entity() {
-                if let Some(entity) = state.entity_manager.get_entity_mut(new_entity_vid) {
-                    // Initialize the new entity as an item with the dropped item
-                    init_as_item(entity, item);
-                    entity.pos =
-                        Vec2::new(tile_pos.x as f32, tile_pos.y as f32) + Vec2::new(0.5, 0.5);
-                    state.add_entity_to_grid(new_entity_vid, tile_pos);
-
-                    // remove item from player inventory
-                    if let Some(player_vid) = state.player_vid {
-                        if let Some(player) = state.entity_manager.get_entity_mut(player_vid) {
-                            player.inventory.remove_selected_entry();
-                        }
-                    }
-                }
+        let dropped = drop_item(state, audio, item, location);
+        // remove what made it onto the ground from player inventory
+        if let Some(player_vid) = state.player_vid {
+            if let Some(player) = state.entity_manager.get_entity_mut(player_vid) {
+                let slot = player.inventory.selected_index;
+                player.inventory.remove_count_from_slot(slot, dropped);
             }
-        } else {
-            audio.play_sound_effect(SoundEffect::CantUse);
         }
     }
 
     // player item pickup logic
     /*
-       if item already in the selected slot, do nothing
-       if theres no item in the players inventory in the selected slot
-       check the tile to see if it has an entity on it
-       go through the entities on the tile to see if any of them are item type
-       if yes, add that item to the inventory
-       delete the item entity from the grid
-       else, do nothing
+       check the tile under the player for items
+       if there's only one stack there, pick it straight up
+       if there's a pile, open the pickup menu to choose from it
     */
-    let mut item_to_try_to_pickup: Option<Item> = None;
-    let mut entity_of_item: Option<VID> = None;
-    let mut location_to_pickup: Option<IVec2> = None;
     if state.playing_inputs.pick_up {
-        if let Some(player_vid) = state.player_vid {
-            if let Some(player) = state.entity_manager.get_entity_mut(player_vid) {
-                // Check if the tile has an item entity
-                let tile_pos = player.pos.as_ivec2();
-                let entities_on_tile = state.spatial_grid[tile_pos.x as usize][tile_pos.y as usize]
-                    .iter()
-                    .filter_map(|vid| state.entity_manager.get_entity(*vid))
-                    .collect::<Vec<&Entity>>();
-                // Find the first item entity on the tile
-                if let Some(item_entity) = entities_on_tile
-                    .iter()
-                    .find(|e| e.type_ == EntityType::Item)
-                {
-                    item_to_try_to_pickup = item_entity.item;
-                    entity_of_item = Some(item_entity.vid);
-                    location_to_pickup = Some(tile_pos);
-                } else {
-                    // No item entity found on the tile, play can't use sound
-                    audio.play_sound_effect(SoundEffect::CantUse);
+        state.playing_input_debounce_timers.pick_up = INTERACT_DEBOUNCE_INTERVAL;
+        if let Some(tile_pos) = state
+            .player_vid
+            .and_then(|vid| state.entity_manager.get_entity(vid))
+            .map(|player| player.pos.as_ivec2())
+        {
+            let pile = ground_items(state, tile_pos);
+            match pile.len() {
+                0 => audio.play_sound_effect(SoundEffect::CantUse),
+                1 => {
+                    pick_up_item(state, audio, pile[0]);
+                }
+                _ => {
+                    state.pickup_menu.tile = tile_pos;
+                    state.pickup_menu.selected = 0;
+                    state.mode = Mode::Pickup;
                 }
             }
         }
     }
-    if let (Some(item), Some(location), Some(entity_of_item_vid)) =
-        (item_to_try_to_pickup, location_to_pickup, entity_of_item)
-    {
-        // Check if the item can be added to the inventory
-        if let Some(player_vid) = state.player_vid {
-            if let Some(player) = state.entity_manager.get_entity_mut(player_vid) {
-                let item_to_put_on_ground = player.inventory.insert(item);
-                // remove the item entity from the grid, and deactivate it
-                if state
-                    .entity_manager
-                    .get_entity_mut(entity_of_item_vid)
-                    .is_some()
-                {
-                    state.remove_entity_from_grid(entity_of_item_vid, location);
-                    state.entity_manager.set_inactive_vid(entity_of_item_vid);
-                }
 
-                // if an item was swapped out, drop it on the ground
-                if let Some(item_to_drop_put_on_ground) = item_to_put_on_ground {
-                    // Check if the tile is empty or has no item entities
-                    let tile_pos = location;
-                    let entities_on_tile = state.spatial_grid[tile_pos.x as usize]
-                        [tile_pos.y as usize]
-                        .iter()
-                        .filter_map(|vid| state.entity_manager.get_entity(*vid))
-                        .collect::<Vec<&Entity>>();
-
-                    if entities_on_tile.iter().all(|e| e.type_ != EntityType::Item) {
-                        // Create a new entity for the dropped item
-                        if let Some(new_entity_vid) = state.entity_manager.new_entity() {
-                            if let Some(entity) =
-                                state.entity_manager.get_entity_mut(new_entity_vid)
-                            {
-                                init_as_item(entity, item_to_drop_put_on_ground);
-                                entity.pos = Vec2::new(tile_pos.x as f32, tile_pos.y as f32)
-                                    + Vec2::new(0.5, 0.5);
-                                state.add_entity_to_grid(new_entity_vid, tile_pos);
-                            }
-                        }
-                    } else {
-                        // print a swapped item was deleted...
-                        audio.play_sound_effect(SoundEffect::CantUse);
-                    }
+    // --- Player Interact Logic ---
+    // loads the selected item onto a train waiting next to the player,
+    // otherwise opens a container or throws a switch underfoot or next to the player
+    if state.playing_inputs.interact {
+        if let Some(player) = state
+            .player_vid
+            .and_then(|vid| state.entity_manager.get_entity(vid))
+        {
+            let player_tile_pos = player.pos.as_ivec2();
+            let targets = [
+                player_tile_pos,
+                player_tile_pos + IVec2::new(0, -1),
+                player_tile_pos + IVec2::new(1, 0),
+                player_tile_pos + IVec2::new(0, 1),
+                player_tile_pos + IVec2::new(-1, 0),
+            ];
+            let cargo = player
+                .inventory
+                .get(player.inventory.selected_index)
+                .filter(|entry| entry.item.droppable)
+                .map(|entry| entry.item);
+
+            let loaded = cargo.is_some_and(|item| {
+                targets
+                    .iter()
+                    .any(|pos| train::load_cargo(state, *pos, item))
+            });
+            if loaded {
+                if let Some(player) = state
+                    .player_vid
+                    .and_then(|vid| state.entity_manager.get_entity_mut(vid))
+                {
+                    player.inventory.remove_selected_entry();
                 }
+                audio.play_sound_effect(SoundEffect::Drop);
+            } else if let Some(vid) = targets.iter().find_map(|pos| container_at(state, *pos)) {
+                open_container(state, vid);
+            } else if targets
+                .iter()
+                .any(|pos| rail::toggle_switch(&mut state.stage, *pos))
+            {
+                audio.play_sound_effect(SoundEffect::HitBlock1);
+            } else {
+                audio.play_sound_effect(SoundEffect::CantUse);
             }
+            state.playing_input_debounce_timers.interact = INTERACT_DEBOUNCE_INTERVAL;
         }
     }
 
@@ -347,20 +328,44 @@ fn step_playing(state: &mut State, audio: &mut Audio, graphics: &mut Graphics) {
     // // Consume the click so it doesn't trigger again next frame.
     // state.mouse_inputs.left = false;
 
+    // --- Time of Day ---
+    step_clock(state, audio);
+
+    // --- Waves ---
+    step_waves(state, audio);
+    if !matches!(state.mode, Mode::Playing) {
+        return; // the last wave just fell
+    }
+
+    // --- Trains ---
+    dispatch_trains(state, audio);
+    step_crossings(state, audio);
+
+    // --- Traps ---
+    step_spike_traps(state, audio);
+
     // --- AI / Other Entity Logic ---
     for vid in state.entity_manager.get_active_vids() {
         step_move_cooldown(state, vid);
+        step_knockback(state, audio, vid);
         wander(state, audio, vid);
+        notice_target(state, vid);
+        chase_target(state, audio, vid);
         entity_shake_attenuation(state, vid);
         growl_sometimes(state, audio, vid);
         indiscriminately_attack_nearby(state, audio, vid);
+        step_status_effects(state, audio, vid);
         die_if_health_zero(state, audio, vid);
         step_attack_cooldown(state, vid);
         step_inventory_item_cooldowns(state, vid);
         step_rail_layer(state, audio, vid);
         step_train(state, audio, vid);
+        step_corpse_decay(state, audio, vid);
     }
 
+    // --- Light & Sight ---
+    step_lighting(state);
+
     // loop through tile coords
     for y in 0..state.stage.get_height() {
         for x in 0..state.stage.get_width() {
@@ -373,20 +378,24 @@ fn step_playing(state: &mut State, audio: &mut Audio, graphics: &mut Graphics) {
     // flip tile variants
     flip_stage_tiles(state);
 
+    // --- Weather ---
+    step_weather(state, audio, graphics);
     spawn_weather_clouds(state, graphics, state.cloud_density);
 
     // --- Entity Cleanup ("Sweep" Phase) ---
     // At the very end of the step, we remove all entities that were marked for destruction.
-    let vids_to_remove: Vec<(VID, IVec2)> = state
+    let vids_to_remove: Vec<(VID, Vec<IVec2>)> = state
         .entity_manager
         .iter()
         .filter(|e| e.marked_for_destruction && e.active)
-        .map(|e| (e.vid, e.pos.as_ivec2()))
+        .map(|e| (e.vid, occupied_tiles(e)))
         .collect();
 
-    for (vid, pos) in vids_to_remove {
-        // Remove from the spatial grid to prevent ghost collisions
-        state.remove_entity_from_grid(vid, pos);
+    for (vid, tiles) in vids_to_remove {
+        // Remove from the spatial grid to prevent ghost collisions, trains sit on several tiles
+        for pos in tiles {
+            state.remove_entity_from_grid(vid, pos);
+        }
         // Deactivate the entity in the manager, freeing up its ID
         state.entity_manager.set_inactive_vid(vid);
 
@@ -425,30 +434,6 @@ pub fn entity_shake_attenuation(state: &mut State, vid: VID) {
     }
 }
 
-pub fn drop_item(state: &mut State, audio: &mut Audio, item: Item, pos: IVec2) -> Option<VID> {
-    // Check if the tile is empty or has no item entities
-    let entities_on_tile = state.spatial_grid[pos.x as usize][pos.y as usize]
-        .iter()
-        .filter_map(|vid| state.entity_manager.get_entity(*vid))
-        .collect::<Vec<&Entity>>();
-
-    if entities_on_tile.iter().any(|e| e.type_ == EntityType::Item) {
-        audio.play_sound_effect(SoundEffect::CantUse);
-        return None; // Cannot drop item, tile is occupied by an item entity
-    }
-
-    // Create a new entity for the dropped item
-    if let Some(new_entity_vid) = state.entity_manager.new_entity() {
-        if let Some(entity) = state.entity_manager.get_entity_mut(new_entity_vid) {
-            init_as_item(entity, item);
-            entity.pos = Vec2::new(pos.x as f32, pos.y as f32) + Vec2::new(0.5, 0.5);
-            state.add_entity_to_grid(new_entity_vid, pos);
-            return Some(new_entity_vid);
-        }
-    }
-    None
-}
-
 pub fn set_inventory_index_from_numpad(state: &mut State) {
     if let Some(player_vid) = state.player_vid {
         if let Some(player) = state.entity_manager.get_entity_mut(player_vid) {
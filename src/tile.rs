@@ -2,10 +2,11 @@ use glam::{IVec2, Vec2};
 
 use crate::{
     audio::{Audio, SoundEffect},
-    entity::DamageType,
+    damage::{mitigate, tile_resistances, DamageType},
+    loot::{drop_loot, tile_loot_source},
     particle_templates::debris_splatter,
     sprite::Sprite,
-    stage::TileData,
+    stage::{TileData, TileLayer, TILE_LAYERS},
     state::State,
 };
 
@@ -17,19 +18,68 @@ pub enum Tile {
     Ruin,
     Water,
     Rail,
+    RailSwitch,
+    Station,
+    Crossing,
+    Door,
+    Mud,
+    Rock,
+    Spikes,
+    Torch,
+    Campfire,
+    /// A fire that's gone out.
+    Ashes,
 }
 
 impl Tile {
     pub fn walkable(self) -> bool {
-        matches!(self, Tile::None | Tile::Grass | Tile::Ruin | Tile::Rail)
+        matches!(
+            self,
+            Tile::None
+                | Tile::Grass
+                | Tile::Ruin
+                | Tile::Rail
+                | Tile::RailSwitch
+                | Tile::Station
+                | Tile::Crossing
+                | Tile::Door
+                | Tile::Mud
+                | Tile::Spikes
+                | Tile::Ashes
+        )
+    }
+
+    /// Whether light and sight pass through this tile.
+    pub fn transparent(self) -> bool {
+        !matches!(self, Tile::Wall | Tile::Door | Tile::Rock)
     }
 
     pub fn empty(self) -> bool {
         matches!(self, Tile::None)
     }
 
+    /// Floors that structures can be placed on top of.
     pub fn can_build_on(self) -> bool {
-        matches!(self, Tile::None | Tile::Grass)
+        matches!(self, Tile::None | Tile::Grass | Tile::Mud | Tile::Water)
+    }
+
+    /// Which stage layer this kind of tile lives on.
+    pub fn layer(self) -> TileLayer {
+        match self {
+            Tile::None | Tile::Grass | Tile::Water | Tile::Mud => TileLayer::Floor,
+            Tile::Wall
+            | Tile::Door
+            | Tile::Rail
+            | Tile::RailSwitch
+            | Tile::Station
+            | Tile::Crossing
+            | Tile::Rock
+            | Tile::Spikes
+            | Tile::Torch
+            | Tile::Campfire
+            | Tile::Ashes => TileLayer::Structure,
+            Tile::Ruin => TileLayer::Overlay,
+        }
     }
 }
 
@@ -44,13 +94,9 @@ pub fn is_tile_walkable(state: &State, tile_coords: IVec2) -> bool {
         return false; // Treat out-of-bounds as not buildable.
     }
 
-    let tile_walkable = match state
+    let tile_walkable = state
         .stage
-        .get_tile_type(tile_coords.x as usize, tile_coords.y as usize)
-    {
-        Some(tile) => tile.walkable(),
-        None => false, // If the tile doesn't exist, treat it as not walkable.
-    };
+        .is_walkable(tile_coords.x as usize, tile_coords.y as usize);
 
     tile_walkable && !is_tile_occupied(state, tile_coords)
 }
@@ -66,13 +112,9 @@ pub fn can_build_on(state: &State, tile_coords: IVec2) -> bool {
         return false; // Treat out-of-bounds as not buildable.
     }
 
-    let tile_buildable_upon = match state
+    let tile_buildable_upon = state
         .stage
-        .get_tile_type(tile_coords.x as usize, tile_coords.y as usize)
-    {
-        Some(tile) => tile.can_build_on(),
-        None => false, // If the tile doesn't exist, treat it as not buildable.
-    };
+        .can_build_on(tile_coords.x as usize, tile_coords.y as usize);
     tile_buildable_upon && !is_tile_occupied(state, tile_coords)
 }
 
@@ -86,13 +128,13 @@ pub fn is_tile_empty(state: &State, tile_coords: IVec2) -> bool {
     {
         return false; // Treat out-of-bounds as not empty.
     }
-    let tile_empty = match state
-        .stage
-        .get_tile_type(tile_coords.x as usize, tile_coords.y as usize)
-    {
-        Some(tile) => tile.empty(),
-        None => false, // If the tile doesn't exist, treat it as occupied.
-    };
+    let (x, y) = (tile_coords.x as usize, tile_coords.y as usize);
+    let tile_empty = TILE_LAYERS.iter().all(|&layer| {
+        state
+            .stage
+            .get_layer_tile(layer, x, y)
+            .is_some_and(|td| td.tile.empty())
+    });
     !is_tile_occupied(state, tile_coords) && tile_empty
 }
 
@@ -128,7 +170,16 @@ pub fn get_tile_variants(tile_data: &TileData) -> Vec<Sprite> {
         Tile::Wall => vec![Sprite::Wall],
         Tile::Ruin => vec![Sprite::Ruin],
         Tile::Water => vec![Sprite::Water3, Sprite::Water4],
-        Tile::Rail => vec![Sprite::Rail],
+        Tile::Rail | Tile::RailSwitch | Tile::Station => vec![Sprite::Rail],
+        // the second variant is the warning light's off phase
+        Tile::Crossing => vec![Sprite::Rail, Sprite::Rail],
+        Tile::Door => vec![Sprite::Door],
+        Tile::Mud => vec![Sprite::Mud],
+        Tile::Rock => vec![Sprite::Rock],
+        Tile::Spikes => vec![Sprite::Spikes],
+        Tile::Torch => vec![Sprite::Torch],
+        Tile::Campfire => vec![Sprite::Campfire],
+        Tile::Ashes => vec![Sprite::Ashes],
         _ => vec![],
     }
 }
@@ -144,42 +195,29 @@ pub fn get_tile_sprite(tile_data: &TileData) -> Option<Sprite> {
 }
 
 /// Determines if a given tile can be damaged by a given damage type.
-/// This allows for future expansion (e.g., some tiles immune to punch, weak to explosion).
+/// Breakable tiles take anything they aren't immune to in `tile_resistances`.
 pub fn can_damage_tile(tile_data: &TileData, damage_type: DamageType) -> bool {
-    if !tile_data.breakable {
-        return false;
-    }
-
-    // For now, let's say Punches can damage any breakable tile.
-    match damage_type {
-        DamageType::Punch => true,
-        DamageType::Scratch => true, // Zombies should also be able to claw at walls
-        _ => false,
-    }
+    tile_data.breakable && !tile_resistances(tile_data.tile).immune_to(damage_type)
 }
 
 /// Called when a tile's HP is reduced to 0.
 /// Handles changing the tile, dropping items, or other break effects.
 pub fn on_tile_break(state: &mut State, audio: &mut Audio, tile_pos: IVec2, tile_data: &TileData) {
+    let (x, y) = (tile_pos.x as usize, tile_pos.y as usize);
+
+    // Breaking a structure reveals the floor underneath it.
+    let layer = tile_data.tile.layer();
+    state.stage.clear_layer_tile(layer, x, y);
+
     // Behavior depends on the type of tile that broke.
-    match tile_data.tile {
-        Tile::Wall => {
-            // A broken wall becomes a non-breakable 'Ruin' tile.
-            let mut tile = TileData::default();
-            tile.tile = Tile::Ruin;
-            state
-                .stage
-                .set_tile(tile_pos.x as usize, tile_pos.y as usize, tile);
-            // TODO: In the future, you could drop a "stone" item here.
-        }
-        _ => {
-            // By default, most broken tiles just become empty space.
-            state.stage.set_tile(
-                tile_pos.x as usize,
-                tile_pos.y as usize,
-                TileData::default(),
-            );
-        }
+    if tile_data.tile == Tile::Wall {
+        // A broken wall leaves some rubble lying around.
+        let mut rubble = TileData::default();
+        rubble.tile = Tile::Ruin;
+        state.stage.set_tile(x, y, rubble);
+    }
+    if let Some(source) = tile_loot_source(tile_data.tile) {
+        drop_loot(state, audio, source, tile_pos);
     }
 
     // Play the appropriate break sound effect.
@@ -252,6 +290,10 @@ pub fn damage_tile(
     // Trigger the visual/audio "hit" effect.
     on_tile_damage(state, audio, tile_pos, attacker_pos);
 
+    // Tiles have no armor, just what they're made of.
+    let resistances = tile_resistances(tile_data.tile);
+    let damage = mitigate(damage as u32, damage_type, &resistances, 0).min(u8::MAX as u32) as u8;
+
     // Get a mutable copy of the tile data to work with.
     let mut tile_data_mut = tile_data;
     let new_hp = tile_data_mut.hp.saturating_sub(damage);
@@ -285,21 +327,34 @@ pub fn tile_damage_sound_lookup(tile: &Tile) -> SoundEffect {
 }
 
 pub fn flip_tile(state: &mut State, pos: IVec2) {
-    if let Some(tile_data) = state.stage.get_tile_mut(pos.x as usize, pos.y as usize) {
-        if tile_data.flip_speed > 0 && state.frame % tile_data.flip_speed as u32 == 0 {
-            let new_variant = (tile_data.variant + 1) % get_tile_variants(tile_data).len() as u8;
-            tile_data.variant = new_variant;
+    for layer in TILE_LAYERS {
+        if let Some(tile_data) =
+            state
+                .stage
+                .get_layer_tile_mut(layer, pos.x as usize, pos.y as usize)
+        {
+            if tile_data.flip_speed > 0 && state.frame % tile_data.flip_speed as u32 == 0 {
+                let new_variant =
+                    (tile_data.variant + 1) % get_tile_variants(tile_data).len() as u8;
+                tile_data.variant = new_variant;
+            }
         }
     }
 }
 
 pub fn tile_shake_attenuation(state: &mut State, pos: IVec2) {
-    // Get the tile at the given position
-    if let Some(tile_data) = state.stage.get_tile_mut(pos.x as usize, pos.y as usize) {
-        // Reduce the shake value of the tile
-        if tile_data.shake > 0.0 {
-            let new_shake = (tile_data.shake - 0.01).max(0.0);
-            tile_data.shake = new_shake;
+    // Get the tiles at the given position, every layer shakes together
+    for layer in TILE_LAYERS {
+        if let Some(tile_data) =
+            state
+                .stage
+                .get_layer_tile_mut(layer, pos.x as usize, pos.y as usize)
+        {
+            // Reduce the shake value of the tile
+            if tile_data.shake > 0.0 {
+                let new_shake = (tile_data.shake - 0.01).max(0.0);
+                tile_data.shake = new_shake;
+            }
         }
     }
 }
@@ -327,20 +382,24 @@ pub fn tile_shake_area_at(state: &mut State, pos: IVec2, magnitude: f32, dist: f
             let distance = (tile_pos - pos).as_vec2().length();
 
             if distance <= dist {
-                // Now we can use the more direct get_tile_mut
-                if let Some(tile_data) = state.stage.get_tile_mut(x as usize, y as usize) {
-                    // Calculate linear falloff (1.0 at center, 0.0 at edge).
-                    // Avoid division by zero if dist is 0.
-                    let falloff = if dist > 0.0 {
-                        (dist - distance) / dist
-                    } else {
-                        1.0
-                    };
-                    let shake_to_add = falloff * magnitude;
-
-                    if shake_to_add > 0.0 {
-                        // Add the new shake and clamp to a max value to prevent excessive shaking.
-                        tile_data.shake = (tile_data.shake + shake_to_add).min(1.0);
+                // Calculate linear falloff (1.0 at center, 0.0 at edge).
+                // Avoid division by zero if dist is 0.
+                let falloff = if dist > 0.0 {
+                    (dist - distance) / dist
+                } else {
+                    1.0
+                };
+                let shake_to_add = falloff * magnitude;
+
+                if shake_to_add > 0.0 {
+                    for layer in TILE_LAYERS {
+                        if let Some(tile_data) = state
+                            .stage
+                            .get_layer_tile_mut(layer, x as usize, y as usize)
+                        {
+                            // Add the new shake and clamp to a max value to prevent excessive shaking.
+                            tile_data.shake = (tile_data.shake + shake_to_add).min(1.0);
+                        }
                     }
                 }
             }
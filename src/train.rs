@@ -0,0 +1,221 @@
+/* Train consists.
+   A train is one entity that owns every car behind it. The head picks the way along the
+   track and each car steps into the tile the car ahead just left, so the whole train keeps
+   to the rail path and can never come apart. Cars that haven't rolled onto the map yet wait
+   in `pending` and enter behind the tail as it clears the spawn tile.
+
+   Trains stop at every station they pass, wait out their dwell time, and carry cargo
+   between them. Cargo is paid out at any station other than the one it was loaded at.
+*/
+
+use glam::IVec2;
+
+use crate::{
+    entity::{Entity, EntityType},
+    item::Item,
+    sprite::Sprite,
+    state::State,
+};
+
+/// An item stack riding in a car, and the station it got on at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cargo {
+    pub item: Item,
+    pub loaded_at: IVec2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainCar {
+    pub sprite: Sprite,
+    pub pos: IVec2,
+    /// The way the car was moving when it entered its tile.
+    pub direction: IVec2,
+    pub cargo: Option<Cargo>,
+}
+
+impl TrainCar {
+    /// Only the freight cars take cargo, not the head or the caboose.
+    pub fn carries_cargo(&self) -> bool {
+        matches!(self.sprite, Sprite::TrainCarA | Sprite::TrainCarB)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Consist {
+    /// Cars on the map, head first.
+    pub cars: Vec<TrainCar>,
+    /// Cars still waiting to enter the map, next one first.
+    pub pending: Vec<Sprite>,
+    /// Tiles per second.
+    pub speed: f32,
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub braking: f32,
+    /// Fraction of a tile covered since the last step.
+    pub progress: f32,
+    /// Seconds to wait at each station.
+    pub dwell_time: f32,
+    /// Seconds left at the current station, zero while running.
+    pub dwell: f32,
+    /// The station the train last stopped at, so it doesn't stop there again on the way out.
+    pub last_station: Option<IVec2>,
+}
+
+impl Consist {
+    /// A head, `length - 2` alternating cars and a caboose, all waiting behind `pos`.
+    pub fn new(pos: IVec2, direction: IVec2, length: usize) -> Consist {
+        let mut pending = Vec::new();
+        for i in 1..length.max(2) {
+            let sprite = if i == length.max(2) - 1 {
+                Sprite::Caboose
+            } else if i % 2 == 1 {
+                Sprite::TrainCarA
+            } else {
+                Sprite::TrainCarB
+            };
+            pending.push(sprite);
+        }
+
+        Consist {
+            cars: vec![TrainCar {
+                sprite: Sprite::TrainHead,
+                pos,
+                direction,
+                cargo: None,
+            }],
+            pending,
+            speed: 0.0,
+            max_speed: 40.0,
+            acceleration: 20.0,
+            braking: 60.0,
+            progress: 0.0,
+            dwell_time: 8.0,
+            dwell: 0.0,
+            last_station: None,
+        }
+    }
+
+    pub fn head(&self) -> Option<&TrainCar> {
+        self.cars.first()
+    }
+
+    /// Total cars, on the map or not.
+    pub fn len(&self) -> usize {
+        self.cars.len() + self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_dwelling(&self) -> bool {
+        self.dwell > 0.0
+    }
+
+    /// Pulls up at a station: stops the train, starts the dwell timer and unloads
+    /// everything that got on somewhere else. Returns the points earned.
+    pub fn arrive_at_station(&mut self, station: IVec2) -> u32 {
+        self.speed = 0.0;
+        self.progress = 0.0;
+        self.dwell = self.dwell_time;
+        self.last_station = Some(station);
+
+        let mut points = 0;
+        for car in &mut self.cars {
+            if let Some(cargo) = car.cargo {
+                if cargo.loaded_at != station {
+                    points += cargo.item.value * cargo.item.count;
+                    car.cargo = None;
+                }
+            }
+        }
+        points
+    }
+
+    /// How many tiles the train needs to come to a stop from its current speed.
+    pub fn stopping_distance(&self) -> f32 {
+        if self.braking <= 0.0 {
+            return f32::INFINITY;
+        }
+        self.speed * self.speed / (2.0 * self.braking)
+    }
+}
+
+/// Every tile an entity sits on. Trains cover one tile per car, everything else just one.
+pub fn occupied_tiles(entity: &Entity) -> Vec<IVec2> {
+    match &entity.consist {
+        Some(consist) => consist.cars.iter().map(|car| car.pos).collect(),
+        None => vec![entity.pos.as_ivec2()],
+    }
+}
+
+/// Rotation in degrees and horizontal flip for a car. Train sprites face left.
+pub fn car_orientation(direction: IVec2) -> (f32, bool) {
+    match (direction.x, direction.y) {
+        (1, _) => (0.0, true),
+        (_, -1) => (90.0, false),
+        (_, 1) => (270.0, false),
+        _ => (0.0, false),
+    }
+}
+
+/// Trains roll in from the map edge on a fixed interval, alternating which end they start at.
+#[derive(Debug, Clone, Copy)]
+pub struct Timetable {
+    /// Seconds between trains.
+    pub interval: f32,
+    /// Seconds until the next train is due.
+    pub countdown: f32,
+    pub eastbound: bool,
+}
+
+impl Timetable {
+    pub fn new() -> Timetable {
+        Timetable {
+            interval: 45.0,
+            countdown: 20.0,
+            eastbound: true,
+        }
+    }
+}
+
+/// Puts an item into the freight car at `pos` if a train is waiting at a station there.
+/// Returns false if there's no car to load, it's not stopped, or it's already full.
+pub fn load_cargo(state: &mut State, pos: IVec2, item: Item) -> bool {
+    if !state.stage.in_bounds(pos) {
+        return false;
+    }
+    let train_vid = state.spatial_grid[pos.x as usize][pos.y as usize]
+        .iter()
+        .copied()
+        .find(|vid| {
+            state
+                .entity_manager
+                .get_entity(*vid)
+                .is_some_and(|e| e.type_ == EntityType::Train)
+        });
+
+    let train = match train_vid.and_then(|vid| state.entity_manager.get_entity_mut(vid)) {
+        Some(train) => train,
+        None => return false,
+    };
+    let consist = match &mut train.consist {
+        Some(consist) if consist.is_dwelling() => consist,
+        _ => return false,
+    };
+    let station = match consist.last_station {
+        Some(station) => station,
+        None => return false,
+    };
+
+    match consist.cars.iter_mut().find(|car| car.pos == pos) {
+        Some(car) if car.carries_cargo() && car.cargo.is_none() => {
+            car.cargo = Some(Cargo {
+                item,
+                loaded_at: station,
+            });
+            true
+        }
+        _ => false,
+    }
+}
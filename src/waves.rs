@@ -0,0 +1,296 @@
+/* Survival mode.
+   The run alternates between a build phase, where the player gets a breather to put up
+   walls and run cargo, and a combat phase, where a wave of zombies walks in from the map
+   edges and heads for where it last heard the player. Each wave is bigger than the last and mixes in faster
+   and tougher zombies. Clear the last wave to win.
+*/
+
+use glam::{IVec2, Vec2};
+use rand::random_range;
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    damage::DamageType,
+    entity::{Entity, EntityType, Mood, VID},
+    entity_templates::init_as_zombie,
+    loot::LootSource,
+    shop::award_points,
+    sprite::Sprite,
+    state::{Mode, State},
+    step::TIMESTEP,
+    tile::is_tile_walkable,
+    utils::new_york_dist,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavePhase {
+    Build,
+    Combat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZombieKind {
+    Walker,
+    Runner,
+    Brute,
+}
+
+#[derive(Debug, Clone)]
+pub struct Waves {
+    pub phase: WavePhase,
+    /// The wave being fought, or the one coming up during a build phase. Starts at 1.
+    pub wave: u32,
+    pub total_waves: u32,
+    /// Seconds left in the build phase.
+    pub build_timer: f32,
+    /// Zombies still to come this wave, spawned one at a time.
+    pub to_spawn: Vec<ZombieKind>,
+    pub spawn_timer: f32,
+    /// Zombies from this wave that are still up.
+    pub alive: Vec<VID>,
+    /// Seconds until the wave hears where the player is again.
+    pub hunt_timer: f32,
+}
+
+impl Waves {
+    pub fn new() -> Waves {
+        Waves {
+            phase: WavePhase::Build,
+            wave: 1,
+            total_waves: 10,
+            build_timer: 45.0,
+            to_spawn: Vec::new(),
+            spawn_timer: 0.0,
+            alive: Vec::new(),
+            hunt_timer: 0.0,
+        }
+    }
+
+    /// Waves fully cleared so far.
+    pub fn waves_survived(&self) -> u32 {
+        self.wave - 1
+    }
+
+    /// Zombies left to deal with this wave, spawned or not.
+    pub fn remaining(&self) -> usize {
+        self.to_spawn.len() + self.alive.len()
+    }
+}
+
+/// Tallies for the end screens.
+#[derive(Debug, Clone, Copy)]
+pub struct RunStats {
+    pub kills: u32,
+    /// Seconds played.
+    pub time: f32,
+}
+
+impl RunStats {
+    pub fn new() -> RunStats {
+        RunStats {
+            kills: 0,
+            time: 0.0,
+        }
+    }
+}
+
+/// Which zombies make up a wave. Runners show up from wave 2 and brutes from wave 3.
+/// Waves that start after dark bring up to half again as many.
+pub fn wave_composition(wave: u32, darkness: f32) -> Vec<ZombieKind> {
+    const NIGHT_EXTRA: f32 = 0.5;
+    let total = ((4 + wave * 3) as f32 * (1.0 + NIGHT_EXTRA * darkness)).round() as u32;
+    let brutes = wave / 3;
+    let runners = if wave >= 2 { wave } else { 0 };
+    let walkers = total.saturating_sub(brutes + runners);
+
+    let mut kinds = Vec::new();
+    kinds.extend(std::iter::repeat_n(ZombieKind::Walker, walkers as usize));
+    kinds.extend(std::iter::repeat_n(ZombieKind::Runner, runners as usize));
+    kinds.extend(std::iter::repeat_n(ZombieKind::Brute, brutes as usize));
+
+    // shuffle so the tough ones don't all arrive at the end
+    for i in (1..kinds.len()).rev() {
+        let j = random_range(0..=i);
+        kinds.swap(i, j);
+    }
+    kinds
+}
+
+/// Sets up a zombie of the given kind on top of the base zombie.
+pub fn init_as_wave_zombie(entity: &mut Entity, kind: ZombieKind) {
+    init_as_zombie(entity);
+    match kind {
+        ZombieKind::Walker => {}
+        ZombieKind::Runner => {
+            entity.sprite = Some(Sprite::ZombieAngry);
+            entity.move_cooldown = 0.35;
+            entity.health = 25;
+            entity.max_hp = 25;
+            entity.loot = Some(LootSource::Runner);
+        }
+        ZombieKind::Brute => {
+            entity.move_cooldown = 1.2;
+            entity.attack_cooldown = 0.7;
+            entity.health = 120;
+            entity.max_hp = 120;
+            entity.size = Vec2::new(1.3, 1.3);
+            entity.can_be_stunned = false; // shrugs off bat hits
+            entity.resistances.set(DamageType::Blunt, 0.25);
+            entity.armor = 2;
+            entity.loot = Some(LootSource::Brute);
+        }
+    }
+    entity.move_cooldown_countdown = rand::random::<f32>() * entity.move_cooldown;
+}
+
+/// Advances the build/combat cycle, spawns the current wave and checks for victory.
+pub fn step_waves(state: &mut State, audio: &mut Audio) {
+    state.run_stats.time += TIMESTEP;
+
+    match state.waves.phase {
+        WavePhase::Build => {
+            state.waves.build_timer -= TIMESTEP;
+            if state.waves.build_timer <= 0.0 {
+                state.waves.phase = WavePhase::Combat;
+                state.waves.to_spawn = wave_composition(state.waves.wave, state.clock.darkness());
+                state.waves.spawn_timer = 0.0;
+                audio.play_sound_effect(SoundEffect::LevelStart);
+            }
+        }
+        WavePhase::Combat => {
+            // forget the ones that went down
+            let alive: Vec<VID> = state
+                .waves
+                .alive
+                .iter()
+                .copied()
+                .filter(|vid| {
+                    state
+                        .entity_manager
+                        .get_entity(*vid)
+                        .is_some_and(|e| e.active && !e.marked_for_destruction)
+                })
+                .collect();
+            state.waves.alive = alive;
+
+            // trickle the wave in
+            const SPAWN_INTERVAL: f32 = 1.5;
+            state.waves.spawn_timer -= TIMESTEP;
+            if state.waves.spawn_timer <= 0.0 && !state.waves.to_spawn.is_empty() {
+                let kind = state.waves.to_spawn[0];
+                if let Some(vid) = spawn_wave_zombie(state, kind) {
+                    state.waves.to_spawn.remove(0);
+                    state.waves.alive.push(vid);
+                }
+                state.waves.spawn_timer = SPAWN_INTERVAL;
+            }
+
+            // every so often the wave hears the player, so it can't just lose interest
+            const HUNT_INTERVAL: f32 = 10.0;
+            state.waves.hunt_timer -= TIMESTEP;
+            if state.waves.hunt_timer <= 0.0 {
+                hunt_player(state);
+                state.waves.hunt_timer = HUNT_INTERVAL;
+            }
+
+            if state.waves.remaining() == 0 {
+                end_wave(state, audio);
+            }
+        }
+    }
+}
+
+/// Sends every wave zombie that isn't already on the player's heels toward where the player is now.
+/// They still have to find the player once they get there, walls and all.
+fn hunt_player(state: &mut State) {
+    let player = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .map(|player| (player.vid, player.pos));
+    let (player_vid, player_pos) = match player {
+        Some(player) => player,
+        None => return,
+    };
+    for vid in state.waves.alive.clone() {
+        if let Some(zombie) = state.entity_manager.get_entity_mut(vid) {
+            if zombie.mood != Mood::ChasingTarget {
+                zombie.mood = Mood::LosingTarget;
+                zombie.target_entity = Some(player_vid);
+                zombie.target_pos = Some(player_pos);
+            }
+        }
+    }
+}
+
+/// Pays out for the wave just cleared. Chickens that made it through count as rescued.
+fn end_wave(state: &mut State, audio: &mut Audio) {
+    const POINTS_PER_WAVE: u32 = 50;
+    const POINTS_PER_CHICKEN: u32 = 5;
+    let chickens = state
+        .entity_manager
+        .iter()
+        .filter(|e| e.active && !e.marked_for_destruction && e.type_ == EntityType::Chicken)
+        .count() as u32;
+    award_points(
+        state,
+        POINTS_PER_WAVE * state.waves.wave + POINTS_PER_CHICKEN * chickens,
+    );
+
+    if state.waves.wave >= state.waves.total_waves {
+        state.waves.wave += 1;
+        state.win = true;
+        state.mode = Mode::Win;
+        audio.play_sound_effect(SoundEffect::LevelWin);
+        return;
+    }
+
+    const BUILD_PHASE_SECONDS: f32 = 30.0;
+    state.waves.wave += 1;
+    state.waves.phase = WavePhase::Build;
+    state.waves.build_timer = BUILD_PHASE_SECONDS;
+    audio.play_sound_effect(SoundEffect::SuperConfirm);
+}
+
+/// Drops a zombie on a free tile along the map edge, well away from the player, aimed at where the player is.
+fn spawn_wave_zombie(state: &mut State, kind: ZombieKind) -> Option<VID> {
+    const MIN_PLAYER_DISTANCE: i32 = 12;
+    const ATTEMPTS: usize = 50;
+
+    let width = state.stage.get_width() as i32;
+    let height = state.stage.get_height() as i32;
+    let player_pos = state
+        .player_vid
+        .and_then(|vid| state.entity_manager.get_entity(vid))
+        .map(|player| player.pos.as_ivec2());
+
+    let mut spawn_pos = None;
+    for _ in 0..ATTEMPTS {
+        let pos = match random_range(0..4) {
+            0 => IVec2::new(random_range(0..width), 0),
+            1 => IVec2::new(width - 1, random_range(0..height)),
+            2 => IVec2::new(random_range(0..width), height - 1),
+            _ => IVec2::new(0, random_range(0..height)),
+        };
+        let far_enough = player_pos
+            .is_none_or(|player_pos| new_york_dist(pos, player_pos) >= MIN_PLAYER_DISTANCE);
+        if far_enough && is_tile_walkable(state, pos) {
+            spawn_pos = Some(pos);
+            break;
+        }
+    }
+    let spawn_pos = spawn_pos?;
+
+    let vid = state.entity_manager.new_entity()?;
+    if let Some(zombie) = state.entity_manager.get_entity_mut(vid) {
+        init_as_wave_zombie(zombie, kind);
+        zombie.pos = spawn_pos.as_vec2() + Vec2::splat(0.5);
+        // head for the player, it'll pick up the scent properly once it has them in sight
+        if let (Some(player_vid), Some(player_pos)) = (state.player_vid, player_pos) {
+            zombie.mood = Mood::LosingTarget;
+            zombie.target_entity = Some(player_vid);
+            zombie.target_pos = Some(player_pos.as_vec2() + Vec2::splat(0.5));
+        }
+    }
+    state.add_entity_to_grid(vid, spawn_pos);
+    Some(vid)
+}
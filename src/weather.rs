@@ -0,0 +1,317 @@
+/* Weather.
+   The sky moves between a handful of kinds of weather, each lasting a couple of minutes.
+   What the rest of the game reads are the levels (clouds, rain, fog, wind, storm), which
+   drift toward whatever the current kind calls for instead of jumping, so rain builds up
+   and clears off gradually. Rain masks sounds and cuts how far the player can see, fog cuts
+   it more, rain douses fires out in the open and floods the mud by ponds, and storms bog
+   zombies down and throw lightning.
+*/
+
+use glam::{IVec2, Vec2};
+use rand::random_range;
+use raylib::color::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::{Audio, SoundEffect},
+    graphics::Graphics,
+    particle::{ParticleData, ParticleLayer},
+    sprite::Sprite,
+    stage::{TileData, TileLayer},
+    state::State,
+    step::{FRAMES_PER_SECOND, TIMESTEP},
+    tile::Tile,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherKind {
+    Clear,
+    Overcast,
+    Rain,
+    Storm,
+    Fog,
+}
+
+impl WeatherKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "Clear",
+            WeatherKind::Overcast => "Overcast",
+            WeatherKind::Rain => "Rain",
+            WeatherKind::Storm => "Storm",
+            WeatherKind::Fog => "Fog",
+        }
+    }
+
+    /// (clouds, rain, fog, wind, storm) this kind of weather settles at.
+    fn levels(self) -> (f32, f32, f32, f32, f32) {
+        match self {
+            WeatherKind::Clear => (0.2, 0.0, 0.0, 0.1, 0.0),
+            WeatherKind::Overcast => (0.8, 0.0, 0.0, 0.3, 0.0),
+            WeatherKind::Rain => (1.0, 0.6, 0.15, 0.4, 0.0),
+            WeatherKind::Storm => (1.0, 1.0, 0.25, 1.0, 1.0),
+            WeatherKind::Fog => (0.4, 0.0, 1.0, 0.0, 0.0),
+        }
+    }
+
+    /// Picks what comes next. Storms only ever come out of rain and wind back down into it.
+    fn next(self) -> WeatherKind {
+        let choices: &[(WeatherKind, f32)] = match self {
+            WeatherKind::Clear => &[(WeatherKind::Overcast, 0.6), (WeatherKind::Fog, 0.4)],
+            WeatherKind::Overcast => &[
+                (WeatherKind::Clear, 0.4),
+                (WeatherKind::Rain, 0.45),
+                (WeatherKind::Fog, 0.15),
+            ],
+            WeatherKind::Rain => &[
+                (WeatherKind::Overcast, 0.5),
+                (WeatherKind::Storm, 0.3),
+                (WeatherKind::Clear, 0.2),
+            ],
+            WeatherKind::Storm => &[(WeatherKind::Rain, 1.0)],
+            WeatherKind::Fog => &[(WeatherKind::Clear, 0.6), (WeatherKind::Overcast, 0.4)],
+        };
+        let mut roll = random_range(0.0..1.0);
+        for &(kind, chance) in choices {
+            if roll < chance {
+                return kind;
+            }
+            roll -= chance;
+        }
+        choices[choices.len() - 1].0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    /// Seconds until the weather moves on.
+    pub timer: f32,
+
+    // Each 0.0 to 1.0, easing toward what `kind` calls for.
+    pub clouds: f32,
+    pub rain: f32,
+    pub fog: f32,
+    pub wind: f32,
+    pub storm: f32,
+
+    /// Brightness of the last lightning strike, fades out over a moment.
+    #[serde(skip)]
+    pub lightning: f32,
+}
+
+impl Weather {
+    pub fn new() -> Weather {
+        let (clouds, rain, fog, wind, storm) = WeatherKind::Clear.levels();
+        Weather {
+            kind: WeatherKind::Clear,
+            timer: 120.0,
+            clouds,
+            rain,
+            fog,
+            wind,
+            storm,
+            lightning: 0.0,
+        }
+    }
+}
+
+/// How far the player can see compared to a clear day.
+pub fn visibility_multiplier(weather: &Weather) -> f32 {
+    (1.0 - 0.5 * weather.fog - 0.25 * weather.rain).max(0.35)
+}
+
+/// How far sounds carry compared to a quiet day. Rain drowns them out.
+pub fn hearing_multiplier(weather: &Weather) -> f32 {
+    1.0 - 0.4 * weather.rain
+}
+
+/// Zombies wade through storms.
+pub fn zombie_speed_multiplier(weather: &Weather) -> f32 {
+    1.0 - 0.35 * weather.storm
+}
+
+/// Gray haze for fog and rain, laid over the world like the time of day tint.
+pub fn weather_tint(weather: &Weather) -> Color {
+    const MAX_FOG_ALPHA: f32 = 150.0;
+    const MAX_RAIN_ALPHA: f32 = 50.0;
+    let alpha = (weather.fog * MAX_FOG_ALPHA + weather.rain * MAX_RAIN_ALPHA).min(200.0);
+    Color::new(150, 155, 165, alpha as u8)
+}
+
+/// Full screen flash from a lightning strike.
+pub fn lightning_flash(weather: &Weather) -> Color {
+    Color::new(235, 240, 255, (weather.lightning * 180.0) as u8)
+}
+
+/// Moves the weather along and applies it to the world.
+pub fn step_weather(state: &mut State, audio: &mut Audio, graphics: &Graphics) {
+    const TRANSITION_RATE: f32 = 0.05; // per second, so about 20s to fully change over
+    const MIN_DURATION: f32 = 60.0;
+    const MAX_DURATION: f32 = 150.0;
+
+    let weather = &mut state.weather;
+    weather.timer -= TIMESTEP;
+    if weather.timer <= 0.0 {
+        weather.kind = weather.kind.next();
+        weather.timer = random_range(MIN_DURATION..MAX_DURATION);
+    }
+
+    let (clouds, rain, fog, wind, storm) = weather.kind.levels();
+    let step = TRANSITION_RATE * TIMESTEP;
+    for (level, target) in [
+        (&mut weather.clouds, clouds),
+        (&mut weather.rain, rain),
+        (&mut weather.fog, fog),
+        (&mut weather.wind, wind),
+        (&mut weather.storm, storm),
+    ] {
+        *level += (target - *level).clamp(-step, step);
+    }
+    state.cloud_density = weather.clouds;
+
+    // lightning
+    const LIGHTNING_CHANCE: f32 = 0.003;
+    weather.lightning = (weather.lightning - 4.0 * TIMESTEP).max(0.0);
+    if random_range(0.0..1.0) < weather.storm * LIGHTNING_CHANCE {
+        weather.lightning = 1.0;
+        audio.play_sound_effect_scaled(SoundEffect::Explosion2, 0.5);
+    }
+
+    spawn_rain(state, graphics);
+
+    // the slow stuff doesn't need to happen every frame
+    const SOAK_INTERVAL: u32 = 30;
+    if state.frame % SOAK_INTERVAL == 0 {
+        soak_stage(state);
+    }
+}
+
+/// Drops falling across the screen, blown sideways by the wind.
+fn spawn_rain(state: &mut State, graphics: &Graphics) {
+    const MAX_DROPS_PER_FRAME: f32 = 6.0;
+    const FALL_SPEED: f32 = 0.35;
+    const WIND_PUSH: f32 = 0.2;
+
+    let rain = state.weather.rain;
+    if rain <= 0.0 {
+        return;
+    }
+    let top_left = graphics.screen_to_world(Vec2::ZERO);
+    let bottom_right = graphics.screen_to_world(graphics.window_dims.as_vec2());
+    let vel = Vec2::new(state.weather.wind * WIND_PUSH, FALL_SPEED);
+    // lean the streak into the direction it's falling
+    let rot = -vel.x.atan2(vel.y).to_degrees();
+
+    let mut drops = rain * MAX_DROPS_PER_FRAME;
+    while drops > 0.0 {
+        if drops < 1.0 && random_range(0.0..1.0) > drops {
+            break;
+        }
+        drops -= 1.0;
+
+        // start above and upwind of the view so the edges fill in too
+        let lifetime = random_range(20..40);
+        let spawn_pos = Vec2::new(
+            random_range(top_left.x - vel.x * lifetime as f32..bottom_right.x),
+            random_range(top_left.y - FALL_SPEED * 40.0..bottom_right.y),
+        );
+        let particle_data = ParticleData::new(
+            spawn_pos,
+            Vec2::new(4.0, 6.0),
+            rot,
+            random_range(0.3..0.6),
+            lifetime,
+            Sprite::RainDrop,
+            ParticleLayer::Foreground,
+        );
+        state.particles.spawn_dynamic(particle_data, vel, 0.0);
+    }
+}
+
+/// Rain puts out fires out in the open and lets ponds spread into the mud around them as
+/// puddles, sun dries the puddles up again.
+fn soak_stage(state: &mut State) {
+    const DOUSE_CHANCE: f32 = 0.02;
+    const FLOOD_CHANCE: f32 = 0.03;
+    const DRY_CHANCE: f32 = 0.02;
+
+    let rain = state.weather.rain;
+    let dry = rain <= 0.0 && state.weather.clouds < 0.5;
+    if rain <= 0.0 && !dry {
+        return;
+    }
+
+    for x in 0..state.stage.get_width() {
+        for y in 0..state.stage.get_height() {
+            // fires out in the rain go out, the ones indoors stay lit
+            let structure = state.stage.get_layer_tile(TileLayer::Structure, x, y);
+            if structure.is_some_and(|td| matches!(td.tile, Tile::Torch | Tile::Campfire))
+                && random_range(0.0..1.0) < rain * DOUSE_CHANCE
+                && !is_sheltered(state, IVec2::new(x as i32, y as i32))
+            {
+                let mut ashes = TileData::default();
+                ashes.tile = Tile::Ashes;
+                state.stage.set_tile(x, y, ashes);
+                continue;
+            }
+
+            let floor = match state.stage.get_layer_tile(TileLayer::Floor, x, y) {
+                Some(td) => td,
+                None => continue,
+            };
+            if floor.tile == Tile::Mud
+                && random_range(0.0..1.0) < rain * FLOOD_CHANCE
+                && next_to_water(state, IVec2::new(x as i32, y as i32))
+                && structure.is_some_and(|td| td.tile.empty())
+                && state.spatial_grid[x][y].is_empty()
+            {
+                let mut puddle = TileData::default();
+                puddle.tile = Tile::Water;
+                puddle.variant = random_range(0..2);
+                puddle.flip_speed = FRAMES_PER_SECOND as u16;
+                puddle.switched = true;
+                state.stage.set_tile(x, y, puddle);
+            } else if dry
+                && floor.tile == Tile::Water
+                && floor.switched
+                && random_range(0.0..1.0) < DRY_CHANCE
+            {
+                let mut mud = TileData::default();
+                mud.tile = Tile::Mud;
+                state.stage.set_tile(x, y, mud);
+            }
+        }
+    }
+}
+
+/// Next to a pond. Puddles don't count, or they'd keep spreading across the mud.
+fn next_to_water(state: &State, pos: IVec2) -> bool {
+    [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+        .iter()
+        .any(|&dir| {
+            let n = pos + dir;
+            state.stage.in_bounds(n)
+                && state
+                    .stage
+                    .get_layer_tile(TileLayer::Floor, n.x as usize, n.y as usize)
+                    .is_some_and(|td| td.tile == Tile::Water && !td.switched)
+        })
+}
+
+/// Walled in on all four sides close by, like inside a house.
+fn is_sheltered(state: &State, pos: IVec2) -> bool {
+    const SHELTER_REACH: i32 = 5;
+    [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+        .iter()
+        .all(|&dir| {
+            (1..=SHELTER_REACH).any(|i| {
+                let n = pos + dir * i;
+                state.stage.in_bounds(n)
+                    && state
+                        .stage
+                        .get_layer_tile(TileLayer::Structure, n.x as usize, n.y as usize)
+                        .is_some_and(|td| matches!(td.tile, Tile::Wall | Tile::Door))
+            })
+        })
+}